@@ -1,17 +1,50 @@
 use super::common::{GENERATED_HEADER, is_void_input};
-use super::typescript::{emit_jsdoc, rust_type_to_ts};
-use crate::model::{Manifest, ProcedureKind};
+use super::typescript::{emit_jsdoc, output_to_ts, rust_type_to_ts};
+use crate::model::{Manifest, Procedure, ProcedureKind, StreamFormat};
 
 /// Standard RPC error class with status code and structured error data.
-const ERROR_CLASS: &str = r#"export class RpcError extends Error {
+///
+/// `E` is the procedure's typed error (`Result<T, E>` on the Rust side), read
+/// from the `error.data` field of the response body into `payload`. Validation
+/// failures (422) carry per-field messages in `error.fields`, exposed as `fieldErrors`.
+/// Every error envelope carries a stable `error.code`, exposed as `code` and
+/// typed by the exported `RpcErrorCode` union.
+const ERROR_CLASS: &str = r#"export type RpcErrorCode =
+  | "BAD_INPUT"
+  | "INPUT_TOO_COMPLEX"
+  | "PAYLOAD_TOO_LARGE"
+  | "UNSUPPORTED_MEDIA_TYPE"
+  | "METHOD_NOT_ALLOWED"
+  | "VALIDATION_FAILED"
+  | "RATE_LIMITED"
+  | "FORBIDDEN"
+  | "NOT_FOUND"
+  | "TIMEOUT"
+  | "HANDLER_ERROR"
+  | "INTERNAL";
+
+export interface RpcFieldError {
+  path: string;
+  message: string;
+}
+
+export class RpcError<E = unknown> extends Error {
   readonly status: number;
   readonly data: unknown;
+  readonly payload: E | undefined;
+  readonly fieldErrors: RpcFieldError[];
+  // `undefined` when the response carried no error envelope, e.g. a proxy error page.
+  readonly code: RpcErrorCode | undefined;
 
   constructor(status: number, message: string, data?: unknown) {
     super(message);
     this.name = "RpcError";
     this.status = status;
     this.data = data;
+    const error = (data as { error?: { data?: E; fields?: RpcFieldError[]; code?: RpcErrorCode } } | null | undefined)?.error;
+    this.payload = error?.data;
+    this.fieldErrors = error?.fields ?? [];
+    this.code = error?.code;
   }
 }"#;
 
@@ -51,6 +84,14 @@ const RETRY_POLICY_INTERFACE: &str = r#"export interface RetryPolicy {
   retryOn?: number[];
 }"#;
 
+/// Reconnect policy for streams that send event ids.
+const RECONNECT_POLICY_INTERFACE: &str = r#"export interface ReconnectPolicy {
+  // Reconnects in a row without receiving an event. Defaults to 3.
+  attempts?: number;
+  // Backoff in ms before each reconnect. Defaults to 500, doubling every attempt.
+  delay?: number | ((attempt: number) => number);
+}"#;
+
 /// Configuration interface for the RPC client.
 const CONFIG_INTERFACE: &str = r#"export interface RpcClientConfig {
   baseUrl: string;
@@ -68,6 +109,55 @@ const CONFIG_INTERFACE: &str = r#"export interface RpcClientConfig {
   // AbortSignal for cancelling all requests made by this client.
   signal?: AbortSignal;
   dedupe?: boolean;
+  // Sends `traceparent` and `x-request-id`: `true` starts a new trace per call.
+  trace?: boolean | ((procedure: string) => TraceContext);
+  // Sends queries made close together as one `POST /_batch` (router mode only).
+  batch?: BatchOptions;
+  // Longest query URL before the input is sent as a `POST` body instead. Defaults to 2000.
+  maxQueryLength?: number;
+  // `"base64url"` sends query input as `?input64=`, shorter than percent-encoded JSON.
+  queryEncoding?: "json" | "base64url";
+  // `"msgpack"` sends and accepts MessagePack bodies (needs the server's `msgpack` feature).
+  // Query input in the URL, batches and streams stay JSON.
+  format?: "json" | "msgpack";
+  // Resumes a dropped stream that sends event ids, with `Last-Event-ID`. `false` disables it.
+  reconnect?: ReconnectPolicy | false;
+  // Opens `method = "GET"` SSE streams with `EventSource`, which reconnects on its own.
+  // `EventSource` cannot send headers, so `headers` and `trace` do not apply.
+  eventSource?: boolean;
+}"#;
+
+/// Batching options: how many queries to collect and for how long.
+const BATCH_OPTIONS_INTERFACE: &str = r#"export interface BatchOptions {
+  // Most calls in one request; a full batch is sent right away. Defaults to 10.
+  maxSize?: number;
+  // How long to collect calls, in ms. Defaults to 0: calls made in the same tick.
+  windowMs?: number;
+}"#;
+
+/// W3C trace context sent with each request when `trace` is configured.
+const TRACE_CONTEXT_INTERFACE: &str = r#"export interface TraceContext {
+  traceparent?: string;
+  requestId?: string;
+}"#;
+
+/// Builds trace headers for a call; `newTraceContext` starts a fresh sampled trace.
+const TRACE_HELPERS: &str = r#"export function newTraceContext(): TraceContext {
+  const hex = (bytes: number) => Array.from(
+    crypto.getRandomValues(new Uint8Array(bytes)),
+    (b) => b.toString(16).padStart(2, "0"),
+  ).join("");
+  const traceId = hex(16);
+  return { traceparent: `00-${traceId}-${hex(8)}-01`, requestId: traceId };
+}
+
+function traceHeaders(config: RpcClientConfig, procedure: string): Record<string, string> {
+  if (!config.trace) return {};
+  const ctx = config.trace === true ? newTraceContext() : config.trace(procedure);
+  const headers: Record<string, string> = {};
+  if (ctx.traceparent) headers["traceparent"] = ctx.traceparent;
+  if (ctx.requestId) headers["x-request-id"] = ctx.requestId;
+  return headers;
 }"#;
 
 /// Per-call options that override client-level defaults for a single request.
@@ -76,6 +166,8 @@ const CALL_OPTIONS_INTERFACE: &str = r#"export interface CallOptions {
   timeout?: number;
   signal?: AbortSignal;
   dedupe?: boolean;
+  reconnect?: ReconnectPolicy | false;
+  eventSource?: boolean;
 }"#;
 
 /// Computes a dedup map key from procedure name and serialized input.
@@ -102,28 +194,313 @@ const WRAP_WITH_SIGNAL_FN: &str = r#"function wrapWithSignal<T>(promise: Promise
   });
 }"#;
 
+/// Collects queries into batches sent to the router's `/_batch` endpoint.
+///
+/// A call whose signal aborts before the batch is sent is dropped from it, and
+/// one that aborts later is rejected without waiting for the response; a lone
+/// call is sent as a regular query with its signal.
+const BATCH_HELPER: &str = r#"interface BatchedCall {
+  procedure: string;
+  input: unknown;
+  signal?: AbortSignal;
+  resolve: (value: unknown) => void;
+  reject: (reason: unknown) => void;
+}
+
+function createBatcher(config: RpcClientConfig) {
+  const maxSize = config.batch?.maxSize ?? 10;
+  const windowMs = config.batch?.windowMs ?? 0;
+  let queue: BatchedCall[] = [];
+  let timer: ReturnType<typeof setTimeout> | undefined;
+
+  const flush = () => {
+    clearTimeout(timer);
+    timer = undefined;
+    const calls = queue;
+    queue = [];
+    if (calls.length === 1) {
+      const [call] = calls;
+      rpcFetch(config, "GET", call.procedure, call.input, { signal: call.signal }).then(call.resolve, call.reject);
+    } else if (calls.length > 1) {
+      void rpcBatch(config, calls);
+    }
+  };
+
+  return (procedure: string, input: unknown, signal?: AbortSignal): Promise<unknown> =>
+    new Promise((resolve, reject) => {
+      if (signal?.aborted) return reject(signal.reason);
+      const onAbort = () => {
+        const index = queue.indexOf(call);
+        if (index !== -1) queue.splice(index, 1);
+        reject(signal?.reason);
+      };
+      const call: BatchedCall = {
+        procedure,
+        input,
+        signal,
+        resolve: (value) => { signal?.removeEventListener("abort", onAbort); resolve(value); },
+        reject: (reason) => { signal?.removeEventListener("abort", onAbort); reject(reason); },
+      };
+      signal?.addEventListener("abort", onAbort, { once: true });
+      queue.push(call);
+      if (queue.length >= maxSize) flush();
+      else timer ??= setTimeout(flush, windowMs);
+    });
+}
+
+async function rpcBatch(config: RpcClientConfig, calls: BatchedCall[]): Promise<void> {
+  const url = `${config.baseUrl}/_batch`;
+  const items = calls.map(({ procedure, input }) => ({ procedure, input }));
+  let timeoutId: ReturnType<typeof setTimeout> | undefined;
+  const start = Date.now();
+
+  try {
+    const customHeaders = typeof config.headers === "function"
+      ? await config.headers()
+      : config.headers;
+    const reqCtx: RequestContext = {
+      procedure: "_batch",
+      method: "POST",
+      url,
+      headers: { ...traceHeaders(config, "_batch"), ...customHeaders, "Content-Type": "application/json" },
+      input: items,
+    };
+    await config.onRequest?.(reqCtx);
+
+    const init: RequestInit = {
+      method: "POST",
+      headers: reqCtx.headers,
+      body: config.serialize ? config.serialize(items) : JSON.stringify(items),
+    };
+    const signals: AbortSignal[] = [];
+    if (config.signal) signals.push(config.signal);
+    // The batch waits for its slowest call, so it gets the longest of their timeouts.
+    const timeouts = calls.map((call) => PROCEDURE_TIMEOUTS[call.procedure] ?? config.timeout);
+    if (timeouts.every((t): t is number => t !== undefined)) {
+      const controller = new AbortController();
+      timeoutId = setTimeout(() => controller.abort(), Math.max(...timeouts));
+      signals.push(controller.signal);
+    }
+    if (signals.length > 0) {
+      init.signal = signals.length === 1 ? signals[0] : AbortSignal.any(signals);
+    }
+
+    const res = await (config.fetch ?? globalThis.fetch)(url, init);
+    if (!res.ok) {
+      let data: unknown;
+      try { data = await res.json(); } catch { data = null; }
+      throw new RpcError(res.status, `RPC batch error: ${res.status} ${res.statusText}`, data);
+    }
+    const results = (config.deserialize ? config.deserialize(await res.text()) : await res.json()) as
+      { status: number; result?: { data: unknown }; error?: unknown }[];
+    const duration = Date.now() - start;
+
+    for (const [i, call] of calls.entries()) {
+      const item = results[i];
+      const ctx = { procedure: call.procedure, method: "GET" as const, url };
+      if (item && item.error === undefined) {
+        const data = item.result?.data;
+        await config.onResponse?.({ ...ctx, response: res, data, duration });
+        call.resolve(data);
+      } else {
+        const status = item?.status ?? 500;
+        const error = new RpcError(status, `RPC error on "${call.procedure}": ${status}`, item);
+        await config.onError?.({ ...ctx, error, attempt: 1, willRetry: false });
+        call.reject(error);
+      }
+    }
+  } catch (err) {
+    for (const call of calls) {
+      await config.onError?.({ procedure: call.procedure, method: "GET", url, error: err, attempt: 1, willRetry: false });
+      call.reject(err);
+    }
+  } finally {
+    if (timeoutId !== undefined) clearTimeout(timeoutId);
+  }
+}"#;
+
+/// MessagePack codec for JSON-compatible values, used when `format` is `"msgpack"`.
+const MSGPACK_CODEC: &str = r#"function encodeMsgpack(value: unknown): ArrayBuffer {
+  const bytes: number[] = [];
+  const scratch = new DataView(new ArrayBuffer(8));
+  const text = new TextEncoder();
+  const push = (type: number, size: number, set: (view: DataView) => void) => {
+    set(scratch);
+    bytes.push(type, ...Array.from(new Uint8Array(scratch.buffer, 0, size)));
+  };
+  const length = (n: number, fix: number, fixMax: number, code16: number, code8?: number) => {
+    if (n < fixMax) bytes.push(fix | n);
+    else if (code8 !== undefined && n < 0x100) push(code8, 1, (d) => d.setUint8(0, n));
+    else if (n < 0x10000) push(code16, 2, (d) => d.setUint16(0, n));
+    else push(code16 + 1, 4, (d) => d.setUint32(0, n));
+  };
+  const write = (v: unknown): void => {
+    if (v === null || v === undefined) bytes.push(0xc0);
+    else if (typeof v === "boolean") bytes.push(v ? 0xc3 : 0xc2);
+    else if (typeof v === "number") {
+      if (!Number.isSafeInteger(v)) push(0xcb, 8, (d) => d.setFloat64(0, v));
+      else if (v >= 0 && v < 0x80) bytes.push(v);
+      else if (v < 0 && v >= -32) bytes.push(v + 0x100);
+      else if (v >= 0 && v < 0x100) push(0xcc, 1, (d) => d.setUint8(0, v));
+      else if (v >= 0 && v < 0x10000) push(0xcd, 2, (d) => d.setUint16(0, v));
+      else if (v >= 0 && v < 0x100000000) push(0xce, 4, (d) => d.setUint32(0, v));
+      else if (v >= 0) push(0xcf, 8, (d) => d.setBigUint64(0, BigInt(v)));
+      else if (v >= -0x80) push(0xd0, 1, (d) => d.setInt8(0, v));
+      else if (v >= -0x8000) push(0xd1, 2, (d) => d.setInt16(0, v));
+      else if (v >= -0x80000000) push(0xd2, 4, (d) => d.setInt32(0, v));
+      else push(0xd3, 8, (d) => d.setBigInt64(0, BigInt(v)));
+    } else if (typeof v === "bigint") {
+      if (v < 0) push(0xd3, 8, (d) => d.setBigInt64(0, v));
+      else push(0xcf, 8, (d) => d.setBigUint64(0, v));
+    } else if (typeof v === "string") {
+      const utf8 = text.encode(v);
+      length(utf8.length, 0xa0, 32, 0xda, 0xd9);
+      utf8.forEach((b) => bytes.push(b));
+    } else if (Array.isArray(v)) {
+      length(v.length, 0x90, 16, 0xdc);
+      v.forEach((item) => write(item));
+    } else if (typeof v === "object") {
+      const toJSON = (v as { toJSON?: () => unknown }).toJSON;
+      if (typeof toJSON === "function") return write(toJSON.call(v));
+      const entries = Object.entries(v).filter(([, item]) => item !== undefined);
+      length(entries.length, 0x80, 16, 0xde);
+      entries.forEach(([key, item]) => { write(key); write(item); });
+    } else {
+      throw new TypeError(`Cannot encode ${typeof v} as MessagePack`);
+    }
+  };
+  write(value);
+  const out = new ArrayBuffer(bytes.length);
+  new Uint8Array(out).set(bytes);
+  return out;
+}
+
+function decodeMsgpack(buffer: ArrayBuffer): unknown {
+  const view = new DataView(buffer);
+  const text = new TextDecoder();
+  let pos = 0;
+  const take = <T>(size: number, get: (offset: number) => T): T => {
+    const value = get(pos);
+    pos += size;
+    return value;
+  };
+  const str = (n: number) => take(n, (o) => text.decode(new Uint8Array(buffer, o, n)));
+  const bin = (n: number) => take(n, (o) => new Uint8Array(buffer.slice(o, o + n)));
+  const array = (n: number) => Array.from({ length: n }, () => read());
+  const map = (n: number) => {
+    const obj: Record<string, unknown> = {};
+    for (let i = 0; i < n; i++) {
+      const key = String(read());
+      obj[key] = read();
+    }
+    return obj;
+  };
+  const read = (): unknown => {
+    const type = take(1, (o) => view.getUint8(o));
+    if (type < 0x80) return type;
+    if (type < 0x90) return map(type & 0x0f);
+    if (type < 0xa0) return array(type & 0x0f);
+    if (type < 0xc0) return str(type & 0x1f);
+    if (type >= 0xe0) return type - 0x100;
+    switch (type) {
+      case 0xc0: return null;
+      case 0xc2: return false;
+      case 0xc3: return true;
+      case 0xc4: return bin(take(1, (o) => view.getUint8(o)));
+      case 0xc5: return bin(take(2, (o) => view.getUint16(o)));
+      case 0xc6: return bin(take(4, (o) => view.getUint32(o)));
+      case 0xca: return take(4, (o) => view.getFloat32(o));
+      case 0xcb: return take(8, (o) => view.getFloat64(o));
+      case 0xcc: return take(1, (o) => view.getUint8(o));
+      case 0xcd: return take(2, (o) => view.getUint16(o));
+      case 0xce: return take(4, (o) => view.getUint32(o));
+      case 0xcf: return take(8, (o) => Number(view.getBigUint64(o)));
+      case 0xd0: return take(1, (o) => view.getInt8(o));
+      case 0xd1: return take(2, (o) => view.getInt16(o));
+      case 0xd2: return take(4, (o) => view.getInt32(o));
+      case 0xd3: return take(8, (o) => Number(view.getBigInt64(o)));
+      case 0xd9: return str(take(1, (o) => view.getUint8(o)));
+      case 0xda: return str(take(2, (o) => view.getUint16(o)));
+      case 0xdb: return str(take(4, (o) => view.getUint32(o)));
+      case 0xdc: return array(take(2, (o) => view.getUint16(o)));
+      case 0xdd: return array(take(4, (o) => view.getUint32(o)));
+      case 0xde: return map(take(2, (o) => view.getUint16(o)));
+      case 0xdf: return map(take(4, (o) => view.getUint32(o)));
+      default: throw new Error(`Unsupported MessagePack type 0x${type.toString(16)}`);
+    }
+  };
+  return read();
+}
+
+function isMsgpack(res: Response): boolean {
+  return res.headers.get("Content-Type")?.startsWith("application/msgpack") ?? false;
+}"#;
+
 /// Internal fetch helper shared by query and mutate methods.
 const FETCH_HELPER: &str = r#"const DEFAULT_RETRY_ON = [408, 429, 500, 502, 503, 504];
 
+// Last `ETag` and data of an `etag` query, resolved again when the server answers 304.
+interface EtagEntry {
+  etag: string;
+  data: unknown;
+}
+
+const ETAG_CACHE_LIMIT = 100;
+
+const DEFAULT_MAX_QUERY_LENGTH = 2000;
+
+function base64url(text: string): string {
+  const binary = Array.from(new TextEncoder().encode(text), (b) => String.fromCharCode(b)).join("");
+  return btoa(binary).replace(/\+/g, "-").replace(/\//g, "_").replace(/=+$/, "");
+}
+
 async function rpcFetch(
   config: RpcClientConfig,
   method: "GET" | "POST",
   procedure: string,
   input?: unknown,
   callOptions?: CallOptions,
+  etagCache?: { entries: Map<string, EtagEntry>; key: string },
 ): Promise<unknown> {
   let url = `${config.baseUrl}/${procedure}`;
   const customHeaders = typeof config.headers === "function"
     ? await config.headers()
     : config.headers;
-  const baseHeaders: Record<string, string> = { ...customHeaders, ...callOptions?.headers };
+  const baseHeaders: Record<string, string> = {
+    ...traceHeaders(config, procedure),
+    ...customHeaders,
+    ...callOptions?.headers,
+  };
+  const msgpack = config.format === "msgpack";
+  if (msgpack) baseHeaders["Accept"] = "application/msgpack";
+  const contentType = msgpack ? "application/msgpack" : "application/json";
 
+  let httpMethod = method;
+  let body: BodyInit | undefined;
   if (method === "GET" && input !== undefined) {
     const serialized = config.serialize ? config.serialize(input) : JSON.stringify(input);
-    url += `?input=${encodeURIComponent(serialized)}`;
+    const query = config.queryEncoding === "base64url"
+      ? `?input64=${base64url(serialized)}`
+      : `?input=${encodeURIComponent(serialized)}`;
+    if (url.length + query.length <= (config.maxQueryLength ?? DEFAULT_MAX_QUERY_LENGTH)) {
+      url += query;
+    } else {
+      // Too long for a URL: the query reads its input from a marked POST body instead.
+      // Nothing caches a POST, so the ETag cache is skipped as well.
+      httpMethod = "POST";
+      body = msgpack ? encodeMsgpack(input) : serialized;
+      baseHeaders["Content-Type"] = contentType;
+      baseHeaders["X-Metaxy-Input"] = "body";
+      etagCache = undefined;
+    }
   } else if (method === "POST" && input !== undefined) {
-    baseHeaders["Content-Type"] = "application/json";
+    baseHeaders["Content-Type"] = contentType;
+    body = msgpack
+      ? encodeMsgpack(input)
+      : config.serialize ? config.serialize(input) : JSON.stringify(input);
   }
+  const cached = etagCache?.entries.get(etagCache.key);
+  if (cached) baseHeaders["If-None-Match"] = cached.etag;
 
   const fetchFn = config.fetch ?? globalThis.fetch;
   const maxAttempts = 1 + (config.retry?.attempts ?? 0);
@@ -132,13 +509,11 @@ async function rpcFetch(
   const start = Date.now();
 
   for (let attempt = 1; attempt <= maxAttempts; attempt++) {
-    const reqCtx: RequestContext = { procedure, method, url, headers: { ...baseHeaders }, input };
+    const reqCtx: RequestContext = { procedure, method: httpMethod, url, headers: { ...baseHeaders }, input };
     await config.onRequest?.(reqCtx);
 
-    const init: RequestInit = { method, headers: reqCtx.headers };
-    if (method === "POST" && input !== undefined) {
-      init.body = config.serialize ? config.serialize(input) : JSON.stringify(input);
-    }
+    const init: RequestInit = { method: httpMethod, headers: reqCtx.headers };
+    if (body !== undefined) init.body = body;
 
     let timeoutId: ReturnType<typeof setTimeout> | undefined;
     const signals: AbortSignal[] = [];
@@ -154,14 +529,19 @@ async function rpcFetch(
     }
 
     const isRetryable = attempt < maxAttempts && (method === "GET" || IDEMPOTENT_MUTATIONS.has(procedure));
+    let retryAfterMs = 0;
 
     try {
       const res = await fetchFn(url, init);
 
-      if (!res.ok) {
+      if (res.status === 304 && cached) {
+        const duration = Date.now() - start;
+        await config.onResponse?.({ procedure, method: httpMethod, url, response: res, data: cached.data, duration });
+        return cached.data;
+      } else if (!res.ok) {
         let data: unknown;
         try {
-          data = await res.json();
+          data = isMsgpack(res) ? decodeMsgpack(await res.arrayBuffer()) : await res.json();
         } catch {
           data = await res.text().catch(() => null);
         }
@@ -170,19 +550,37 @@ async function rpcFetch(
           `RPC error on "${procedure}": ${res.status} ${res.statusText}`,
           data,
         );
-        const canRetry = retryOn.includes(res.status) && isRetryable;
-        await config.onError?.({ procedure, method, url, error: rpcError, attempt, willRetry: canRetry });
+        // A rate-limited request is rejected before the handler runs, so any procedure may retry it.
+        const rateLimited = res.status === 429 && RATE_LIMITED_PROCEDURES.has(procedure);
+        const canRetry = retryOn.includes(res.status)
+          && (isRetryable || (rateLimited && attempt < maxAttempts));
+        if (canRetry && rateLimited) {
+          retryAfterMs = (Number(res.headers.get("Retry-After")) || 0) * 1000;
+        }
+        await config.onError?.({ procedure, method: httpMethod, url, error: rpcError, attempt, willRetry: canRetry });
         if (!canRetry) throw rpcError;
       } else {
-        const json = config.deserialize ? config.deserialize(await res.text()) : await res.json();
+        const json = isMsgpack(res)
+          ? decodeMsgpack(await res.arrayBuffer())
+          : config.deserialize ? config.deserialize(await res.text()) : await res.json();
         const result = json?.result?.data ?? json;
+        if (etagCache) {
+          const etag = res.headers.get("ETag");
+          etagCache.entries.delete(etagCache.key);
+          if (etag) {
+            etagCache.entries.set(etagCache.key, { etag, data: result });
+            if (etagCache.entries.size > ETAG_CACHE_LIMIT) {
+              etagCache.entries.delete(etagCache.entries.keys().next().value!);
+            }
+          }
+        }
         const duration = Date.now() - start;
-        await config.onResponse?.({ procedure, method, url, response: res, data: result, duration });
+        await config.onResponse?.({ procedure, method: httpMethod, url, response: res, data: result, duration });
         return result;
       }
     } catch (err) {
       if (err instanceof RpcError) throw err;
-      await config.onError?.({ procedure, method, url, error: err, attempt, willRetry: isRetryable });
+      await config.onError?.({ procedure, method: httpMethod, url, error: err, attempt, willRetry: isRetryable });
       if (!isRetryable) throw err;
     } finally {
       if (timeoutId !== undefined) clearTimeout(timeoutId);
@@ -191,33 +589,50 @@ async function rpcFetch(
     if (config.retry) {
       const d = typeof config.retry.delay === "function"
         ? config.retry.delay(attempt) : config.retry.delay;
-      await new Promise(r => setTimeout(r, d));
+      await new Promise(r => setTimeout(r, Math.max(d, retryAfterMs)));
     }
   }
 }"#;
 
-/// Internal SSE stream helper for the `stream()` method.
+/// Internal stream helper for the `stream()` method.
+///
+/// For SSE streams, yields every data event, returns the value of a `done`
+/// event and throws an `error` event as an `RpcError` carrying its status and
+/// typed payload. NDJSON streams (per `STREAM_FORMATS`) yield every parsed
+/// line and byte streams every `Uint8Array` chunk as it arrives. Streams in
+/// `GET_STREAMS` send their input as `?input=` and, with `eventSource`, are
+/// read through `rpcEventSource`.
 const STREAM_HELPER: &str = r#"async function* rpcStream<T>(
   config: RpcClientConfig,
   procedure: string,
   input?: unknown,
   callOptions?: CallOptions,
-): AsyncGenerator<T> {
+): AsyncGenerator<T, unknown> {
   let url = `${config.baseUrl}/${procedure}`;
+  const method = GET_STREAMS.has(procedure) ? "GET" : "POST";
   const customHeaders = typeof config.headers === "function"
     ? await config.headers()
     : config.headers;
   const headers: Record<string, string> = {
-    "Content-Type": "application/json",
+    ...(method === "POST" && { "Content-Type": "application/json" }),
+    ...traceHeaders(config, procedure),
     ...customHeaders,
     ...callOptions?.headers,
   };
 
   const fetchFn = config.fetch ?? globalThis.fetch;
-  const init: RequestInit = { method: "POST", headers };
+  const init: RequestInit = { method, headers };
   if (input !== undefined) {
-    init.body = config.serialize ? config.serialize(input) : JSON.stringify(input);
+    const serialized = config.serialize ? config.serialize(input) : JSON.stringify(input);
+    if (method === "GET") {
+      url += config.queryEncoding === "base64url"
+        ? `?input64=${base64url(serialized)}`
+        : `?input=${encodeURIComponent(serialized)}`;
+    } else {
+      init.body = serialized;
+    }
   }
+  const format = STREAM_FORMATS[procedure] ?? "sse";
 
   const signals: AbortSignal[] = [];
   if (config.signal) signals.push(config.signal);
@@ -233,55 +648,181 @@ const STREAM_HELPER: &str = r#"async function* rpcStream<T>(
     init.signal = signals.length === 1 ? signals[0] : AbortSignal.any(signals);
   }
 
-  await config.onRequest?.({ procedure, method: "POST", url, headers: { ...headers }, input });
+  if (method === "GET" && format === "sse" && (callOptions?.eventSource ?? config.eventSource)) {
+    return yield* rpcEventSource<T>(config, procedure, url, input, init.signal ?? undefined);
+  }
 
-  try {
-    const res = await fetchFn(url, init);
-    if (!res.ok) {
-      let data: unknown;
-      try { data = await res.json(); } catch { data = null; }
-      const err = new RpcError(res.status, `RPC stream error on "${procedure}": ${res.status} ${res.statusText}`, data);
-      await config.onError?.({ procedure, method: "POST", url, error: err, attempt: 1, willRetry: false });
-      throw err;
-    }
+  // A stream that sends event ids is resumed after a network error; the server
+  // continues after the `Last-Event-ID` it is sent.
+  const reconnect = callOptions?.reconnect ?? config.reconnect;
+  const maxReconnects = reconnect === false ? 0 : reconnect?.attempts ?? 3;
+  let lastEventId: string | undefined;
+  let attempt = 1;
 
-    const reader = res.body!.getReader();
-    const decoder = new TextDecoder();
-    let buffer = "";
+  while (true) {
+    if (lastEventId !== undefined) headers["Last-Event-ID"] = lastEventId;
+    await config.onRequest?.({ procedure, method, url, headers: { ...headers }, input });
 
     try {
-      while (true) {
-        const { done, value } = await reader.read();
-        if (done) break;
-        buffer += decoder.decode(value, { stream: true });
-        const parts = buffer.split("\n\n");
-        buffer = parts.pop()!;
-        for (const part of parts) {
-          let eventType = "message";
-          const dataLines: string[] = [];
-          for (const line of part.split("\n")) {
-            if (line.startsWith("event: ")) {
-              eventType = line.slice(7).trim();
-            } else if (line.startsWith("data: ")) {
-              dataLines.push(line.slice(6));
+      const res = await fetchFn(url, init);
+      if (!res.ok) {
+        let data: unknown;
+        try { data = await res.json(); } catch { data = null; }
+        const err = new RpcError(res.status, `RPC stream error on "${procedure}": ${res.status} ${res.statusText}`, data);
+        await config.onError?.({ procedure, method, url, error: err, attempt, willRetry: false });
+        throw err;
+      }
+
+      const reader = res.body!.getReader();
+      const decoder = new TextDecoder();
+      let buffer = "";
+
+      try {
+        while (true) {
+          const { done, value } = await reader.read();
+          if (done) break;
+          if (format === "bytes") {
+            yield value as T;
+            continue;
+          }
+          buffer += decoder.decode(value, { stream: true });
+          if (format === "ndjson") {
+            const lines = buffer.split("\n");
+            buffer = lines.pop()!;
+            for (const line of lines) {
+              if (line.trim()) yield (config.deserialize ? config.deserialize(line) : JSON.parse(line)) as T;
             }
+            continue;
           }
-          for (const payload of dataLines) {
+          const parts = buffer.split("\n\n");
+          buffer = parts.pop()!;
+          for (const part of parts) {
+            let eventType = "message";
+            let eventId: string | undefined;
+            const dataLines: string[] = [];
+            for (const line of part.split("\n")) {
+              if (line.startsWith("event: ")) {
+                eventType = line.slice(7).trim();
+              } else if (line.startsWith("id: ")) {
+                eventId = line.slice(4);
+              } else if (line.startsWith("data: ")) {
+                dataLines.push(line.slice(6));
+              }
+            }
+            if (eventId !== undefined) {
+              lastEventId = eventId;
+              attempt = 1;
+            }
             if (eventType === "error") {
-              throw new RpcError(500, `RPC stream error on "${procedure}": ${payload}`, null);
+              // `{ status, error: { message, data? } }`, like the body of an error response.
+              const payload = dataLines.join("\n");
+              let data: unknown = null;
+              try { data = JSON.parse(payload); } catch { /* plain-text error */ }
+              const envelope = data as { status?: number; error?: { message?: string } } | null;
+              throw new RpcError(
+                envelope?.status ?? 500,
+                `RPC stream error on "${procedure}": ${envelope?.error?.message ?? payload}`,
+                data,
+              );
+            }
+            if (eventType === "done") {
+              // The handler's return value, returned from the generator.
+              const payload = dataLines.join("\n");
+              return payload ? (config.deserialize ? config.deserialize(payload) : JSON.parse(payload)) : undefined;
+            }
+            for (const payload of dataLines) {
+              yield (config.deserialize ? config.deserialize(payload) : JSON.parse(payload)) as T;
             }
-            yield (config.deserialize ? config.deserialize(payload) : JSON.parse(payload)) as T;
           }
         }
+        if (format === "ndjson" && buffer.trim()) {
+          yield (config.deserialize ? config.deserialize(buffer) : JSON.parse(buffer)) as T;
+        }
+      } finally {
+        reader.releaseLock();
       }
-    } finally {
-      reader.releaseLock();
+      return;
+    } catch (err) {
+      if (err instanceof RpcError) throw err;
+      const willRetry = lastEventId !== undefined && attempt <= maxReconnects && !init.signal?.aborted;
+      await config.onError?.({ procedure, method, url, error: err, attempt, willRetry });
+      if (!willRetry) throw err;
+    }
+
+    const delay = reconnect ? reconnect.delay : undefined;
+    const ms = typeof delay === "function" ? delay(attempt) : delay ?? 500 * 2 ** (attempt - 1);
+    await new Promise(r => setTimeout(r, ms));
+    attempt++;
+  }
+}
+
+// Reads a GET SSE stream through `EventSource`, whose events are queued until the
+// generator asks for them. `EventSource` reconnects by itself, so only a closed
+// source (e.g. a non-2xx response) ends the stream with a plain `Error`.
+async function* rpcEventSource<T>(
+  config: RpcClientConfig,
+  procedure: string,
+  url: string,
+  input: unknown,
+  signal?: AbortSignal,
+): AsyncGenerator<T, unknown> {
+  await config.onRequest?.({ procedure, method: "GET", url, headers: {}, input });
+  const parse = (payload: string) => config.deserialize ? config.deserialize(payload) : JSON.parse(payload);
+  type Step = { value: T } | { done: unknown } | { error: unknown };
+  const queue: Step[] = [];
+  let wake: (() => void) | undefined;
+  const push = (step: Step) => {
+    queue.push(step);
+    wake?.();
+  };
+
+  const source = new EventSource(url);
+  source.onmessage = (e) => push({ value: parse(e.data) as T });
+  source.addEventListener("done", (e) => {
+    const payload = (e as MessageEvent).data;
+    push({ done: payload ? parse(payload) : undefined });
+  });
+  source.addEventListener("error", (e) => {
+    if (e instanceof MessageEvent) {
+      // An `error` event sent by the handler, not a connection error.
+      let data: unknown = null;
+      try { data = JSON.parse(e.data); } catch { /* plain-text error */ }
+      const envelope = data as { status?: number; error?: { message?: string } } | null;
+      push({
+        error: new RpcError(
+          envelope?.status ?? 500,
+          `RPC stream error on "${procedure}": ${envelope?.error?.message ?? e.data}`,
+          data,
+        ),
+      });
+    } else if (source.readyState === EventSource.CLOSED) {
+      push({ error: new Error(`RPC stream error on "${procedure}": connection closed`) });
+    }
+  });
+  const onAbort = () => push({ error: signal!.reason });
+  signal?.addEventListener("abort", onAbort);
+
+  try {
+    if (signal?.aborted) throw signal.reason;
+    while (true) {
+      const step = queue.shift();
+      if (!step) {
+        await new Promise<void>((r) => (wake = r));
+        wake = undefined;
+        continue;
+      }
+      if ("error" in step) throw step.error;
+      if ("done" in step) return step.done;
+      yield step.value;
     }
   } catch (err) {
     if (!(err instanceof RpcError)) {
-      await config.onError?.({ procedure, method: "POST", url, error: err, attempt: 1, willRetry: false });
+      await config.onError?.({ procedure, method: "GET", url, error: err, attempt: 1, willRetry: false });
     }
     throw err;
+  } finally {
+    signal?.removeEventListener("abort", onAbort);
+    source.close();
   }
 }"#;
 
@@ -339,6 +880,15 @@ pub fn generate_client_file(
     // Retry policy interface
     emit!(out, "{RETRY_POLICY_INTERFACE}\n");
 
+    // Stream reconnect policy interface
+    emit!(out, "{RECONNECT_POLICY_INTERFACE}\n");
+
+    // Trace context interface
+    emit!(out, "{TRACE_CONTEXT_INTERFACE}\n");
+
+    // Batching options interface
+    emit!(out, "{BATCH_OPTIONS_INTERFACE}\n");
+
     // Client config interface
     emit!(out, "{CONFIG_INTERFACE}\n");
 
@@ -351,7 +901,17 @@ pub fn generate_client_file(
     // Idempotent mutations set (for retry gating)
     generate_idempotent_mutations(manifest, &mut out);
 
+    // Rate-limited procedures (retries honour `Retry-After`)
+    generate_rate_limited_procedures(manifest, &mut out);
+
+    // Queries answering `If-None-Match` with 304
+    generate_etag_queries(manifest, &mut out);
+
+    // Trace header helpers
+    emit!(out, "{TRACE_HELPERS}\n");
+
     // Internal fetch helper
+    emit!(out, "{MSGPACK_CODEC}\n");
     emit!(out, "{FETCH_HELPER}\n");
 
     // Dedup helpers (only when the manifest has queries)
@@ -362,6 +922,7 @@ pub fn generate_client_file(
     if has_queries {
         emit!(out, "{DEDUP_KEY_FN}\n");
         emit!(out, "{WRAP_WITH_SIGNAL_FN}\n");
+        emit!(out, "{BATCH_HELPER}\n");
     }
 
     // Stream helper (only when the manifest has streams)
@@ -370,6 +931,8 @@ pub fn generate_client_file(
         .iter()
         .any(|p| p.kind == ProcedureKind::Stream);
     if has_streams {
+        generate_stream_formats(manifest, &mut out);
+        generate_get_streams(manifest, &mut out);
         emit!(out, "{STREAM_HELPER}\n");
     }
 
@@ -428,6 +991,98 @@ fn generate_idempotent_mutations(manifest: &Manifest, out: &mut String) {
     }
 }
 
+/// Emits the `RATE_LIMITED_PROCEDURES` set listing procedures with a `rate_limit`.
+fn generate_rate_limited_procedures(manifest: &Manifest, out: &mut String) {
+    let names: Vec<_> = manifest
+        .procedures
+        .iter()
+        .filter(|p| p.rate_limit.is_some())
+        .map(|p| format!("\"{}\"", p.name))
+        .collect();
+
+    if names.is_empty() {
+        emit!(
+            out,
+            "const RATE_LIMITED_PROCEDURES: Set<string> = new Set();\n"
+        );
+    } else {
+        emit!(
+            out,
+            "const RATE_LIMITED_PROCEDURES: Set<string> = new Set([{}]);\n",
+            names.join(", ")
+        );
+    }
+}
+
+/// Emits the `ETAG_QUERIES` set listing queries with the `etag` flag.
+fn generate_etag_queries(manifest: &Manifest, out: &mut String) {
+    let names: Vec<_> = manifest
+        .procedures
+        .iter()
+        .filter(|p| p.etag)
+        .map(|p| format!("\"{}\"", p.name))
+        .collect();
+
+    if names.is_empty() {
+        emit!(out, "const ETAG_QUERIES: Set<string> = new Set();\n");
+    } else {
+        emit!(
+            out,
+            "const ETAG_QUERIES: Set<string> = new Set([{}]);\n",
+            names.join(", ")
+        );
+    }
+}
+
+/// Emits the `STREAM_FORMATS` record mapping NDJSON and byte streams to their format.
+fn generate_stream_formats(manifest: &Manifest, out: &mut String) {
+    let entries: Vec<_> = manifest
+        .procedures
+        .iter()
+        .filter_map(|p| match p.stream_format {
+            StreamFormat::Sse => None,
+            StreamFormat::Ndjson => Some(format!("  \"{}\": \"ndjson\"", p.name)),
+            StreamFormat::Bytes => Some(format!("  \"{}\": \"bytes\"", p.name)),
+        })
+        .collect();
+
+    if entries.is_empty() {
+        emit!(
+            out,
+            "const STREAM_FORMATS: Record<string, \"ndjson\" | \"bytes\"> = {{}};\n"
+        );
+    } else {
+        emit!(
+            out,
+            "const STREAM_FORMATS: Record<string, \"ndjson\" | \"bytes\"> = {{"
+        );
+        for entry in &entries {
+            emit!(out, "{entry},");
+        }
+        emit!(out, "}};\n");
+    }
+}
+
+/// Emits the `GET_STREAMS` set listing streams served over `GET`.
+fn generate_get_streams(manifest: &Manifest, out: &mut String) {
+    let names: Vec<_> = manifest
+        .procedures
+        .iter()
+        .filter(|p| p.stream_get)
+        .map(|p| format!("\"{}\"", p.name))
+        .collect();
+
+    if names.is_empty() {
+        emit!(out, "const GET_STREAMS: Set<string> = new Set();\n");
+    } else {
+        emit!(
+            out,
+            "const GET_STREAMS: Set<string> = new Set([{}]);\n",
+            names.join(", ")
+        );
+    }
+}
+
 /// Emits utility types that power the typed client API.
 fn generate_type_helpers(out: &mut String) {
     emit!(out, "type QueryKey = keyof Procedures[\"queries\"];");
@@ -564,7 +1219,12 @@ fn generate_client_factory(manifest: &Manifest, preserve_docs: bool, out: &mut S
     if has_queries {
         emit!(
             out,
-            "  const inflight = new Map<string, Promise<unknown>>();\n"
+            "  const inflight = new Map<string, Promise<unknown>>();"
+        );
+        emit!(out, "  const etags = new Map<string, EtagEntry>();");
+        emit!(
+            out,
+            "  const batcher = config.batch ? createBatcher(config) : undefined;\n"
         );
     }
 
@@ -607,13 +1267,28 @@ fn generate_client_factory(manifest: &Manifest, preserve_docs: bool, out: &mut S
             );
         }
 
-        // Dedup logic
+        // Dedup logic; the dedup key also indexes the ETag cache
+        emit!(out, "      const k = dedupKey(key, input, config);");
+        emit!(
+            out,
+            "      const etagCache = ETAG_QUERIES.has(key) ? {{ entries: etags, key: k }} : undefined;"
+        );
+        // Calls with per-call headers or timeout, or an ETag, are never batched
+        emit!(out, "      const send = (): Promise<unknown> =>");
+        emit!(
+            out,
+            "        batcher && !etagCache && !callOptions?.headers && callOptions?.timeout === undefined"
+        );
+        emit!(out, "          ? batcher(key, input, callOptions?.signal)");
+        emit!(
+            out,
+            "          : rpcFetch(config, \"GET\", key, input, callOptions, etagCache);"
+        );
         emit!(
             out,
             "      const shouldDedupe = callOptions?.dedupe ?? config.dedupe ?? true;"
         );
         emit!(out, "      if (shouldDedupe) {{");
-        emit!(out, "        const k = dedupKey(key, input, config);");
         emit!(out, "        const existing = inflight.get(k);");
         emit!(
             out,
@@ -621,9 +1296,8 @@ fn generate_client_factory(manifest: &Manifest, preserve_docs: bool, out: &mut S
         );
         emit!(
             out,
-            "        const promise = rpcFetch(config, \"GET\", key, input, callOptions)"
+            "        const promise = send().finally(() => inflight.delete(k));"
         );
-        emit!(out, "          .finally(() => inflight.delete(k));");
         emit!(out, "        inflight.set(k, promise);");
         emit!(
             out,
@@ -632,7 +1306,7 @@ fn generate_client_factory(manifest: &Manifest, preserve_docs: bool, out: &mut S
         emit!(out, "      }}");
         emit!(
             out,
-            "      return rpcFetch(config, \"GET\", key, input, callOptions);"
+            "      return wrapWithSignal(send(), callOptions?.signal);"
         );
         emit!(out, "    }},");
     }
@@ -832,6 +1506,16 @@ fn generate_mutation_overloads(manifest: &Manifest, preserve_docs: bool, out: &m
     }
 }
 
+/// Type arguments of the `AsyncGenerator` a stream returns: the chunk type and,
+/// when the handler returns a final value, its type.
+fn stream_generator_args(proc: &Procedure) -> String {
+    let output = output_to_ts(proc);
+    match &proc.result {
+        Some(result) => format!("{output}, {}", rust_type_to_ts(result)),
+        None => output,
+    }
+}
+
 /// Generates stream overload signatures for the RpcClient interface.
 fn generate_stream_overloads(manifest: &Manifest, preserve_docs: bool, out: &mut String) {
     let (void_streams, non_void_streams): (Vec<_>, Vec<_>) = manifest
@@ -845,11 +1529,7 @@ fn generate_stream_overloads(manifest: &Manifest, preserve_docs: bool, out: &mut
         if preserve_docs && let Some(doc) = &proc.docs {
             emit_jsdoc(doc, "  ", out);
         }
-        let output_ts = proc
-            .output
-            .as_ref()
-            .map(rust_type_to_ts)
-            .unwrap_or_else(|| "void".to_string());
+        let output_ts = stream_generator_args(proc);
         emit!(
             out,
             "  stream(key: \"{}\"): AsyncGenerator<{}>;",
@@ -874,11 +1554,7 @@ fn generate_stream_overloads(manifest: &Manifest, preserve_docs: bool, out: &mut
             .as_ref()
             .map(rust_type_to_ts)
             .unwrap_or_else(|| "void".to_string());
-        let output_ts = proc
-            .output
-            .as_ref()
-            .map(rust_type_to_ts)
-            .unwrap_or_else(|| "void".to_string());
+        let output_ts = stream_generator_args(proc);
         emit!(
             out,
             "  stream(key: \"{}\", input: {}): AsyncGenerator<{}>;",
@@ -231,7 +231,8 @@ fn emit_re_exports(out: &mut String, type_names: &[&str]) {
     }
 }
 
-/// Emits QueryKey/MutationKey/StreamKey type aliases and their Input/Output helpers.
+/// Emits QueryKey/MutationKey/StreamKey type aliases and their Input/Output/Error
+/// (and stream Return) helpers.
 fn emit_type_helpers(out: &mut String, has_queries: bool, has_mutations: bool, has_streams: bool) {
     if has_queries {
         emit!(out, "type QueryKey = keyof Procedures[\"queries\"];");
@@ -243,6 +244,10 @@ fn emit_type_helpers(out: &mut String, has_queries: bool, has_mutations: bool, h
             out,
             "type QueryOutput<K extends QueryKey> = Procedures[\"queries\"][K][\"output\"];"
         );
+        emit!(
+            out,
+            "type QueryError<K extends QueryKey> = Procedures[\"queries\"][K] extends {{ error: infer E }} ? E : never;"
+        );
     }
     if has_mutations {
         emit!(out, "type MutationKey = keyof Procedures[\"mutations\"];");
@@ -254,6 +259,10 @@ fn emit_type_helpers(out: &mut String, has_queries: bool, has_mutations: bool, h
             out,
             "type MutationOutput<K extends MutationKey> = Procedures[\"mutations\"][K][\"output\"];"
         );
+        emit!(
+            out,
+            "type MutationError<K extends MutationKey> = Procedures[\"mutations\"][K] extends {{ error: infer E }} ? E : never;"
+        );
     }
     if has_streams {
         emit!(out, "type StreamKey = keyof Procedures[\"streams\"];");
@@ -265,6 +274,14 @@ fn emit_type_helpers(out: &mut String, has_queries: bool, has_mutations: bool, h
             out,
             "type StreamOutput<K extends StreamKey> = Procedures[\"streams\"][K][\"output\"];"
         );
+        emit!(
+            out,
+            "type StreamReturn<K extends StreamKey> = Procedures[\"streams\"][K] extends {{ result: infer R }} ? R : void;"
+        );
+        emit!(
+            out,
+            "type StreamError<K extends StreamKey> = Procedures[\"streams\"][K] extends {{ error: infer E }} ? E : never;"
+        );
     }
 }
 
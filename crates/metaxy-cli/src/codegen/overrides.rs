@@ -43,6 +43,12 @@ pub fn apply_type_overrides(
         if let Some(ty) = &mut proc.output {
             override_type(ty, overrides, base_index);
         }
+        if let Some(ty) = &mut proc.error {
+            override_type(ty, overrides, base_index);
+        }
+        if let Some(ty) = &mut proc.result {
+            override_type(ty, overrides, base_index);
+        }
     }
 
     for s in &mut manifest.structs {
@@ -24,7 +24,7 @@ const QUERY_OPTIONS_INTERFACE: &str = r#"export interface QueryOptions<K extends
   onSuccess?: (data: QueryOutput<K>) => void;
 
   /** Called when the query fails. */
-  onError?: (error: RpcError) => void;
+  onError?: (error: RpcError<QueryError<K>>) => void;
 
   /** Called when the query settles (success or failure). */
   onSettled?: () => void;
@@ -35,7 +35,7 @@ const QUERY_RESULT_INTERFACE: &str = r#"export interface QueryResult<K extends Q
   readonly data: QueryOutput<K> | undefined;
 
   /** The error from the most recent failed fetch, cleared on next attempt. */
-  readonly error: RpcError | undefined;
+  readonly error: RpcError<QueryError<K>> | undefined;
 
   /** True while a fetch is in-flight (including the initial fetch). */
   readonly isLoading: boolean;
@@ -58,7 +58,7 @@ const MUTATION_OPTIONS_INTERFACE: &str = r#"export interface MutationOptions<K e
   onSuccess?: (data: MutationOutput<K>) => void;
 
   /** Called when the mutation fails. */
-  onError?: (error: RpcError) => void;
+  onError?: (error: RpcError<MutationError<K>>) => void;
 
   /** Called when the mutation settles (success or failure). */
   onSettled?: () => void;
@@ -75,7 +75,7 @@ const MUTATION_RESULT_INTERFACE: &str = r#"export interface MutationResult<K ext
   readonly data: MutationOutput<K> | undefined;
 
   /** The error from the most recent failed mutation, cleared on next attempt. */
-  readonly error: RpcError | undefined;
+  readonly error: RpcError<MutationError<K>> | undefined;
 
   /** True while a mutation is in-flight. */
   readonly isLoading: boolean;
@@ -123,7 +123,7 @@ const USE_QUERY_IMPL: &str = r#"export function useQuery<K extends QueryKey>(
   }
 
   const [data, setData] = useState<QueryOutput<K> | undefined>(() => resolveOptions()?.placeholderData);
-  const [error, setError] = useState<RpcError | undefined>();
+  const [error, setError] = useState<RpcError<QueryError<K>> | undefined>();
   const [isLoading, setIsLoading] = useState(resolveEnabled);
   const [hasFetched, setHasFetched] = useState(false);
 
@@ -157,7 +157,7 @@ const USE_QUERY_IMPL: &str = r#"export function useQuery<K extends QueryKey>(
       opts?.onSuccess?.(result);
     } catch (e) {
       if (gen !== generationRef.current) return;
-      const err = e as RpcError;
+      const err = e as RpcError<QueryError<K>>;
       setError(err);
       opts?.onError?.(err);
     } finally {
@@ -220,7 +220,7 @@ const USE_MUTATION_IMPL: &str = r#"export function useMutation<K extends Mutatio
   options?: MutationOptions<K>,
 ): MutationResult<K> {
   const [data, setData] = useState<MutationOutput<K> | undefined>();
-  const [error, setError] = useState<RpcError | undefined>();
+  const [error, setError] = useState<RpcError<MutationError<K>> | undefined>();
   const [isLoading, setIsLoading] = useState(false);
   const [hasSucceeded, setHasSucceeded] = useState(false);
 
@@ -241,7 +241,7 @@ const USE_MUTATION_IMPL: &str = r#"export function useMutation<K extends Mutatio
       optionsRef.current?.onSuccess?.(result);
       return result;
     } catch (e) {
-      const err = e as RpcError;
+      const err = e as RpcError<MutationError<K>>;
       setError(err);
       optionsRef.current?.onError?.(err);
       throw e;
@@ -271,13 +271,14 @@ const USE_MUTATION_IMPL: &str = r#"export function useMutation<K extends Mutatio
 const STREAM_OPTIONS_INTERFACE: &str = r#"export interface StreamOptions<K extends StreamKey> {
   callOptions?: CallOptions;
   onChunk?: (chunk: StreamOutput<K>) => void;
-  onDone?: () => void;
-  onError?: (error: RpcError) => void;
+  onDone?: (result: StreamReturn<K>) => void;
+  onError?: (error: RpcError<StreamError<K>>) => void;
 }"#;
 
 const STREAM_RESULT_INTERFACE: &str = r#"export interface StreamResult<K extends StreamKey> {
   readonly chunks: StreamOutput<K>[];
-  readonly error: RpcError | undefined;
+  readonly error: RpcError<StreamError<K>> | undefined;
+  readonly result: StreamReturn<K> | undefined;
   readonly isStreaming: boolean;
   readonly isDone: boolean;
   start: () => void;
@@ -301,7 +302,8 @@ const USE_STREAM_IMPL: &str = r#"export function useStream<K extends StreamKey>(
   }
 
   const [chunks, setChunks] = useState<StreamOutput<K>[]>([]);
-  const [error, setError] = useState<RpcError | undefined>();
+  const [error, setError] = useState<RpcError<StreamError<K>> | undefined>();
+  const [result, setResult] = useState<StreamReturn<K> | undefined>();
   const [isStreaming, setIsStreaming] = useState(false);
   const [isDone, setIsDone] = useState(false);
   const controllerRef = useRef<AbortController | undefined>();
@@ -321,6 +323,7 @@ const USE_STREAM_IMPL: &str = r#"export function useStream<K extends StreamKey>(
     controllerRef.current = controller;
     setChunks([]);
     setError(undefined);
+    setResult(undefined);
     setIsStreaming(true);
     setIsDone(false);
 
@@ -333,19 +336,28 @@ const USE_STREAM_IMPL: &str = r#"export function useStream<K extends StreamKey>(
         const mergedCallOptions = { ...optionsRef.current?.callOptions, signal: mergedSignal };
         callArgs.push(mergedCallOptions);
         const gen = (client.stream as (...a: unknown[]) => AsyncGenerator<unknown>)(...callArgs);
-        for await (const chunk of gen) {
-          if (controller.signal.aborted) break;
+        // Iterated by hand to keep the value the handler returns in its `done` event.
+        let next = await gen.next();
+        while (!next.done) {
+          if (controller.signal.aborted) {
+            await gen.return(undefined);
+            break;
+          }
+          const chunk = next.value;
           setChunks(prev => [...prev, chunk as StreamOutput<K>]);
           optionsRef.current?.onChunk?.(chunk as StreamOutput<K>);
+          next = await gen.next();
         }
         if (!controller.signal.aborted) {
+          const value = next.value as StreamReturn<K>;
+          setResult(() => value);
           setIsDone(true);
-          optionsRef.current?.onDone?.();
+          optionsRef.current?.onDone?.(value);
         }
       } catch (e) {
         if (!controller.signal.aborted) {
-          setError(e as RpcError);
-          optionsRef.current?.onError?.(e as RpcError);
+          setError(e as RpcError<StreamError<K>>);
+          optionsRef.current?.onError?.(e as RpcError<StreamError<K>>);
         }
       } finally {
         setIsStreaming(false);
@@ -357,7 +369,7 @@ const USE_STREAM_IMPL: &str = r#"export function useStream<K extends StreamKey>(
     return () => stop();
   }, [stop]);
 
-  return { chunks, error, isStreaming, isDone, start, stop };
+  return { chunks, error, result, isStreaming, isDone, start, stop };
 }"#;
 
 const FRAMEWORK_IMPORT: &str =
@@ -24,7 +24,7 @@ const QUERY_OPTIONS_INTERFACE: &str = r#"export interface QueryOptions<K extends
   onSuccess?: (data: QueryOutput<K>) => void;
 
   /** Called when the query fails. */
-  onError?: (error: RpcError) => void;
+  onError?: (error: RpcError<QueryError<K>>) => void;
 
   /** Called when the query settles (success or failure). */
   onSettled?: () => void;
@@ -35,7 +35,7 @@ const QUERY_RESULT_INTERFACE: &str = r#"export interface QueryResult<K extends Q
   data: () => QueryOutput<K> | undefined;
 
   /** The error from the most recent failed fetch, cleared on next attempt. */
-  error: () => RpcError | undefined;
+  error: () => RpcError<QueryError<K>> | undefined;
 
   /** True while a fetch is in-flight (including the initial fetch). */
   isLoading: () => boolean;
@@ -58,7 +58,7 @@ const MUTATION_OPTIONS_INTERFACE: &str = r#"export interface MutationOptions<K e
   onSuccess?: (data: MutationOutput<K>) => void;
 
   /** Called when the mutation fails. */
-  onError?: (error: RpcError) => void;
+  onError?: (error: RpcError<MutationError<K>>) => void;
 
   /** Called when the mutation settles (success or failure). */
   onSettled?: () => void;
@@ -75,7 +75,7 @@ const MUTATION_RESULT_INTERFACE: &str = r#"export interface MutationResult<K ext
   data: () => MutationOutput<K> | undefined;
 
   /** The error from the most recent failed mutation, cleared on next attempt. */
-  error: () => RpcError | undefined;
+  error: () => RpcError<MutationError<K>> | undefined;
 
   /** True while a mutation is in-flight. */
   isLoading: () => boolean;
@@ -129,7 +129,7 @@ const CREATE_QUERY_IMPL: &str = r#"export function createQuery<K extends QueryKe
     : (initialOpts?.enabled ?? true);
 
   const [data, setData] = createSignal<QueryOutput<K> | undefined>(initialOpts?.placeholderData);
-  const [error, setError] = createSignal<RpcError | undefined>();
+  const [error, setError] = createSignal<RpcError<QueryError<K>> | undefined>();
   const [isLoading, setIsLoading] = createSignal(initialEnabled);
   const [hasFetched, setHasFetched] = createSignal(false);
 
@@ -161,8 +161,8 @@ const CREATE_QUERY_IMPL: &str = r#"export function createQuery<K extends QueryKe
       opts?.onSuccess?.(result);
     } catch (e) {
       if (gen !== generation) return;
-      const err = e as RpcError;
-      setError(err as Exclude<RpcError | undefined, Function>);
+      const err = e as RpcError<QueryError<K>>;
+      setError(err as Exclude<RpcError<QueryError<K>> | undefined, Function>);
       opts?.onError?.(err);
     } finally {
       if (gen === generation) {
@@ -239,7 +239,7 @@ const CREATE_MUTATION_IMPL: &str = r#"export function createMutation<K extends M
   options?: MutationOptions<K>,
 ): MutationResult<K> {
   const [data, setData] = createSignal<MutationOutput<K> | undefined>();
-  const [error, setError] = createSignal<RpcError | undefined>();
+  const [error, setError] = createSignal<RpcError<MutationError<K>> | undefined>();
   const [isLoading, setIsLoading] = createSignal(false);
   const [hasSucceeded, setHasSucceeded] = createSignal(false);
 
@@ -262,8 +262,8 @@ const CREATE_MUTATION_IMPL: &str = r#"export function createMutation<K extends M
       options?.onSuccess?.(result);
       return result;
     } catch (e) {
-      const err = e as RpcError;
-      setError(err as Exclude<RpcError | undefined, Function>);
+      const err = e as RpcError<MutationError<K>>;
+      setError(err as Exclude<RpcError<MutationError<K>> | undefined, Function>);
       options?.onError?.(err);
       throw e;
     } finally {
@@ -287,13 +287,14 @@ const CREATE_MUTATION_IMPL: &str = r#"export function createMutation<K extends M
 const STREAM_OPTIONS_INTERFACE: &str = r#"export interface StreamOptions<K extends StreamKey> {
   callOptions?: CallOptions;
   onChunk?: (chunk: StreamOutput<K>) => void;
-  onDone?: () => void;
-  onError?: (error: RpcError) => void;
+  onDone?: (result: StreamReturn<K>) => void;
+  onError?: (error: RpcError<StreamError<K>>) => void;
 }"#;
 
 const STREAM_RESULT_INTERFACE: &str = r#"export interface StreamResult<K extends StreamKey> {
   readonly chunks: () => StreamOutput<K>[];
-  readonly error: () => RpcError | undefined;
+  readonly error: () => RpcError<StreamError<K>> | undefined;
+  readonly result: () => StreamReturn<K> | undefined;
   readonly isStreaming: () => boolean;
   readonly isDone: () => boolean;
   start: () => void;
@@ -320,7 +321,8 @@ const CREATE_STREAM_IMPL: &str = r#"export function createStream<K extends Strea
   }
 
   const [chunks, setChunks] = createSignal<StreamOutput<K>[]>([]);
-  const [error, setError] = createSignal<RpcError | undefined>();
+  const [error, setError] = createSignal<RpcError<StreamError<K>> | undefined>();
+  const [result, setResult] = createSignal<StreamReturn<K> | undefined>();
   const [isStreaming, setIsStreaming] = createSignal(false);
   const [isDone, setIsDone] = createSignal(false);
   let controller: AbortController | undefined;
@@ -338,6 +340,7 @@ const CREATE_STREAM_IMPL: &str = r#"export function createStream<K extends Strea
     batch(() => {
       setChunks([]);
       setError(undefined);
+      setResult(undefined);
       setIsStreaming(true);
       setIsDone(false);
     });
@@ -351,19 +354,28 @@ const CREATE_STREAM_IMPL: &str = r#"export function createStream<K extends Strea
       const mergedCallOptions = { ...options?.callOptions, signal: mergedSignal };
       callArgs.push(mergedCallOptions);
       const gen = (client.stream as (...a: unknown[]) => AsyncGenerator<unknown>)(...callArgs);
-      for await (const chunk of gen) {
-        if (controller.signal.aborted) break;
+      // Iterated by hand to keep the value the handler returns in its `done` event.
+      let next = await gen.next();
+      while (!next.done) {
+        if (controller.signal.aborted) {
+          await gen.return(undefined);
+          break;
+        }
+        const chunk = next.value;
         setChunks(prev => [...prev, chunk as StreamOutput<K>]);
         options?.onChunk?.(chunk as StreamOutput<K>);
+        next = await gen.next();
       }
       if (!controller.signal.aborted) {
+        const value = next.value as StreamReturn<K>;
+        setResult(() => value);
         setIsDone(true);
-        options?.onDone?.();
+        options?.onDone?.(value);
       }
     } catch (e) {
       if (!controller.signal.aborted) {
-        setError(e as RpcError);
-        options?.onError?.(e as RpcError);
+        setError(e as RpcError<StreamError<K>>);
+        options?.onError?.(e as RpcError<StreamError<K>>);
       }
     } finally {
       setIsStreaming(false);
@@ -372,7 +384,7 @@ const CREATE_STREAM_IMPL: &str = r#"export function createStream<K extends Strea
 
   onCleanup(stop);
 
-  return { chunks, error, isStreaming, isDone, start: () => { void run(); }, stop };
+  return { chunks, error, result, isStreaming, isDone, start: () => { void run(); }, stop };
 }"#;
 
 const FRAMEWORK_IMPORT: &str = "import { createSignal, createEffect, createMemo, onCleanup, batch, untrack } from \"solid-js\";";
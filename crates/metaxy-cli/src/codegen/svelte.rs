@@ -24,7 +24,7 @@ const QUERY_OPTIONS_INTERFACE: &str = r#"export interface QueryOptions<K extends
   onSuccess?: (data: QueryOutput<K>) => void;
 
   /** Called when the query fails. */
-  onError?: (error: RpcError) => void;
+  onError?: (error: RpcError<QueryError<K>>) => void;
 
   /** Called when the query settles (success or failure). */
   onSettled?: () => void;
@@ -37,7 +37,7 @@ export interface QueryResult<K extends QueryKey> {
   readonly data: QueryOutput<K> | undefined;
 
   /** The error from the most recent failed fetch, cleared on next attempt. */
-  readonly error: RpcError | undefined;
+  readonly error: RpcError<QueryError<K>> | undefined;
 
   /** Current status of the query. Derived: loading > error > success > idle. */
   readonly status: QueryStatus;
@@ -66,7 +66,7 @@ const MUTATION_OPTIONS_INTERFACE: &str = r#"export interface MutationOptions<K e
   onSuccess?: (data: MutationOutput<K>) => void;
 
   /** Called when the mutation fails. */
-  onError?: (error: RpcError) => void;
+  onError?: (error: RpcError<MutationError<K>>) => void;
 
   /** Called when the mutation settles (success or failure). */
   onSettled?: () => void;
@@ -83,7 +83,7 @@ const MUTATION_RESULT_INTERFACE: &str = r#"export interface MutationResult<K ext
   readonly data: MutationOutput<K> | undefined;
 
   /** The error from the most recent failed mutation, cleared on next attempt. */
-  readonly error: RpcError | undefined;
+  readonly error: RpcError<MutationError<K>> | undefined;
 
   /** True while a mutation is in-flight. */
   readonly isLoading: boolean;
@@ -132,7 +132,7 @@ const CREATE_QUERY_IMPL: &str = r#"export function createQuery<K extends QueryKe
   }
 
   let data = $state<QueryOutput<K> | undefined>(resolveOptions()?.placeholderData);
-  let error = $state<RpcError | undefined>();
+  let error = $state<RpcError<QueryError<K>> | undefined>();
   let hasFetched = $state(false);
   let loading = $state(false);
 
@@ -157,7 +157,7 @@ const CREATE_QUERY_IMPL: &str = r#"export function createQuery<K extends QueryKe
       opts?.onSuccess?.(result);
     } catch (e) {
       if (gen !== generation) return;
-      error = e as RpcError;
+      error = e as RpcError<QueryError<K>>;
       opts?.onError?.(error);
     } finally {
       if (gen === generation) {
@@ -240,7 +240,7 @@ const CREATE_MUTATION_IMPL: &str = r#"export function createMutation<K extends M
   options?: MutationOptions<K>,
 ): MutationResult<K> {
   let data = $state<MutationOutput<K> | undefined>();
-  let error = $state<RpcError | undefined>();
+  let error = $state<RpcError<MutationError<K>> | undefined>();
   let loading = $state(false);
   let hasSucceeded = $state(false);
 
@@ -258,7 +258,7 @@ const CREATE_MUTATION_IMPL: &str = r#"export function createMutation<K extends M
       options?.onSuccess?.(result);
       return result;
     } catch (e) {
-      error = e as RpcError;
+      error = e as RpcError<MutationError<K>>;
       options?.onError?.(error);
       throw e;
     } finally {
@@ -286,11 +286,11 @@ const STREAM_OPTIONS_INTERFACE: &str = r#"export interface StreamOptions<K exten
   /** Called for each chunk received from the stream. */
   onChunk?: (chunk: StreamOutput<K>) => void;
 
-  /** Called when the stream completes successfully. */
-  onDone?: () => void;
+  /** Called with the handler's final value when the stream completes successfully. */
+  onDone?: (result: StreamReturn<K>) => void;
 
   /** Called when the stream encounters an error. */
-  onError?: (error: RpcError) => void;
+  onError?: (error: RpcError<StreamError<K>>) => void;
 }"#;
 
 const STREAM_RESULT_INTERFACE: &str = r#"export interface StreamResult<K extends StreamKey> {
@@ -298,7 +298,10 @@ const STREAM_RESULT_INTERFACE: &str = r#"export interface StreamResult<K extends
   readonly chunks: StreamOutput<K>[];
 
   /** The error from the stream, if any. */
-  readonly error: RpcError | undefined;
+  readonly error: RpcError<StreamError<K>> | undefined;
+
+  /** The final value returned by the handler, once the stream is done. */
+  readonly result: StreamReturn<K> | undefined;
 
   /** True while the stream is active. */
   readonly isStreaming: boolean;
@@ -333,7 +336,8 @@ const CREATE_STREAM_IMPL: &str = r#"export function createStream<K extends Strea
   }
 
   let chunks = $state<StreamOutput<K>[]>([]);
-  let error = $state<RpcError | undefined>();
+  let error = $state<RpcError<StreamError<K>> | undefined>();
+  let result = $state<StreamReturn<K> | undefined>();
   let streaming = $state(false);
   let done = $state(false);
   let controller: AbortController | undefined;
@@ -343,6 +347,7 @@ const CREATE_STREAM_IMPL: &str = r#"export function createStream<K extends Strea
     controller = new AbortController();
     chunks = [];
     error = undefined;
+    result = undefined;
     streaming = true;
     done = false;
 
@@ -355,18 +360,26 @@ const CREATE_STREAM_IMPL: &str = r#"export function createStream<K extends Strea
       const mergedCallOptions = { ...options?.callOptions, signal: mergedSignal };
       callArgs.push(mergedCallOptions);
       const gen = (client.stream as (...a: unknown[]) => AsyncGenerator<unknown>)(...callArgs);
-      for await (const chunk of gen) {
-        if (controller.signal.aborted) break;
+      // Iterated by hand to keep the value the handler returns in its `done` event.
+      let next = await gen.next();
+      while (!next.done) {
+        if (controller.signal.aborted) {
+          await gen.return(undefined);
+          break;
+        }
+        const chunk = next.value;
         chunks = [...chunks, chunk as StreamOutput<K>];
         options?.onChunk?.(chunk as StreamOutput<K>);
+        next = await gen.next();
       }
       if (!controller.signal.aborted) {
+        result = next.value as StreamReturn<K>;
         done = true;
-        options?.onDone?.();
+        options?.onDone?.(result);
       }
     } catch (e) {
       if (!controller.signal.aborted) {
-        error = e as RpcError;
+        error = e as RpcError<StreamError<K>>;
         options?.onError?.(error);
       }
     } finally {
@@ -377,6 +390,7 @@ const CREATE_STREAM_IMPL: &str = r#"export function createStream<K extends Strea
   return {
     get chunks() { return chunks; },
     get error() { return error; },
+    get result() { return result; },
     get isStreaming() { return streaming; },
     get isDone() { return done; },
     start: () => { void run(); },
@@ -2,7 +2,7 @@ use super::common::GENERATED_HEADER;
 use crate::config::FieldNaming;
 use crate::model::{
     EnumDef, EnumTagging, EnumVariant, FieldDef, Manifest, Procedure, ProcedureKind, RenameRule,
-    RustType, StructDef, VariantKind,
+    RustType, StreamFormat, StructDef, VariantKind,
 };
 
 /// Converts a `RustType` into its TypeScript equivalent.
@@ -547,6 +547,42 @@ fn format_generic_params(generics: &[String]) -> String {
     }
 }
 
+/// Emits a single `name: { input; output; result?; error? }` entry of the `Procedures` type.
+///
+/// The `error` key is only present for handlers returning `Result<T, E>`, the
+/// `result` key only for streams returning a final value.
+fn emit_procedure_entry(proc: &Procedure, preserve_docs: bool, out: &mut String) {
+    if preserve_docs && let Some(doc) = &proc.docs {
+        emit_jsdoc(doc, "    ", out);
+    }
+    let input = proc
+        .input
+        .as_ref()
+        .map(rust_type_to_ts)
+        .unwrap_or_else(|| "void".to_string());
+    let output = output_to_ts(proc);
+    let mut entry = format!("input: {input}; output: {output}");
+    if let Some(result) = &proc.result {
+        entry.push_str(&format!("; result: {}", rust_type_to_ts(result)));
+    }
+    if let Some(error) = &proc.error {
+        entry.push_str(&format!("; error: {}", rust_type_to_ts(error)));
+    }
+    emit!(out, "    {}: {{ {entry} }};", proc.name);
+}
+
+/// Returns the TypeScript output type of a procedure. Chunks of a
+/// `format = "bytes"` stream are always `Uint8Array`.
+pub fn output_to_ts(proc: &Procedure) -> String {
+    if proc.stream_format == StreamFormat::Bytes {
+        return "Uint8Array".to_string();
+    }
+    proc.output
+        .as_ref()
+        .map(rust_type_to_ts)
+        .unwrap_or_else(|| "void".to_string())
+}
+
 /// Generates the `Procedures` type that maps procedure names to their input/output types,
 /// grouped by kind (queries / mutations).
 fn generate_procedures_type(procedures: &[Procedure], preserve_docs: bool, out: &mut String) {
@@ -567,72 +603,21 @@ fn generate_procedures_type(procedures: &[Procedure], preserve_docs: bool, out:
     // Queries
     emit!(out, "  queries: {{");
     for proc in &queries {
-        if preserve_docs && let Some(doc) = &proc.docs {
-            emit_jsdoc(doc, "    ", out);
-        }
-        let input = proc
-            .input
-            .as_ref()
-            .map(rust_type_to_ts)
-            .unwrap_or_else(|| "void".to_string());
-        let output = proc
-            .output
-            .as_ref()
-            .map(rust_type_to_ts)
-            .unwrap_or_else(|| "void".to_string());
-        emit!(
-            out,
-            "    {}: {{ input: {input}; output: {output} }};",
-            proc.name
-        );
+        emit_procedure_entry(proc, preserve_docs, out);
     }
     emit!(out, "  }};");
 
     // Mutations
     emit!(out, "  mutations: {{");
     for proc in &mutations {
-        if preserve_docs && let Some(doc) = &proc.docs {
-            emit_jsdoc(doc, "    ", out);
-        }
-        let input = proc
-            .input
-            .as_ref()
-            .map(rust_type_to_ts)
-            .unwrap_or_else(|| "void".to_string());
-        let output = proc
-            .output
-            .as_ref()
-            .map(rust_type_to_ts)
-            .unwrap_or_else(|| "void".to_string());
-        emit!(
-            out,
-            "    {}: {{ input: {input}; output: {output} }};",
-            proc.name
-        );
+        emit_procedure_entry(proc, preserve_docs, out);
     }
     emit!(out, "  }};");
 
     // Streams
     emit!(out, "  streams: {{");
     for proc in &streams {
-        if preserve_docs && let Some(doc) = &proc.docs {
-            emit_jsdoc(doc, "    ", out);
-        }
-        let input = proc
-            .input
-            .as_ref()
-            .map(rust_type_to_ts)
-            .unwrap_or_else(|| "void".to_string());
-        let output = proc
-            .output
-            .as_ref()
-            .map(rust_type_to_ts)
-            .unwrap_or_else(|| "void".to_string());
-        emit!(
-            out,
-            "    {}: {{ input: {input}; output: {output} }};",
-            proc.name
-        );
+        emit_procedure_entry(proc, preserve_docs, out);
     }
     emit!(out, "  }};");
 
@@ -24,7 +24,7 @@ const QUERY_OPTIONS_INTERFACE: &str = r#"export interface QueryOptions<K extends
   onSuccess?: (data: QueryOutput<K>) => void;
 
   /** Called when the query fails. */
-  onError?: (error: RpcError) => void;
+  onError?: (error: RpcError<QueryError<K>>) => void;
 
   /** Called when the query settles (success or failure). */
   onSettled?: () => void;
@@ -35,7 +35,7 @@ const QUERY_RESULT_INTERFACE: &str = r#"export interface QueryResult<K extends Q
   readonly data: Ref<QueryOutput<K> | undefined>;
 
   /** The error from the most recent failed fetch, cleared on next attempt. */
-  readonly error: Ref<RpcError | undefined>;
+  readonly error: Ref<RpcError<QueryError<K>> | undefined>;
 
   /** True while a fetch is in-flight (including the initial fetch). */
   readonly isLoading: Ref<boolean>;
@@ -58,7 +58,7 @@ const MUTATION_OPTIONS_INTERFACE: &str = r#"export interface MutationOptions<K e
   onSuccess?: (data: MutationOutput<K>) => void;
 
   /** Called when the mutation fails. */
-  onError?: (error: RpcError) => void;
+  onError?: (error: RpcError<MutationError<K>>) => void;
 
   /** Called when the mutation settles (success or failure). */
   onSettled?: () => void;
@@ -75,7 +75,7 @@ const MUTATION_RESULT_INTERFACE: &str = r#"export interface MutationResult<K ext
   readonly data: Ref<MutationOutput<K> | undefined>;
 
   /** The error from the most recent failed mutation, cleared on next attempt. */
-  readonly error: Ref<RpcError | undefined>;
+  readonly error: Ref<RpcError<MutationError<K>> | undefined>;
 
   /** True while a mutation is in-flight. */
   readonly isLoading: Ref<boolean>;
@@ -124,7 +124,7 @@ const USE_QUERY_IMPL: &str = r#"export function useQuery<K extends QueryKey>(
   }
 
   const data = ref<QueryOutput<K> | undefined>(resolveOptions()?.placeholderData) as Ref<QueryOutput<K> | undefined>;
-  const error = ref<RpcError | undefined>();
+  const error = ref<RpcError<QueryError<K>> | undefined>();
   const hasFetched = ref(false);
   const isLoading = ref(false);
   const isSuccess = computed(() => hasFetched.value);
@@ -154,7 +154,7 @@ const USE_QUERY_IMPL: &str = r#"export function useQuery<K extends QueryKey>(
       opts?.onSuccess?.(data.value!);
     } catch (e) {
       if (gen !== generation) return;
-      error.value = e as RpcError;
+      error.value = e as RpcError<QueryError<K>>;
       opts?.onError?.(error.value);
     } finally {
       if (gen === generation) {
@@ -233,7 +233,7 @@ const USE_MUTATION_IMPL: &str = r#"export function useMutation<K extends Mutatio
   options?: MutationOptions<K>,
 ): MutationResult<K> {
   const data = ref<MutationOutput<K> | undefined>() as Ref<MutationOutput<K> | undefined>;
-  const error = ref<RpcError | undefined>();
+  const error = ref<RpcError<MutationError<K>> | undefined>();
   const isLoading = ref(false);
   const hasSucceeded = ref(false);
   const isSuccess = computed(() => hasSucceeded.value);
@@ -255,7 +255,7 @@ const USE_MUTATION_IMPL: &str = r#"export function useMutation<K extends Mutatio
       options?.onSuccess?.(result);
       return result;
     } catch (e) {
-      error.value = e as RpcError;
+      error.value = e as RpcError<MutationError<K>>;
       options?.onError?.(error.value);
       throw e;
     } finally {
@@ -279,13 +279,14 @@ const USE_MUTATION_IMPL: &str = r#"export function useMutation<K extends Mutatio
 const STREAM_OPTIONS_INTERFACE: &str = r#"export interface StreamOptions<K extends StreamKey> {
   callOptions?: CallOptions;
   onChunk?: (chunk: StreamOutput<K>) => void;
-  onDone?: () => void;
-  onError?: (error: RpcError) => void;
+  onDone?: (result: StreamReturn<K>) => void;
+  onError?: (error: RpcError<StreamError<K>>) => void;
 }"#;
 
 const STREAM_RESULT_INTERFACE: &str = r#"export interface StreamResult<K extends StreamKey> {
   readonly chunks: Ref<StreamOutput<K>[]>;
-  readonly error: Ref<RpcError | undefined>;
+  readonly error: Ref<RpcError<StreamError<K>> | undefined>;
+  readonly result: Ref<StreamReturn<K> | undefined>;
   readonly isStreaming: Ref<boolean>;
   readonly isDone: Ref<boolean>;
   start: () => void;
@@ -312,7 +313,8 @@ const USE_STREAM_IMPL: &str = r#"export function useStream<K extends StreamKey>(
   }
 
   const chunks = ref<StreamOutput<K>[]>([]) as Ref<StreamOutput<K>[]>;
-  const error = ref<RpcError | undefined>();
+  const error = ref<RpcError<StreamError<K>> | undefined>() as Ref<RpcError<StreamError<K>> | undefined>;
+  const result = ref<StreamReturn<K> | undefined>() as Ref<StreamReturn<K> | undefined>;
   const isStreaming = ref(false);
   const isDone = ref(false);
   let controller: AbortController | undefined;
@@ -329,6 +331,7 @@ const USE_STREAM_IMPL: &str = r#"export function useStream<K extends StreamKey>(
     controller = new AbortController();
     chunks.value = [];
     error.value = undefined;
+    result.value = undefined;
     isStreaming.value = true;
     isDone.value = false;
 
@@ -341,18 +344,27 @@ const USE_STREAM_IMPL: &str = r#"export function useStream<K extends StreamKey>(
       const mergedCallOptions = { ...options?.callOptions, signal: mergedSignal };
       callArgs.push(mergedCallOptions);
       const gen = (client.stream as (...a: unknown[]) => AsyncGenerator<unknown>)(...callArgs);
-      for await (const chunk of gen) {
-        if (controller.signal.aborted) break;
+      // Iterated by hand to keep the value the handler returns in its `done` event.
+      let next = await gen.next();
+      while (!next.done) {
+        if (controller.signal.aborted) {
+          await gen.return(undefined);
+          break;
+        }
+        const chunk = next.value;
         chunks.value = [...chunks.value, chunk as StreamOutput<K>];
         options?.onChunk?.(chunk as StreamOutput<K>);
+        next = await gen.next();
       }
       if (!controller.signal.aborted) {
+        const value = next.value as StreamReturn<K>;
+        result.value = value;
         isDone.value = true;
-        options?.onDone?.();
+        options?.onDone?.(value);
       }
     } catch (e) {
       if (!controller.signal.aborted) {
-        error.value = e as RpcError;
+        error.value = e as RpcError<StreamError<K>>;
         options?.onError?.(error.value);
       }
     } finally {
@@ -362,7 +374,7 @@ const USE_STREAM_IMPL: &str = r#"export function useStream<K extends StreamKey>(
 
   onScopeDispose(stop);
 
-  return { chunks, error, isStreaming, isDone, start: () => { void run(); }, stop };
+  return { chunks, error, result, isStreaming, isDone, start: () => { void run(); }, stop };
 }"#;
 
 const FRAMEWORK_IMPORT: &str =
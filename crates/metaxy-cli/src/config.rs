@@ -13,6 +13,7 @@ pub struct RpcConfig {
     pub output: OutputConfig,
     pub codegen: CodegenConfig,
     pub watch: WatchConfig,
+    pub dev: DevConfig,
 }
 
 #[derive(Debug, Deserialize)]
@@ -76,6 +77,12 @@ pub struct WatchConfig {
     pub clear_screen: bool,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct DevConfig {
+    pub port: u16,
+}
+
 impl Default for InputConfig {
     fn default() -> Self {
         Self {
@@ -125,6 +132,12 @@ impl Default for WatchConfig {
     }
 }
 
+impl Default for DevConfig {
+    fn default() -> Self {
+        Self { port: 3000 }
+    }
+}
+
 /// Walk up from `start` looking for `metaxy.config.toml`.
 /// Returns `None` if not found.
 pub fn discover(start: &Path) -> Option<PathBuf> {
@@ -174,6 +187,8 @@ pub struct CliOverrides {
     // watch
     pub debounce_ms: Option<u64>,
     pub clear_screen: bool,
+    // dev
+    pub port: Option<u16>,
 }
 
 /// Resolve config: discover/load the file, then apply CLI overrides.
@@ -245,6 +260,9 @@ pub fn resolve(cli: CliOverrides) -> Result<RpcConfig> {
     if cli.clear_screen {
         config.watch.clear_screen = true;
     }
+    if let Some(port) = cli.port {
+        config.dev.port = port;
+    }
 
     Ok(config)
 }
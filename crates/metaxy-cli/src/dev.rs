@@ -0,0 +1,494 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use walkdir::WalkDir;
+
+use crate::config::RpcConfig;
+use crate::model::ProcedureKind;
+use crate::parser;
+use crate::watch;
+
+/// Environment variable read by the generated `main` to pick its listening port.
+///
+/// Mirrors `metaxy::__private::DEV_PORT_ENV`.
+pub const DEV_PORT_ENV: &str = "METAXY_DEV_PORT";
+
+/// Route prefix under which Vercel exposes functions in `api/`.
+const API_PREFIX: &str = "/api/";
+
+/// Route name of the batch endpoint served by a `metaxy::router!`.
+const BATCH_ROUTE: &str = "_batch";
+
+/// Upper bound on the size of a request line plus headers.
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+/// How long a request waits for its function to accept connections
+/// (covers process startup and `init` functions).
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Procedure name → port of the function process serving it.
+type Routes = Arc<RwLock<HashMap<String, u16>>>;
+
+/// A compiled procedure binary.
+struct DevFunction {
+    name: String,
+    kind: ProcedureKind,
+    executable: PathBuf,
+}
+
+/// Runs the dev server: builds every procedure binary, starts one process per
+/// function and serves them behind a single HTTP server on `config.dev.port`,
+/// routing `/api/<name>` to the matching function and `/api/_batch` to the
+/// router binary, if there is exactly one.
+///
+/// Source changes in the api directory trigger a rebuild; the running
+/// functions are only replaced once the new build succeeds.
+///
+/// Blocks until the process receives SIGINT (Ctrl+C).
+#[cfg(not(tarpaulin_include))]
+pub fn run(config: &RpcConfig) -> Result<()> {
+    let running = Arc::new(AtomicBool::new(true));
+    let running_clone = running.clone();
+
+    ctrlc::set_handler(move || {
+        running_clone.store(false, Relaxed);
+    })
+    .context("Failed to set Ctrl+C handler")?;
+
+    let project_dir = find_project_dir(&config.input.dir)?;
+    let listener = TcpListener::bind(("127.0.0.1", config.dev.port))
+        .with_context(|| format!("Failed to bind 127.0.0.1:{}", config.dev.port))?;
+
+    print_banner(config, &project_dir);
+
+    let routes: Routes = Arc::default();
+    let mut processes = Vec::new();
+    match build(config, &project_dir) {
+        Ok(functions) => processes = start(&functions, &project_dir, &routes)?,
+        Err(e) => watch::print_error(&e),
+    }
+
+    let server_routes = routes.clone();
+    thread::spawn(move || serve(&listener, &server_routes));
+
+    println!(
+        "  {} on {}\n",
+        "Listening".cyan().bold(),
+        format!("http://localhost:{}", config.dev.port).underline(),
+    );
+
+    // Building reads every source file, which some platforms report as
+    // change events; only rebuild when a modification time actually moved.
+    let mut mtimes = snapshot_mtimes(&config.input.dir);
+    let result = watch::watch_rs_changes(config, &running, |changed| {
+        let changed: Vec<&Path> = changed
+            .iter()
+            .copied()
+            .filter(|path| {
+                let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+                let mtime = modified(path);
+                mtimes.insert(key, mtime) != Some(mtime)
+            })
+            .collect();
+        if changed.is_empty() {
+            return;
+        }
+
+        if config.watch.clear_screen {
+            watch::clear_screen();
+            print_banner(config, &project_dir);
+        }
+
+        watch::print_change(&changed);
+
+        let restarted = build(config, &project_dir).and_then(|functions| {
+            stop(&mut processes);
+            start(&functions, &project_dir, &routes)
+        });
+        match restarted {
+            Ok(started) => processes = started,
+            Err(e) => watch::print_error(&e),
+        }
+    });
+
+    stop(&mut processes);
+    println!("\n  {} Dev server stopped.", "●".dimmed());
+    result
+}
+
+/// Records the modification time of every `.rs` file under `dir`.
+fn snapshot_mtimes(dir: &Path) -> HashMap<PathBuf, Option<SystemTime>> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
+        .filter_map(|e| {
+            let path = e.path().canonicalize().ok()?;
+            let mtime = modified(&path);
+            Some((path, mtime))
+        })
+        .collect()
+}
+
+/// Modification time of `path`, or `None` when it was deleted.
+fn modified(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|m| m.modified()).ok()
+}
+
+/// Returns the nearest directory containing `Cargo.toml`, starting at the api directory.
+fn find_project_dir(api_dir: &Path) -> Result<PathBuf> {
+    let api_dir = api_dir
+        .canonicalize()
+        .with_context(|| format!("Cannot read directory {}", api_dir.display()))?;
+    api_dir
+        .ancestors()
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map(Path::to_path_buf)
+        .with_context(|| format!("No Cargo.toml found above {}", api_dir.display()))
+}
+
+/// Scans the api directory and builds the binaries of every discovered procedure.
+#[cfg(not(tarpaulin_include))]
+fn build(config: &RpcConfig, project_dir: &Path) -> Result<Vec<DevFunction>> {
+    let start = Instant::now();
+    let manifest = parser::scan_directory(&config.input)?;
+
+    let output = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+        .args([
+            "build",
+            "--bins",
+            "--message-format=json-render-diagnostics",
+        ])
+        .current_dir(project_dir)
+        .stderr(Stdio::inherit())
+        .output()
+        .context("Failed to run cargo build")?;
+    if !output.status.success() {
+        bail!("cargo build failed");
+    }
+    let executables = parse_executables(&output.stdout);
+
+    let mut functions = Vec::new();
+    for proc in manifest.procedures {
+        let source = proc
+            .source_file
+            .canonicalize()
+            .unwrap_or_else(|_| proc.source_file.clone());
+        match executables.get(&source) {
+            Some(executable) => functions.push(DevFunction {
+                name: proc.name,
+                kind: proc.kind,
+                executable: executable.clone(),
+            }),
+            None => eprintln!(
+                "  {} No binary target for {} ({}), skipping",
+                "!".yellow().bold(),
+                proc.name,
+                proc.source_file.display(),
+            ),
+        }
+    }
+
+    println!(
+        "  {} Built {} function(s) in {:.0?}",
+        "✓".green().bold(),
+        functions.len().to_string().bold(),
+        start.elapsed(),
+    );
+    Ok(functions)
+}
+
+/// Maps the source file of every binary target to its executable, from the
+/// JSON messages printed by `cargo build --message-format=json`.
+pub fn parse_executables(cargo_stdout: &[u8]) -> HashMap<PathBuf, PathBuf> {
+    cargo_stdout
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(&line).ok())
+        .filter(|msg| msg["reason"] == "compiler-artifact")
+        .filter_map(|msg| {
+            let src = msg["target"]["src_path"].as_str()?;
+            let exe = msg["executable"].as_str()?;
+            let src = Path::new(src);
+            Some((
+                src.canonicalize().unwrap_or_else(|_| src.to_path_buf()),
+                PathBuf::from(exe),
+            ))
+        })
+        .collect()
+}
+
+/// Starts one process per binary on a free port and publishes the new routes.
+///
+/// Procedures compiled into the same binary (router mode) share its process.
+#[cfg(not(tarpaulin_include))]
+fn start(functions: &[DevFunction], project_dir: &Path, routes: &Routes) -> Result<Vec<Child>> {
+    let mut processes = Vec::with_capacity(functions.len());
+    let mut ports: HashMap<&Path, u16> = HashMap::new();
+    let mut table = HashMap::with_capacity(functions.len());
+    for function in functions {
+        if let Some(&port) = ports.get(function.executable.as_path()) {
+            table.insert(function.name.clone(), port);
+            print_route(function);
+            continue;
+        }
+        let spawned = TcpListener::bind(("127.0.0.1", 0))
+            .and_then(|l| l.local_addr())
+            .context("Failed to allocate a port")
+            .and_then(|addr| {
+                let child = Command::new(&function.executable)
+                    .env(DEV_PORT_ENV, addr.port().to_string())
+                    .current_dir(project_dir)
+                    .stdin(Stdio::null())
+                    .spawn()
+                    .with_context(|| {
+                        format!("Failed to start {}", function.executable.display())
+                    })?;
+                Ok((child, addr.port()))
+            });
+        let (child, port) = match spawned {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                stop(&mut processes);
+                return Err(e);
+            }
+        };
+        processes.push(child);
+        ports.insert(&function.executable, port);
+        table.insert(function.name.clone(), port);
+        print_route(function);
+    }
+    if let Some(port) = batch_port(&table) {
+        table.insert(BATCH_ROUTE.to_owned(), port);
+        println!(
+            "    {} {} {}",
+            "→".dimmed(),
+            format!("{API_PREFIX}{BATCH_ROUTE}").bold(),
+            "(batch)".dimmed(),
+        );
+    }
+    *routes.write().unwrap_or_else(|e| e.into_inner()) = table;
+    Ok(processes)
+}
+
+/// Kills every running function process.
+#[cfg(not(tarpaulin_include))]
+fn stop(processes: &mut Vec<Child>) {
+    for mut child in processes.drain(..) {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Accepts connections forever, proxying each one on its own thread.
+#[cfg(not(tarpaulin_include))]
+fn serve(listener: &TcpListener, routes: &Routes) {
+    for stream in listener.incoming().flatten() {
+        let routes = routes.clone();
+        thread::spawn(move || {
+            let _ = proxy(stream, &routes);
+        });
+    }
+}
+
+/// Forwards a single request to the function named in its path and streams
+/// the response back (SSE included) until the function closes the connection.
+#[cfg(not(tarpaulin_include))]
+fn proxy(mut client: TcpStream, routes: &Routes) -> io::Result<()> {
+    let Some((head, body_start)) = read_head(&mut client)? else {
+        return respond_error(
+            &mut client,
+            "431 Request Header Fields Too Large",
+            "BAD_INPUT",
+            "Request headers too large",
+        );
+    };
+    let Some(path) = head.split_whitespace().nth(1) else {
+        return respond_error(
+            &mut client,
+            "400 Bad Request",
+            "BAD_INPUT",
+            "Malformed request line",
+        );
+    };
+    let Some(name) = route_name(path) else {
+        return respond_error(
+            &mut client,
+            "404 Not Found",
+            "NOT_FOUND",
+            &format!("No route for {path}"),
+        );
+    };
+    let port = routes
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(name)
+        .copied();
+    let Some(port) = port else {
+        let message = if name == BATCH_ROUTE {
+            "Batching needs exactly one router-mode function".to_owned()
+        } else {
+            format!("Procedure `{name}` not found")
+        };
+        return respond_error(&mut client, "404 Not Found", "NOT_FOUND", &message);
+    };
+    let Some(mut upstream) = connect(port) else {
+        return respond_error(
+            &mut client,
+            "502 Bad Gateway",
+            "INTERNAL",
+            &format!("Procedure `{name}` is not running"),
+        );
+    };
+
+    let client_ip = client.peer_addr().ok().map(|a| a.ip().to_string());
+    upstream.write_all(rewrite_head(&head, client_ip.as_deref()).as_bytes())?;
+    upstream.write_all(&body_start)?;
+
+    // The rest of the request body flows upstream on a helper thread while
+    // the response is copied back here, so streamed responses are not buffered.
+    let mut client_reader = client.try_clone()?;
+    let mut upstream_writer = upstream.try_clone()?;
+    thread::spawn(move || io::copy(&mut client_reader, &mut upstream_writer));
+
+    let copied = io::copy(&mut upstream, &mut client);
+    let _ = client.shutdown(Shutdown::Both);
+    copied.map(|_| ())
+}
+
+/// Connects to a function, retrying while it starts up.
+fn connect(port: u16) -> Option<TcpStream> {
+    let deadline = Instant::now() + CONNECT_TIMEOUT;
+    loop {
+        match TcpStream::connect(("127.0.0.1", port)) {
+            Ok(stream) => return Some(stream),
+            Err(_) if Instant::now() < deadline => thread::sleep(Duration::from_millis(50)),
+            Err(_) => return None,
+        }
+    }
+}
+
+/// Reads the request line and headers. Returns them as text together with
+/// any body bytes already read, or `None` when the head is too large.
+fn read_head(stream: &mut impl Read) -> io::Result<Option<(String, Vec<u8>)>> {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 4096];
+    loop {
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            let body = buf.split_off(end + 4);
+            return Ok(Some((String::from_utf8_lossy(&buf).into_owned(), body)));
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            return Ok(None);
+        }
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+}
+
+/// Extracts the procedure name from a request target such as `/api/hello?input=1`.
+pub fn route_name(target: &str) -> Option<&str> {
+    let path = target.split(['?', '#']).next()?;
+    let name = path.strip_prefix(API_PREFIX)?.trim_end_matches('/');
+    (!name.is_empty() && !name.contains('/')).then_some(name)
+}
+
+/// Port of the process that answers `/api/_batch`: the only one serving more
+/// than one procedure, i.e. a `metaxy::router!` binary.
+///
+/// A standalone function would run its handler on the batch body, so with no
+/// router, or several of them, batches are not routed.
+pub fn batch_port(routes: &HashMap<String, u16>) -> Option<u16> {
+    let mut counts: HashMap<u16, usize> = HashMap::new();
+    for &port in routes.values() {
+        *counts.entry(port).or_default() += 1;
+    }
+    let mut routers = counts
+        .into_iter()
+        .filter(|&(_, n)| n > 1)
+        .map(|(port, _)| port);
+    match (routers.next(), routers.next()) {
+        (Some(port), None) => Some(port),
+        _ => None,
+    }
+}
+
+/// Rewrites a request head for the upstream function: forces
+/// `Connection: close` so each proxied request maps to one connection, and
+/// adds `X-Forwarded-For` like Vercel's edge does.
+pub fn rewrite_head(head: &str, client_ip: Option<&str>) -> String {
+    let mut lines = head.split("\r\n").filter(|line| !line.is_empty());
+    let mut out = String::with_capacity(head.len() + 64);
+    if let Some(request_line) = lines.next() {
+        out.push_str(request_line);
+        out.push_str("\r\n");
+    }
+    let mut has_forwarded_for = false;
+    for line in lines {
+        let name = line.split(':').next().unwrap_or_default().trim();
+        if name.eq_ignore_ascii_case("connection") || name.eq_ignore_ascii_case("keep-alive") {
+            continue;
+        }
+        has_forwarded_for |= name.eq_ignore_ascii_case("x-forwarded-for");
+        out.push_str(line);
+        out.push_str("\r\n");
+    }
+    if !has_forwarded_for && let Some(ip) = client_ip {
+        out.push_str(&format!("X-Forwarded-For: {ip}\r\n"));
+    }
+    out.push_str("Connection: close\r\n\r\n");
+    out
+}
+
+/// Writes an error in the same envelope the generated handlers use.
+#[cfg(not(tarpaulin_include))]
+fn respond_error(
+    client: &mut TcpStream,
+    status: &str,
+    code: &str,
+    message: &str,
+) -> io::Result<()> {
+    let body =
+        serde_json::json!({ "error": { "type": "error", "code": code, "message": message } })
+            .to_string();
+    write!(
+        client,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len(),
+    )
+}
+
+#[cfg(not(tarpaulin_include))]
+fn print_route(function: &DevFunction) {
+    println!(
+        "    {} {} {}",
+        "→".dimmed(),
+        format!("{API_PREFIX}{}", function.name).bold(),
+        format!("({:?})", function.kind).to_lowercase().dimmed(),
+    );
+}
+
+#[cfg(not(tarpaulin_include))]
+fn print_banner(config: &RpcConfig, project_dir: &Path) {
+    println!();
+    println!("  {} {}", "metaxy".bold(), "dev server".cyan());
+    println!("  {} {}", "api dir:".dimmed(), config.input.dir.display());
+    println!("  {} {}", "project:".dimmed(), project_dir.display());
+    println!();
+}
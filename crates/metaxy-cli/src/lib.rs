@@ -6,7 +6,7 @@
 //!
 //! # Binary
 //!
-//! The installed binary is called `metaxy` and provides three subcommands:
+//! The installed binary is called `metaxy` and provides four subcommands:
 //!
 //! - **`metaxy scan`** — parse a directory and print discovered procedures as
 //!   human-readable text plus a JSON manifest.
@@ -14,6 +14,8 @@
 //!   type) and `rpc-client.ts` (typed `RpcClient` + `createRpcClient` factory).
 //! - **`metaxy watch`** — same as `generate`, but re-runs automatically whenever
 //!   a `.rs` file changes (configurable debounce).
+//! - **`metaxy dev`** — build every procedure binary and serve them on one
+//!   local port under `/api/<name>`, rebuilding and restarting on changes.
 //!
 //! # Architecture
 //!
@@ -36,10 +38,13 @@
 //! - [`codegen::vue`] — Vue 3 Composition API (`useQuery`, `useMutation`).
 //! - [`codegen::solid`] — SolidJS reactive primitives (`createQuery`, `createMutation`).
 //! - [`watch`] — wraps `generate` in a file-watcher loop with debouncing.
+//! - [`dev`] — local dev server that proxies `/api/<name>` to per-function
+//!   processes, reusing the `watch` debouncer to rebuild on changes.
 
 pub mod codegen;
 pub mod commands;
 pub mod config;
+pub mod dev;
 pub mod model;
 pub mod parser;
 pub mod watch;
@@ -4,7 +4,7 @@ use anyhow::Result;
 use clap::{Parser, Subcommand};
 
 use metaxy_cli::config::FieldNaming;
-use metaxy_cli::{commands, config, watch};
+use metaxy_cli::{commands, config, dev, watch};
 
 #[derive(Parser)]
 #[command(
@@ -24,7 +24,7 @@ struct Cli {
     command: Command,
 }
 
-/// Shared input arguments for scan, generate, and watch commands.
+/// Shared input arguments for scan, generate, watch, and dev commands.
 #[derive(clap::Args)]
 struct InputArgs {
     /// Path to the directory containing Rust lambda source files
@@ -133,6 +133,24 @@ enum Command {
         #[arg(long)]
         clear_screen: bool,
     },
+
+    /// Build every procedure and serve them locally under /api/<name>, rebuilding on changes
+    Dev {
+        #[command(flatten)]
+        input: InputArgs,
+
+        /// Port for the local dev server (default: 3000)
+        #[arg(short, long)]
+        port: Option<u16>,
+
+        /// File watcher debounce interval in milliseconds
+        #[arg(long)]
+        debounce_ms: Option<u64>,
+
+        /// Clear the terminal before each rebuild
+        #[arg(long)]
+        clear_screen: bool,
+    },
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -209,8 +227,28 @@ fn main() -> Result<()> {
                 bigint_types: args.bigint_types,
                 debounce_ms,
                 clear_screen,
+                ..config::CliOverrides::default()
             })?;
             watch::run(&cfg)
         }
+        Command::Dev {
+            input,
+            port,
+            debounce_ms,
+            clear_screen,
+        } => {
+            let cfg = config::resolve(config::CliOverrides {
+                config: cli.config,
+                no_config: cli.no_config,
+                dir: input.dir,
+                include: input.include,
+                exclude: input.exclude,
+                debounce_ms,
+                clear_screen,
+                port,
+                ..config::CliOverrides::default()
+            })?;
+            dev::run(&cfg)
+        }
     }
 }
@@ -232,6 +232,12 @@ pub struct Procedure {
     pub input: Option<RustType>,
     /// Return type; `None` means unit return
     pub output: Option<RustType>,
+    /// Error type `E` from a `Result<T, E>` return; `None` means the handler is infallible
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RustType>,
+    /// Final value of a stream handler returning `R` or `Result<R, E>`, sent as its `done` event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<RustType>,
     /// Source file this procedure was extracted from
     pub source_file: PathBuf,
     /// Doc comment extracted from `///` lines
@@ -243,6 +249,44 @@ pub struct Procedure {
     /// Whether this mutation is marked as idempotent (safe to retry)
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub idempotent: bool,
+    /// Rate limit from the `rate_limit = "..."` attribute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    /// Whether this query answers `If-None-Match` with 304 (`etag` flag)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub etag: bool,
+    /// Wire format of a stream (`format = "..."` attribute)
+    #[serde(default, skip_serializing_if = "StreamFormat::is_sse")]
+    pub stream_format: StreamFormat,
+    /// Whether a stream is served over `GET` (`method = "GET"` attribute)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stream_get: bool,
+}
+
+/// Wire format of a stream procedure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamFormat {
+    /// Server-sent events (the default).
+    #[default]
+    Sse,
+    /// One JSON value per line.
+    Ndjson,
+    /// Raw byte chunks.
+    Bytes,
+}
+
+impl StreamFormat {
+    pub fn is_sse(&self) -> bool {
+        *self == Self::Sse
+    }
+}
+
+/// Allowed request rate of a procedure: `requests` per `window_ms`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub window_ms: u64,
 }
 
 /// All user-defined struct types found in the scanned source files.
@@ -10,7 +10,8 @@ use super::serde as serde_attr;
 use super::types::{extract_rust_type, extract_struct_fields, extract_tuple_fields};
 use crate::config::InputConfig;
 use crate::model::{
-    EnumDef, EnumVariant, Manifest, Procedure, ProcedureKind, RustType, StructDef, VariantKind,
+    EnumDef, EnumVariant, Manifest, Procedure, ProcedureKind, RateLimit, RustType, StreamFormat,
+    StructDef, VariantKind,
 };
 
 /// RPC attribute names recognized by the parser.
@@ -194,46 +195,93 @@ fn try_extract_procedure(func: &ItemFn, path: &Path) -> Option<Procedure> {
         if is_stream_sender_type(&pat.ty) {
             return None;
         }
+        // Skip the Guarded parameter — it's produced by the guard function.
+        if is_guarded_type(&pat.ty) {
+            return None;
+        }
+        // Skip `FromRequest` extractors — their values come from the request itself.
+        if is_extractor_param(pat) {
+            return None;
+        }
         Some(extract_rust_type(&pat.ty))
     });
 
-    // For streams, the output type comes from the StreamSender<T> parameter.
+    let returned = match &func.sig.output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ty) => Some(&**ty),
+    };
+    let stream_item = returned.and_then(extract_stream_item_type);
+
+    // For streams returning `impl Stream<Item = T>`, the output type is `T`
+    // (or the `Ok` type of a `Result` item). Otherwise it comes from the
+    // StreamSender<T> parameter and the return type is the final `done` value.
     // For queries/mutations, it comes from the function return type.
-    let output = if kind == ProcedureKind::Stream {
-        func.sig.inputs.iter().find_map(|arg| {
-            let FnArg::Typed(pat) = arg else { return None };
-            extract_stream_chunk_type(&pat.ty)
-        })
+    let (output, error, result) = if kind == ProcedureKind::Stream
+        && let Some(item) = stream_item
+    {
+        let (chunk, error) = split_result(extract_rust_type(item));
+        (chunk, error, None)
     } else {
-        match &func.sig.output {
-            ReturnType::Default => None,
-            ReturnType::Type(_, ty) => {
-                let rust_type = extract_rust_type(ty);
-                // Unwrap Result<T, _> to just T
-                if rust_type.name == "Result" && !rust_type.generics.is_empty() {
-                    rust_type.generics.into_iter().next()
-                } else {
-                    Some(rust_type)
-                }
-            }
+        let (returned, error) = match returned {
+            Some(ty) => split_result(extract_rust_type(ty)),
+            None => (None, None),
+        };
+        if kind == ProcedureKind::Stream {
+            let chunk = func.sig.inputs.iter().find_map(|arg| {
+                let FnArg::Typed(pat) = arg else { return None };
+                extract_stream_chunk_type(&pat.ty)
+            });
+            (chunk, error, returned)
+        } else {
+            (returned.map(unwrap_rpc_response), error, None)
         }
     };
 
     let timeout_ms = extract_timeout_ms(&func.attrs);
     let idempotent = extract_idempotent(&func.attrs);
+    let rate_limit = extract_rate_limit(&func.attrs);
+    let etag = extract_etag(&func.attrs);
+    let stream_format = extract_stream_format(&func.attrs);
+    let stream_get = extract_stream_get(&func.attrs);
 
     Some(Procedure {
         name,
         kind,
         input,
         output,
+        error,
+        result,
         source_file: path.to_path_buf(),
         docs,
         timeout_ms,
         idempotent,
+        rate_limit,
+        etag,
+        stream_format,
+        stream_get,
     })
 }
 
+/// Unwraps a `Result<T, E>` into `T` and `E`.
+fn split_result(ty: RustType) -> (Option<RustType>, Option<RustType>) {
+    if ty.name == "Result" && !ty.generics.is_empty() {
+        let mut generics = ty.generics.into_iter();
+        (generics.next(), generics.next())
+    } else {
+        (Some(ty), None)
+    }
+}
+
+/// Unwraps `RpcResponse<T>` into `T` — the wrapper only carries response
+/// metadata (status, headers, cookies) and serializes as `T`.
+fn unwrap_rpc_response(mut ty: RustType) -> RustType {
+    let last_segment = ty.name.rsplit("::").next().unwrap_or_default();
+    if last_segment == "RpcResponse" && ty.generics.len() == 1 {
+        return ty.generics.remove(0);
+    }
+    ty
+}
+
 /// Checks function attributes for `#[rpc_query]`, `#[rpc_mutation]`, or `#[rpc_stream]`.
 fn detect_rpc_kind(attrs: &[Attribute]) -> Option<ProcedureKind> {
     for attr in attrs {
@@ -305,28 +353,71 @@ fn is_headers_type(ty: &syn::Type) -> bool {
     false
 }
 
-/// Returns `true` if the type path ends with `StreamSender` (e.g. `StreamSender`, `metaxy::StreamSender`).
+/// Returns `true` if the type path ends with `StreamSender`, `NdjsonSender`
+/// or `ByteSender` (e.g. `StreamSender`, `metaxy::NdjsonSender`).
 ///
-/// Used to skip the `StreamSender` parameter when extracting RPC input types,
+/// Used to skip the sender parameter when extracting RPC input types,
 /// since it is an internal streaming channel rather than user-provided input.
 fn is_stream_sender_type(ty: &syn::Type) -> bool {
     if let syn::Type::Path(type_path) = ty
         && let Some(segment) = type_path.path.segments.last()
     {
-        return segment.ident == "StreamSender";
+        return ["StreamSender", "NdjsonSender", "ByteSender"]
+            .iter()
+            .any(|name| segment.ident == name);
+    }
+    false
+}
+
+/// Returns `true` if the type path ends with `Guarded` (e.g. `Guarded<User>`, `metaxy::Guarded<User>`).
+///
+/// Used to skip the guard context parameter when extracting RPC input types,
+/// since its value comes from the `guard` function rather than the client.
+fn is_guarded_type(ty: &syn::Type) -> bool {
+    if let syn::Type::Path(type_path) = ty
+        && let Some(segment) = type_path.path.segments.last()
+    {
+        return segment.ident == "Guarded";
     }
     false
 }
 
-/// Extracts the chunk type `T` from `StreamSender<T>`.
+/// Built-in `metaxy::FromRequest` types recognised by name.
+const BUILTIN_EXTRACTORS: &[&str] = &[
+    "Cookies",
+    "ClientIp",
+    "RequestInfo",
+    "VercelContext",
+    "LastEventId",
+];
+
+/// Returns `true` if the parameter is a `FromRequest` extractor: marked
+/// `#[from_request]` or typed as one of the built-in extractors.
 ///
-/// Returns `None` for bare `StreamSender` (no type parameter).
+/// Mirrors the proc macro's classification so such parameters are never
+/// mistaken for the RPC input.
+fn is_extractor_param(pat: &syn::PatType) -> bool {
+    if pat.attrs.iter().any(|a| a.path().is_ident("from_request")) {
+        return true;
+    }
+    if let syn::Type::Path(type_path) = &*pat.ty
+        && let Some(segment) = type_path.path.segments.last()
+    {
+        return BUILTIN_EXTRACTORS.iter().any(|name| segment.ident == name);
+    }
+    false
+}
+
+/// Extracts the chunk type `T` from `StreamSender<T>` or `NdjsonSender<T>`.
+///
+/// Returns `None` for a bare sender (no type parameter) and for `ByteSender`,
+/// whose chunks are always bytes.
 fn extract_stream_chunk_type(ty: &syn::Type) -> Option<RustType> {
     let syn::Type::Path(type_path) = ty else {
         return None;
     };
     let segment = type_path.path.segments.last()?;
-    if segment.ident != "StreamSender" {
+    if segment.ident != "StreamSender" && segment.ident != "NdjsonSender" {
         return None;
     }
     let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
@@ -340,6 +431,29 @@ fn extract_stream_chunk_type(ty: &syn::Type) -> Option<RustType> {
     None
 }
 
+/// Extracts the item type `T` from an `impl Stream<Item = T>` return type.
+fn extract_stream_item_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::ImplTrait(impl_trait) = ty else {
+        return None;
+    };
+    impl_trait.bounds.iter().find_map(|bound| {
+        let syn::TypeParamBound::Trait(bound) = bound else {
+            return None;
+        };
+        let segment = bound.path.segments.last()?;
+        if segment.ident != "Stream" {
+            return None;
+        }
+        let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+            return None;
+        };
+        args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::AssocType(assoc) if assoc.ident == "Item" => Some(&assoc.ty),
+            _ => None,
+        })
+    })
+}
+
 /// Extracts the `timeout` value from `#[rpc_query(timeout = "30s")]` or `#[rpc_mutation(timeout = "30s")]`.
 ///
 /// Returns `Some(milliseconds)` if a valid timeout is found, `None` otherwise.
@@ -372,6 +486,51 @@ fn extract_timeout_ms(attrs: &[Attribute]) -> Option<u64> {
     None
 }
 
+/// Extracts the `rate_limit` value from `#[rpc_mutation(rate_limit = "10/m")]`
+/// and the other RPC attributes.
+///
+/// The window is either a bare unit (`10/m`) or a duration (`5/30s`).
+/// Lenient: returns `None` on any parse error instead of failing the scan.
+fn extract_rate_limit(attrs: &[Attribute]) -> Option<RateLimit> {
+    for attr in attrs {
+        if !attr.path().is_ident(RPC_QUERY_ATTR)
+            && !attr.path().is_ident(RPC_MUTATION_ATTR)
+            && !attr.path().is_ident(RPC_STREAM_ATTR)
+        {
+            continue;
+        }
+        let Ok(parsed) = attr.parse_args_with(
+            syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+        ) else {
+            continue;
+        };
+        for meta in &parsed {
+            if let syn::Meta::NameValue(nv) = meta
+                && nv.path.is_ident("rate_limit")
+                && let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) = &nv.value
+            {
+                let value = s.value();
+                let (requests, window) = value.split_once('/')?;
+                let requests: u32 = requests.trim().parse().ok().filter(|&n| n > 0)?;
+                let window = window.trim();
+                let window_ms = if matches!(window, "s" | "m" | "h" | "d") {
+                    parse_duration_to_ms(&format!("1{window}"))?
+                } else {
+                    parse_duration_to_ms(window)?
+                };
+                return Some(RateLimit {
+                    requests,
+                    window_ms,
+                });
+            }
+        }
+    }
+    None
+}
+
 /// Parses a human-readable duration shorthand into milliseconds.
 ///
 /// Lenient: returns `None` on any parse error instead of failing the scan.
@@ -399,8 +558,62 @@ fn parse_duration_to_ms(s: &str) -> Option<u64> {
 /// Only checks `RPC_MUTATION_ATTR` attributes. Lenient: silently ignores
 /// `rpc_query(idempotent)` (the proc macro rejects it at compile time).
 fn extract_idempotent(attrs: &[Attribute]) -> bool {
+    has_bare_flag(attrs, RPC_MUTATION_ATTR, "idempotent")
+}
+
+/// Extracts the bare `etag` flag from `#[rpc_query(etag)]`.
+///
+/// Only checks `RPC_QUERY_ATTR` attributes, like [`extract_idempotent`].
+fn extract_etag(attrs: &[Attribute]) -> bool {
+    has_bare_flag(attrs, RPC_QUERY_ATTR, "etag")
+}
+
+/// Extracts the `format` value from `#[rpc_stream(format = "ndjson")]`.
+///
+/// Unknown formats fall back to SSE; the macro rejects them at compile time.
+fn extract_stream_format(attrs: &[Attribute]) -> StreamFormat {
+    match stream_attr_value(attrs, "format").as_deref() {
+        Some("ndjson") => StreamFormat::Ndjson,
+        Some("bytes") => StreamFormat::Bytes,
+        _ => StreamFormat::Sse,
+    }
+}
+
+/// Extracts `method = "GET"` from `#[rpc_stream(method = "GET")]`.
+fn extract_stream_get(attrs: &[Attribute]) -> bool {
+    stream_attr_value(attrs, "method").is_some_and(|m| m.eq_ignore_ascii_case("GET"))
+}
+
+/// Returns the string value of `key = "..."` in an `#[rpc_stream(...)]` attribute.
+fn stream_attr_value(attrs: &[Attribute], key: &str) -> Option<String> {
+    for attr in attrs {
+        if !attr.path().is_ident(RPC_STREAM_ATTR) {
+            continue;
+        }
+        let Ok(parsed) = attr.parse_args_with(
+            syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+        ) else {
+            continue;
+        };
+        for meta in &parsed {
+            if let syn::Meta::NameValue(nv) = meta
+                && nv.path.is_ident(key)
+                && let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) = &nv.value
+            {
+                return Some(s.value());
+            }
+        }
+    }
+    None
+}
+
+/// Checks whether an `#[<macro_attr>(...)]` attribute contains the bare `flag`.
+fn has_bare_flag(attrs: &[Attribute], macro_attr: &str, flag: &str) -> bool {
     for attr in attrs {
-        if !attr.path().is_ident(RPC_MUTATION_ATTR) {
+        if !attr.path().is_ident(macro_attr) {
             continue;
         }
         let Ok(parsed) = attr.parse_args_with(
@@ -410,7 +623,7 @@ fn extract_idempotent(attrs: &[Attribute]) -> bool {
         };
         for meta in &parsed {
             if let syn::Meta::Path(path) = meta
-                && path.is_ident("idempotent")
+                && path.is_ident(flag)
             {
                 return true;
             }
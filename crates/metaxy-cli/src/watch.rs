@@ -35,7 +35,33 @@ pub fn run(config: &RpcConfig) -> Result<()> {
         print_error(&e);
     }
 
-    // Set up file watcher with debouncing
+    watch_rs_changes(config, &running, |changed| {
+        if config.watch.clear_screen {
+            clear_screen();
+            print_banner(config);
+        }
+
+        print_change(changed);
+
+        if let Err(e) = generate(config) {
+            print_error(&e);
+        }
+    })?;
+
+    println!("\n  {} Stopped watching.", "●".dimmed());
+    Ok(())
+}
+
+/// Watches the api directory and calls `on_change` with the changed `.rs`
+/// files after each debounced batch of events.
+///
+/// Blocks until `running` is cleared (by the Ctrl+C handler).
+#[cfg(not(tarpaulin_include))]
+pub(crate) fn watch_rs_changes(
+    config: &RpcConfig,
+    running: &AtomicBool,
+    mut on_change: impl FnMut(&[&Path]),
+) -> Result<()> {
     let (tx, rx) = mpsc::channel();
     let debounce_duration = std::time::Duration::from_millis(config.watch.debounce_ms);
 
@@ -68,16 +94,7 @@ pub fn run(config: &RpcConfig) -> Result<()> {
                         .map(|e| e.path.as_path())
                         .collect();
 
-                    if config.watch.clear_screen {
-                        clear_screen();
-                        print_banner(config);
-                    }
-
-                    print_change(&changed);
-
-                    if let Err(e) = generate(config) {
-                        print_error(&e);
-                    }
+                    on_change(&changed);
                 }
             }
             Ok(Err(errs)) => {
@@ -88,7 +105,6 @@ pub fn run(config: &RpcConfig) -> Result<()> {
         }
     }
 
-    println!("\n  {} Stopped watching.", "●".dimmed());
     Ok(())
 }
 
@@ -164,7 +180,7 @@ fn print_banner(config: &RpcConfig) {
 }
 
 #[cfg(not(tarpaulin_include))]
-fn print_change(paths: &[&Path]) {
+pub(crate) fn print_change(paths: &[&Path]) {
     for p in paths {
         let name = p
             .file_name()
@@ -175,12 +191,12 @@ fn print_change(paths: &[&Path]) {
 }
 
 #[cfg(not(tarpaulin_include))]
-fn clear_screen() {
+pub(crate) fn clear_screen() {
     print!("\x1B[2J\x1B[H");
 }
 
 #[cfg(not(tarpaulin_include))]
-fn print_error(err: &anyhow::Error) {
+pub(crate) fn print_error(err: &anyhow::Error) {
     eprintln!("  {} {err:#}", "✗".red().bold());
     for cause in err.chain().skip(1) {
         eprintln!("    {} {cause}", "caused by:".dimmed());
@@ -30,10 +30,42 @@ fn reexports_procedures_type() {
 fn contains_rpc_error_class() {
     let manifest = common::make_manifest(vec![]);
     let output = generate_client_file(&manifest, "./rpc-types", false);
-    assert!(output.contains("export class RpcError extends Error"));
+    assert!(output.contains("export class RpcError<E = unknown> extends Error"));
     assert!(output.contains("this.status = status"));
 }
 
+#[test]
+fn rpc_error_exposes_typed_payload() {
+    let manifest = common::make_manifest(vec![]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("export class RpcError<E = unknown> extends Error"));
+    assert!(output.contains("readonly payload: E | undefined;"));
+    assert!(output.contains("this.payload = error?.data;"));
+}
+
+#[test]
+fn rpc_error_exposes_field_errors() {
+    let manifest = common::make_manifest(vec![]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(
+        output.contains("export interface RpcFieldError {\n  path: string;\n  message: string;\n}")
+    );
+    assert!(output.contains("readonly fieldErrors: RpcFieldError[];"));
+    assert!(output.contains("this.fieldErrors = error?.fields ?? [];"));
+    assert!(output.contains("readonly code: RpcErrorCode | undefined;"));
+    assert!(output.contains("this.code = error?.code;"));
+}
+
+#[test]
+fn rpc_error_code_union_is_exported() {
+    let manifest = common::make_manifest(vec![]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("export type RpcErrorCode =\n  | \"BAD_INPUT\""));
+    for code in ["METHOD_NOT_ALLOWED", "TIMEOUT", "HANDLER_ERROR", "INTERNAL"] {
+        assert!(output.contains(&format!("  | \"{code}\"")));
+    }
+}
+
 #[test]
 fn contains_fetch_helper() {
     let manifest = common::make_manifest(vec![]);
@@ -51,7 +83,7 @@ fn generates_query_method() {
     )]);
     let output = generate_client_file(&manifest, "./rpc-types", false);
     assert!(output.contains("query(key: \"hello\", input: string): Promise<string>"));
-    assert!(output.contains("rpcFetch(config, \"GET\", key, input, callOptions)"));
+    assert!(output.contains("rpcFetch(config, \"GET\", key, input, callOptions, etagCache)"));
     assert!(output.contains("export interface RpcClient"));
 }
 
@@ -175,7 +207,7 @@ fn uses_get_for_queries_post_for_mutations() {
         ),
     ]);
     let output = generate_client_file(&manifest, "./rpc-types", false);
-    assert!(output.contains("rpcFetch(config, \"GET\", key, input, callOptions)"));
+    assert!(output.contains("rpcFetch(config, \"GET\", key, input, callOptions, etagCache)"));
     assert!(
         output.contains(
             "rpcFetch(config, \"POST\", key, args[0], args[1] as CallOptions | undefined)"
@@ -257,20 +289,32 @@ fn test_jsdoc_on_overload() {
             kind: ProcedureKind::Query,
             input: Some(RustType::simple("String")),
             output: Some(RustType::simple("String")),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/hello.rs"),
             docs: Some("Say hello.".to_string()),
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         },
         Procedure {
             name: "reset".to_string(),
             kind: ProcedureKind::Mutation,
             input: None,
             output: Some(RustType::simple("bool")),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/reset.rs"),
             docs: Some("Reset state.".to_string()),
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         },
     ]);
     let output = generate_client_file(&manifest, "./rpc-types", true);
@@ -289,10 +333,16 @@ fn test_jsdoc_on_void_query_overload() {
         kind: ProcedureKind::Query,
         input: None,
         output: Some(RustType::simple("String")),
+        error: None,
+        result: None,
         source_file: PathBuf::from("api/version.rs"),
         docs: Some("Get version.".to_string()),
         timeout_ms: None,
         idempotent: false,
+        rate_limit: None,
+        etag: false,
+        stream_format: StreamFormat::Sse,
+        stream_get: false,
     }]);
     let output = generate_client_file(&manifest, "./rpc-types", true);
     assert!(output.contains("  /** Get version. */\n  query(key: \"version\"): Promise<string>;"));
@@ -305,10 +355,16 @@ fn test_jsdoc_on_non_void_mutation_overload() {
         kind: ProcedureKind::Mutation,
         input: Some(RustType::simple("String")),
         output: Some(RustType::simple("bool")),
+        error: None,
+        result: None,
         source_file: PathBuf::from("api/update.rs"),
         docs: Some("Update item.".to_string()),
         timeout_ms: None,
         idempotent: false,
+        rate_limit: None,
+        etag: false,
+        stream_format: StreamFormat::Sse,
+        stream_get: false,
     }]);
     let output = generate_client_file(&manifest, "./rpc-types", true);
     assert!(output.contains(
@@ -323,10 +379,16 @@ fn test_no_jsdoc_on_overload_when_disabled() {
         kind: ProcedureKind::Query,
         input: Some(RustType::simple("String")),
         output: Some(RustType::simple("String")),
+        error: None,
+        result: None,
         source_file: PathBuf::from("api/hello.rs"),
         docs: Some("Say hello.".to_string()),
         timeout_ms: None,
         idempotent: false,
+        rate_limit: None,
+        etag: false,
+        stream_format: StreamFormat::Sse,
+        stream_get: false,
     }]);
     let output = generate_client_file(&manifest, "./rpc-types", false);
     assert!(!output.contains("/**"));
@@ -570,20 +632,32 @@ fn snapshot_client_with_jsdoc() {
             kind: ProcedureKind::Query,
             input: Some(RustType::simple("String")),
             output: Some(RustType::simple("String")),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/hello.rs"),
             docs: Some("Say hello to someone.".to_string()),
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         },
         Procedure {
             name: "reset".to_string(),
             kind: ProcedureKind::Mutation,
             input: None,
             output: Some(RustType::simple("bool")),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/reset.rs"),
             docs: Some("Reset all state.".to_string()),
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         },
     ]);
     let output = generate_client_file(&manifest, "./rpc-types", true);
@@ -887,6 +961,62 @@ fn snapshot_client_with_call_options() {
     insta::assert_snapshot!(output);
 }
 
+// --- Batching tests ---
+
+#[test]
+fn config_has_batch_option() {
+    let manifest = common::make_manifest(vec![]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("batch?: BatchOptions;"));
+    assert!(output.contains("export interface BatchOptions {"));
+    assert!(output.contains("maxSize?: number;"));
+    assert!(output.contains("windowMs?: number;"));
+}
+
+#[test]
+fn batched_queries_keep_dedup_and_signal() {
+    let manifest = common::make_manifest(vec![common::make_query(
+        "hello",
+        Some(RustType::simple("String")),
+        Some(RustType::simple("String")),
+    )]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("const batcher = config.batch ? createBatcher(config) : undefined;"));
+    assert!(output.contains("? batcher(key, input, callOptions?.signal)"));
+    assert!(output.contains("const promise = send().finally(() => inflight.delete(k));"));
+    assert!(output.contains("return wrapWithSignal(send(), callOptions?.signal);"));
+    assert!(output.contains("const url = `${config.baseUrl}/_batch`;"));
+    assert!(output.contains("queue.splice(index, 1);"));
+}
+
+#[test]
+fn batched_calls_honor_signal_after_flush() {
+    let manifest = common::make_manifest(vec![common::make_query(
+        "hello",
+        Some(RustType::simple("String")),
+        Some(RustType::simple("String")),
+    )]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("signal?: AbortSignal;\n  resolve: (value: unknown) => void;"));
+    assert!(output.contains(
+        "rpcFetch(config, \"GET\", call.procedure, call.input, { signal: call.signal })"
+    ));
+    assert!(output.contains("signal?.removeEventListener(\"abort\", onAbort); resolve(value);"));
+    assert!(output.contains("signal?.removeEventListener(\"abort\", onAbort); reject(reason);"));
+}
+
+#[test]
+fn batching_omitted_when_no_queries() {
+    let manifest = common::make_manifest(vec![common::make_mutation(
+        "create_item",
+        Some(RustType::simple("CreateInput")),
+        Some(RustType::simple("Item")),
+    )]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(!output.contains("createBatcher"));
+    assert!(!output.contains("rpcBatch"));
+}
+
 // --- Procedure timeout tests ---
 
 #[test]
@@ -958,6 +1088,134 @@ fn retry_guard_checks_idempotent() {
     assert!(output.contains("IDEMPOTENT_MUTATIONS.has(procedure)"));
 }
 
+// --- Trace context tests ---
+
+#[test]
+fn trace_option_and_helpers_emitted() {
+    let manifest = common::make_manifest(vec![]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("export interface TraceContext {"));
+    assert!(output.contains("trace?: boolean | ((procedure: string) => TraceContext);"));
+    assert!(output.contains("export function newTraceContext(): TraceContext {"));
+    assert!(output.contains("headers[\"traceparent\"] = ctx.traceparent;"));
+    assert!(output.contains("headers[\"x-request-id\"] = ctx.requestId;"));
+}
+
+#[test]
+fn fetch_and_stream_send_trace_headers() {
+    let manifest = common::make_manifest(vec![common::make_stream(
+        "events",
+        None,
+        Some(RustType::simple("String")),
+    )]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert_eq!(
+        output
+            .matches("...traceHeaders(config, procedure),")
+            .count(),
+        2
+    );
+}
+
+// --- Rate-limited procedures tests ---
+
+#[test]
+fn rate_limited_procedures_set_emitted() {
+    let mut proc = common::make_mutation(
+        "login",
+        Some(RustType::simple("String")),
+        Some(RustType::simple("String")),
+    );
+    proc.rate_limit = Some(RateLimit {
+        requests: 10,
+        window_ms: 60_000,
+    });
+    let manifest = common::make_manifest(vec![proc]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("RATE_LIMITED_PROCEDURES: Set<string> = new Set([\"login\"])"));
+}
+
+#[test]
+fn retry_honours_retry_after_for_rate_limited_procedures() {
+    let manifest = common::make_manifest(vec![]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("RATE_LIMITED_PROCEDURES: Set<string> = new Set()"));
+    assert!(output.contains("res.status === 429 && RATE_LIMITED_PROCEDURES.has(procedure)"));
+    assert!(output.contains("res.headers.get(\"Retry-After\")"));
+    assert!(output.contains("Math.max(d, retryAfterMs)"));
+}
+
+// --- ETag tests ---
+
+#[test]
+fn etag_queries_reuse_cached_data_on_304() {
+    let mut proc = common::make_query(
+        "stats",
+        None,
+        Some(RustType::with_generics(
+            "Vec",
+            vec![RustType::simple("u32")],
+        )),
+    );
+    proc.etag = true;
+    let manifest = common::make_manifest(vec![proc]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("ETAG_QUERIES: Set<string> = new Set([\"stats\"])"));
+    assert!(output.contains("baseHeaders[\"If-None-Match\"] = cached.etag"));
+    assert!(output.contains("res.status === 304 && cached"));
+    assert!(output.contains("return cached.data;"));
+    assert!(output.contains("ETAG_QUERIES.has(key) ? { entries: etags, key: k } : undefined"));
+}
+
+// --- Large query input tests ---
+
+#[test]
+fn long_query_input_falls_back_to_post_body() {
+    let manifest = common::make_manifest(vec![]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("maxQueryLength?: number;"));
+    assert!(output.contains("const DEFAULT_MAX_QUERY_LENGTH = 2000;"));
+    assert!(output.contains(
+        "url.length + query.length <= (config.maxQueryLength ?? DEFAULT_MAX_QUERY_LENGTH)"
+    ));
+    assert!(output.contains("httpMethod = \"POST\";"));
+    assert!(output.contains("baseHeaders[\"X-Metaxy-Input\"] = \"body\";"));
+    assert!(output.contains("etagCache = undefined;"));
+    assert!(
+        output
+            .contains("const init: RequestInit = { method: httpMethod, headers: reqCtx.headers };")
+    );
+    // Retries still follow the procedure kind, not the transport.
+    assert!(output.contains("(method === \"GET\" || IDEMPOTENT_MUTATIONS.has(procedure))"));
+}
+
+#[test]
+fn query_input_can_use_base64url() {
+    let manifest = common::make_manifest(vec![]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("queryEncoding?: \"json\" | \"base64url\";"));
+    assert!(output.contains("function base64url(text: string): string {"));
+    assert!(output.contains("`?input64=${base64url(serialized)}`"));
+}
+
+// --- MessagePack format tests ---
+
+#[test]
+fn msgpack_format_switches_body_encoding() {
+    let manifest = common::make_manifest(vec![]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("format?: \"json\" | \"msgpack\";"));
+    assert!(output.contains("function encodeMsgpack(value: unknown): ArrayBuffer {"));
+    assert!(output.contains("function decodeMsgpack(buffer: ArrayBuffer): unknown {"));
+    assert!(output.contains("if (msgpack) baseHeaders[\"Accept\"] = \"application/msgpack\";"));
+    assert!(output.contains("baseHeaders[\"Content-Type\"] = contentType;"));
+    assert!(output.contains("? encodeMsgpack(input)"));
+    assert!(output.contains("isMsgpack(res)\n          ? decodeMsgpack(await res.arrayBuffer())"));
+    assert!(output.contains(
+        "data = isMsgpack(res) ? decodeMsgpack(await res.arrayBuffer()) : await res.json();"
+    ));
+}
+
 // --- rpcFetch: headers as function ---
 
 #[test]
@@ -1359,6 +1617,27 @@ fn stream_helper_calls_on_error() {
     );
 }
 
+#[test]
+fn stream_helper_reconnects_with_last_event_id() {
+    let manifest = common::make_manifest(vec![common::make_stream(
+        "chat",
+        Some(RustType::simple("String")),
+        Some(RustType::simple("String")),
+    )]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("export interface ReconnectPolicy {"));
+    assert!(output.contains("reconnect?: ReconnectPolicy | false;"));
+    let stream_start = output.find("async function* rpcStream").unwrap();
+    let stream_body = &output[stream_start..];
+    assert!(stream_body.contains("const reconnect = callOptions?.reconnect ?? config.reconnect;"));
+    assert!(stream_body.contains("} else if (line.startsWith(\"id: \")) {"));
+    assert!(stream_body.contains("headers[\"Last-Event-ID\"] = lastEventId;"));
+    assert!(stream_body.contains(
+        "const willRetry = lastEventId !== undefined && attempt <= maxReconnects && !init.signal?.aborted;"
+    ));
+    assert!(stream_body.contains("delay ?? 500 * 2 ** (attempt - 1)"));
+}
+
 #[test]
 fn stream_jsdoc_on_overload() {
     let mut proc = common::make_stream(
@@ -1490,6 +1769,94 @@ fn stream_helper_handles_error_event() {
     );
 }
 
+#[test]
+fn stream_helper_returns_done_event_and_reads_error_envelope() {
+    let mut proc = common::make_stream("import", None, Some(RustType::simple("u32")));
+    proc.result = Some(RustType::simple("Summary"));
+    let manifest = common::make_manifest(vec![proc]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("stream(key: \"import\"): AsyncGenerator<number, Summary>;"));
+    let stream_start = output.find("async function* rpcStream").unwrap();
+    let stream_body = &output[stream_start..];
+    assert!(stream_body.contains("if (eventType === \"done\") {"));
+    assert!(stream_body.contains("envelope?.status ?? 500"));
+}
+
+#[test]
+fn stream_formats_record_lists_ndjson_and_bytes_streams() {
+    let mut rows = common::make_stream("rows", None, Some(RustType::simple("Row")));
+    rows.stream_format = StreamFormat::Ndjson;
+    let mut report = common::make_stream("report", None, None);
+    report.stream_format = StreamFormat::Bytes;
+    let chat = common::make_stream("chat", None, Some(RustType::simple("String")));
+    let manifest = common::make_manifest(vec![rows, report, chat]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains(
+        "const STREAM_FORMATS: Record<string, \"ndjson\" | \"bytes\"> = {\n  \"rows\": \"ndjson\",\n  \"report\": \"bytes\",\n};"
+    ));
+    assert!(output.contains("stream(key: \"report\"): AsyncGenerator<Uint8Array>;"));
+    assert!(output.contains("stream(key: \"rows\"): AsyncGenerator<Row>;"));
+}
+
+#[test]
+fn stream_helper_parses_ndjson_lines_and_yields_bytes() {
+    let manifest = common::make_manifest(vec![common::make_stream(
+        "chat",
+        None,
+        Some(RustType::simple("String")),
+    )]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("const STREAM_FORMATS: Record<string, \"ndjson\" | \"bytes\"> = {};"));
+    let stream_start = output.find("async function* rpcStream").unwrap();
+    let stream_body = &output[stream_start..];
+    assert!(stream_body.contains("const format = STREAM_FORMATS[procedure] ?? \"sse\";"));
+    assert!(stream_body.contains("if (format === \"bytes\") {\n            yield value as T;"));
+    assert!(stream_body.contains("const lines = buffer.split(\"\\n\");"));
+    assert!(stream_body.contains("if (format === \"ndjson\" && buffer.trim()) {"));
+}
+
+#[test]
+fn stream_formats_record_omitted_without_streams() {
+    let manifest = common::make_manifest(vec![common::make_query(
+        "hello",
+        None,
+        Some(RustType::simple("String")),
+    )]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(!output.contains("STREAM_FORMATS"));
+}
+
+#[test]
+fn get_streams_set_lists_get_streams() {
+    let mut ticks = common::make_stream("ticks", None, Some(RustType::simple("u32")));
+    ticks.stream_get = true;
+    let chat = common::make_stream("chat", None, Some(RustType::simple("String")));
+    let manifest = common::make_manifest(vec![ticks, chat]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("const GET_STREAMS: Set<string> = new Set([\"ticks\"]);"));
+}
+
+#[test]
+fn stream_helper_sends_get_input_in_url_and_supports_event_source() {
+    let manifest = common::make_manifest(vec![common::make_stream(
+        "chat",
+        None,
+        Some(RustType::simple("String")),
+    )]);
+    let output = generate_client_file(&manifest, "./rpc-types", false);
+    assert!(output.contains("const GET_STREAMS: Set<string> = new Set();"));
+    assert!(output.contains("  eventSource?: boolean;"));
+    let stream_start = output.find("async function* rpcStream").unwrap();
+    let stream_body = &output[stream_start..];
+    assert!(
+        stream_body.contains("const method = GET_STREAMS.has(procedure) ? \"GET\" : \"POST\";")
+    );
+    assert!(stream_body.contains("`?input=${encodeURIComponent(serialized)}`"));
+    assert!(stream_body.contains("return yield* rpcEventSource<T>("));
+    assert!(stream_body.contains("const source = new EventSource(url);"));
+    assert!(stream_body.contains("source.close();"));
+}
+
 // --- Stream: combined option tests ---
 
 #[test]
@@ -47,10 +47,16 @@ pub fn make_query(name: &str, input: Option<RustType>, output: Option<RustType>)
         kind: ProcedureKind::Query,
         input,
         output,
+        error: None,
+        result: None,
         source_file: PathBuf::from("api/test.rs"),
         docs: None,
         timeout_ms: None,
         idempotent: false,
+        rate_limit: None,
+        etag: false,
+        stream_format: StreamFormat::Sse,
+        stream_get: false,
     }
 }
 
@@ -60,10 +66,16 @@ pub fn make_mutation(name: &str, input: Option<RustType>, output: Option<RustTyp
         kind: ProcedureKind::Mutation,
         input,
         output,
+        error: None,
+        result: None,
         source_file: PathBuf::from("api/test.rs"),
         docs: None,
         timeout_ms: None,
         idempotent: false,
+        rate_limit: None,
+        etag: false,
+        stream_format: StreamFormat::Sse,
+        stream_get: false,
     }
 }
 
@@ -73,10 +85,16 @@ pub fn make_stream(name: &str, input: Option<RustType>, output: Option<RustType>
         kind: ProcedureKind::Stream,
         input,
         output,
+        error: None,
+        result: None,
         source_file: PathBuf::from("api/test.rs"),
         docs: None,
         timeout_ms: None,
         idempotent: false,
+        rate_limit: None,
+        etag: false,
+        stream_format: StreamFormat::Sse,
+        stream_get: false,
     }
 }
 
@@ -96,30 +114,48 @@ pub fn make_test_manifest() -> Manifest {
                 kind: ProcedureKind::Query,
                 input: Some(RustType::simple("String")),
                 output: Some(RustType::simple("String")),
+                error: None,
+                result: None,
                 source_file: PathBuf::from("api/hello.rs"),
                 docs: None,
                 timeout_ms: None,
                 idempotent: false,
+                rate_limit: None,
+                etag: false,
+                stream_format: StreamFormat::Sse,
+                stream_get: false,
             },
             Procedure {
                 name: "time".to_string(),
                 kind: ProcedureKind::Query,
                 input: None,
                 output: Some(RustType::simple("TimeResponse")),
+                error: None,
+                result: None,
                 source_file: PathBuf::from("api/time.rs"),
                 docs: None,
                 timeout_ms: None,
                 idempotent: false,
+                rate_limit: None,
+                etag: false,
+                stream_format: StreamFormat::Sse,
+                stream_get: false,
             },
             Procedure {
                 name: "create_item".to_string(),
                 kind: ProcedureKind::Mutation,
                 input: Some(RustType::simple("CreateInput")),
                 output: Some(RustType::simple("Item")),
+                error: None,
+                result: None,
                 source_file: PathBuf::from("api/create_item.rs"),
                 docs: None,
                 timeout_ms: None,
                 idempotent: false,
+                rate_limit: None,
+                etag: false,
+                stream_format: StreamFormat::Sse,
+                stream_get: false,
             },
         ],
         structs: vec![
@@ -23,6 +23,7 @@ fn test_default_matches_current_behavior() {
     assert_eq!(config.codegen.naming.fields, FieldNaming::Preserve);
     assert_eq!(config.watch.debounce_ms, 200);
     assert!(!config.watch.clear_screen);
+    assert_eq!(config.dev.port, 3000);
 }
 
 #[test]
@@ -65,6 +66,9 @@ fields = "camelCase"
 [watch]
 debounce_ms = 500
 clear_screen = true
+
+[dev]
+port = 8080
 "#;
     let config: RpcConfig = toml::from_str(toml_str).unwrap();
     assert_eq!(config.input.dir, PathBuf::from("lambdas"));
@@ -79,6 +83,7 @@ clear_screen = true
     assert_eq!(config.codegen.naming.fields, FieldNaming::CamelCase);
     assert_eq!(config.watch.debounce_ms, 500);
     assert!(config.watch.clear_screen);
+    assert_eq!(config.dev.port, 8080);
 }
 
 #[test]
@@ -256,6 +261,7 @@ client = "client.ts"
         bigint_types: vec![],
         debounce_ms: Some(500),
         clear_screen: true,
+        port: Some(4000),
     };
 
     let config = resolve(overrides).unwrap();
@@ -271,6 +277,7 @@ client = "client.ts"
     assert_eq!(config.codegen.naming.fields, FieldNaming::CamelCase);
     assert_eq!(config.watch.debounce_ms, 500);
     assert!(config.watch.clear_screen);
+    assert_eq!(config.dev.port, 4000);
 }
 
 #[test]
@@ -296,6 +303,7 @@ fn test_resolve_no_config_flag() {
         bigint_types: vec![],
         debounce_ms: None,
         clear_screen: false,
+        port: None,
     };
     let config = resolve(overrides).unwrap();
     assert_eq!(config.input.dir, PathBuf::from("custom"));
@@ -326,6 +334,7 @@ fn test_resolve_client_output_override() {
         bigint_types: vec![],
         debounce_ms: None,
         clear_screen: false,
+        port: None,
     };
     let config = resolve(overrides).unwrap();
     assert_eq!(config.output.client, PathBuf::from("custom-client.ts"));
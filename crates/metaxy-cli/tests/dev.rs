@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use tempfile::TempDir;
+
+use metaxy_cli::dev::{batch_port, parse_executables, rewrite_head, route_name};
+
+// --- route_name ---
+
+#[test]
+fn route_name_strips_prefix_and_query() {
+    assert_eq!(route_name("/api/hello"), Some("hello"));
+    assert_eq!(route_name("/api/hello?input=%22x%22"), Some("hello"));
+    assert_eq!(route_name("/api/hello/"), Some("hello"));
+}
+
+#[test]
+fn route_name_rejects_other_paths() {
+    assert_eq!(route_name("/"), None);
+    assert_eq!(route_name("/api/"), None);
+    assert_eq!(route_name("/other/hello"), None);
+    assert_eq!(route_name("/api/a/b"), None);
+}
+
+#[test]
+fn route_name_accepts_batch_endpoint() {
+    assert_eq!(route_name("/api/_batch"), Some("_batch"));
+}
+
+// --- batch_port ---
+
+fn routes(entries: &[(&str, u16)]) -> HashMap<String, u16> {
+    entries
+        .iter()
+        .map(|&(name, port)| (name.to_owned(), port))
+        .collect()
+}
+
+#[test]
+fn batch_port_is_the_router_process() {
+    let table = routes(&[("hello", 4001), ("time", 4001), ("upload", 4002)]);
+    assert_eq!(batch_port(&table), Some(4001));
+}
+
+#[test]
+fn batch_port_needs_exactly_one_router() {
+    assert_eq!(
+        batch_port(&routes(&[("hello", 4001), ("time", 4002)])),
+        None
+    );
+    let table = routes(&[("a", 4001), ("b", 4001), ("c", 4002), ("d", 4002)]);
+    assert_eq!(batch_port(&table), None);
+}
+
+// --- rewrite_head ---
+
+#[test]
+fn rewrite_head_forces_connection_close() {
+    let head = "GET /api/hello HTTP/1.1\r\nHost: localhost\r\nConnection: keep-alive\r\nKeep-Alive: timeout=5\r\n\r\n";
+    let out = rewrite_head(head, Some("127.0.0.1"));
+    assert_eq!(
+        out,
+        "GET /api/hello HTTP/1.1\r\nHost: localhost\r\nX-Forwarded-For: 127.0.0.1\r\nConnection: close\r\n\r\n"
+    );
+}
+
+#[test]
+fn rewrite_head_keeps_existing_forwarded_for() {
+    let head =
+        "POST /api/echo HTTP/1.1\r\nx-forwarded-for: 203.0.113.7\r\nContent-Length: 2\r\n\r\n";
+    let out = rewrite_head(head, Some("127.0.0.1"));
+    assert!(out.contains("x-forwarded-for: 203.0.113.7\r\n"));
+    assert!(!out.contains("127.0.0.1"));
+    assert!(out.contains("Content-Length: 2\r\n"));
+    assert!(out.ends_with("Connection: close\r\n\r\n"));
+}
+
+// --- parse_executables ---
+
+#[test]
+fn parse_executables_maps_bin_sources() {
+    let tmp = TempDir::new().unwrap();
+    let src = tmp.path().join("hello.rs");
+    std::fs::write(&src, "fn main() {}").unwrap();
+
+    let messages = [
+        serde_json::json!({ "reason": "compiler-artifact", "target": { "src_path": "/x/lib.rs" }, "executable": null }),
+        serde_json::json!({ "reason": "compiler-artifact", "target": { "src_path": src }, "executable": "/target/debug/hello" }),
+        serde_json::json!({ "reason": "build-finished", "success": true }),
+    ];
+    let stdout: String = messages.iter().map(|m| format!("{m}\n")).collect();
+
+    let executables = parse_executables(stdout.as_bytes());
+    assert_eq!(executables.len(), 1);
+    assert_eq!(
+        executables[&src.canonicalize().unwrap()],
+        PathBuf::from("/target/debug/hello")
+    );
+}
+
+#[test]
+fn parse_executables_ignores_noise() {
+    let executables = parse_executables(b"not json\n\n   Compiling foo\n");
+    assert!(executables.is_empty());
+}
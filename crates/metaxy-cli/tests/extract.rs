@@ -72,6 +72,77 @@ fn unwraps_result_return_type() {
     assert_eq!(output.generics[0].name, "Item");
 }
 
+#[test]
+fn extracts_result_error_type() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_query]
+            async fn find_user(id: u32) -> Result<User, UserError> {
+                todo!()
+            }
+            "#,
+    );
+    let proc = &manifest.procedures[0];
+    assert_eq!(proc.output.as_ref().unwrap().name, "User");
+    assert_eq!(proc.error.as_ref().unwrap().name, "UserError");
+}
+
+#[test]
+fn plain_return_has_no_error_type() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_mutation]
+            async fn reset() -> u32 {
+                0
+            }
+            "#,
+    );
+    assert!(manifest.procedures[0].error.is_none());
+}
+
+#[test]
+fn rpc_response_unwrapped_to_inner_type() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_mutation]
+            async fn login(input: Credentials) -> Result<RpcResponse<User>, AuthError> {
+                todo!()
+            }
+
+            #[rpc_query]
+            async fn feed() -> metaxy::RpcResponse<Vec<Post>> {
+                todo!()
+            }
+            "#,
+    );
+    let login = &manifest.procedures[0];
+    assert_eq!(login.output.as_ref().unwrap().name, "User");
+    assert_eq!(login.error.as_ref().unwrap().name, "AuthError");
+    let feed = &manifest.procedures[1];
+    assert_eq!(feed.output.as_ref().unwrap().name, "Vec");
+}
+
+#[test]
+fn router_procedures_share_a_file() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_query(router)]
+            async fn hello(name: String) -> String {
+                todo!()
+            }
+
+            #[rpc_mutation(router, idempotent)]
+            async fn echo(input: String) -> String {
+                input
+            }
+            "#,
+    );
+    assert_eq!(manifest.procedures.len(), 2);
+    assert_eq!(manifest.procedures[0].name, "hello");
+    assert_eq!(manifest.procedures[1].name, "echo");
+    assert!(manifest.procedures[1].idempotent);
+}
+
 #[test]
 fn extracts_serde_structs() {
     let manifest = common::parse_source(
@@ -992,6 +1063,73 @@ fn idempotent_with_timeout() {
     assert_eq!(manifest.procedures[0].timeout_ms, Some(30_000));
 }
 
+// --- Rate limit extraction tests ---
+
+#[test]
+fn extracts_rate_limit() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_mutation(rate_limit = "10/m", key = "ip")]
+            async fn login(input: String) -> String {
+                input
+            }
+
+            #[rpc_query(rate_limit = "5/30s")]
+            async fn search(input: String) -> String {
+                input
+            }
+            "#,
+    );
+    assert_eq!(
+        manifest.procedures[0].rate_limit,
+        Some(RateLimit {
+            requests: 10,
+            window_ms: 60_000
+        })
+    );
+    assert_eq!(
+        manifest.procedures[1].rate_limit,
+        Some(RateLimit {
+            requests: 5,
+            window_ms: 30_000
+        })
+    );
+}
+
+#[test]
+fn invalid_rate_limit_ignored() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_mutation(rate_limit = "lots")]
+            async fn login(input: String) -> String {
+                input
+            }
+            "#,
+    );
+    assert!(manifest.procedures[0].rate_limit.is_none());
+}
+
+// --- ETag extraction tests ---
+
+#[test]
+fn extracts_etag_flag_on_queries() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_query(etag, cache = "1m")]
+            async fn stats() -> u32 {
+                1
+            }
+
+            #[rpc_query]
+            async fn plain() -> u32 {
+                1
+            }
+            "#,
+    );
+    assert!(manifest.procedures[0].etag);
+    assert!(!manifest.procedures[1].etag);
+}
+
 // --- Stream extraction tests ---
 
 #[test]
@@ -1086,3 +1224,175 @@ fn stream_is_not_idempotent() {
     );
     assert!(!manifest.procedures[0].idempotent);
 }
+
+#[test]
+fn guarded_param_is_not_input() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_query(guard = "require_user")]
+            async fn me(user: Guarded<User>) -> String { todo!() }
+            "#,
+    );
+    assert!(manifest.procedures[0].input.is_none());
+}
+
+#[test]
+fn guarded_param_skipped_alongside_input() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_mutation(guard = "require_user")]
+            async fn rename(user: metaxy::Guarded<User>, name: String) -> String { todo!() }
+            "#,
+    );
+    assert_eq!(
+        manifest.procedures[0].input.as_ref().unwrap().name,
+        "String"
+    );
+}
+
+#[test]
+fn builtin_extractors_are_not_input() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_query]
+            async fn whoami(ip: ClientIp, cookies: metaxy::Cookies, info: RequestInfo, ctx: VercelContext) -> String { todo!() }
+            "#,
+    );
+    assert!(manifest.procedures[0].input.is_none());
+}
+
+#[test]
+fn last_event_id_is_not_stream_input() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_stream]
+            async fn feed(topic: String, last: LastEventId, tx: StreamSender<u64>) {}
+            "#,
+    );
+    let input = manifest.procedures[0].input.as_ref().unwrap();
+    assert_eq!(input.name, "String");
+}
+
+#[test]
+fn extracts_stream_result_and_error() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_stream]
+            async fn import(rows: Vec<Row>, tx: StreamSender<u32>) -> Result<Summary, ImportError> {}
+            "#,
+    );
+    let proc = &manifest.procedures[0];
+    assert_eq!(proc.output.as_ref().unwrap().name, "u32");
+    assert_eq!(proc.result.as_ref().unwrap().name, "Summary");
+    assert_eq!(proc.error.as_ref().unwrap().name, "ImportError");
+}
+
+#[test]
+fn extracts_stream_plain_return_as_result() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_stream]
+            async fn total(tx: StreamSender<i32>) -> i32 {}
+            "#,
+    );
+    let proc = &manifest.procedures[0];
+    assert_eq!(proc.result.as_ref().unwrap().name, "i32");
+    assert!(proc.error.is_none());
+}
+
+#[test]
+fn extracts_stream_item_from_returned_stream() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_stream]
+            async fn ticks(every: u64) -> impl Stream<Item = Tick> {}
+            "#,
+    );
+    let proc = &manifest.procedures[0];
+    assert_eq!(proc.input.as_ref().unwrap().name, "u64");
+    assert_eq!(proc.output.as_ref().unwrap().name, "Tick");
+    assert!(proc.result.is_none());
+    assert!(proc.error.is_none());
+}
+
+#[test]
+fn extracts_result_items_of_returned_stream() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_stream]
+            async fn rows(query: String) -> impl futures::Stream<Item = Result<Row, DbError>> + Send {}
+            "#,
+    );
+    let proc = &manifest.procedures[0];
+    assert_eq!(proc.output.as_ref().unwrap().name, "Row");
+    assert_eq!(proc.error.as_ref().unwrap().name, "DbError");
+    assert!(proc.result.is_none());
+}
+
+#[test]
+fn extracts_ndjson_stream_format_and_chunk() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_stream(format = "ndjson")]
+            async fn export(filter: Filter, tx: NdjsonSender<Order>) {}
+            "#,
+    );
+    let proc = &manifest.procedures[0];
+    assert_eq!(proc.stream_format, StreamFormat::Ndjson);
+    assert_eq!(proc.input.as_ref().unwrap().name, "Filter");
+    assert_eq!(proc.output.as_ref().unwrap().name, "Order");
+}
+
+#[test]
+fn extracts_bytes_stream_format() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_stream(format = "bytes", timeout = "30s")]
+            async fn report(month: String, tx: ByteSender) {}
+            "#,
+    );
+    let proc = &manifest.procedures[0];
+    assert_eq!(proc.stream_format, StreamFormat::Bytes);
+    assert_eq!(proc.input.as_ref().unwrap().name, "String");
+    assert!(proc.output.is_none());
+}
+
+#[test]
+fn stream_format_defaults_to_sse() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_stream]
+            async fn events(tx: StreamSender<u32>) {}
+            "#,
+    );
+    assert_eq!(manifest.procedures[0].stream_format, StreamFormat::Sse);
+}
+
+#[test]
+fn extracts_stream_get_method() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_stream(method = "GET")]
+            async fn ticks(from: u32, tx: StreamSender<u32>) {}
+
+            #[rpc_stream(method = "POST")]
+            async fn upload(rows: Vec<u32>, tx: StreamSender<u32>) {}
+            "#,
+    );
+    assert!(manifest.procedures[0].stream_get);
+    assert!(!manifest.procedures[1].stream_get);
+}
+
+#[test]
+fn from_request_param_skipped_alongside_input() {
+    let manifest = common::parse_source(
+        r#"
+            #[rpc_mutation]
+            async fn rename(#[from_request] tenant: Tenant, name: String) -> String { todo!() }
+            "#,
+    );
+    assert_eq!(
+        manifest.procedures[0].input.as_ref().unwrap().name,
+        "String"
+    );
+}
@@ -174,10 +174,16 @@ fn override_in_procedure_io() {
             kind: ProcedureKind::Query,
             input: Some(RustType::simple("Uuid")),
             output: Some(RustType::simple("User")),
+            error: None,
+            result: None,
             source_file: PathBuf::from("test.rs"),
             docs: None,
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         }],
         structs: vec![],
         enums: vec![],
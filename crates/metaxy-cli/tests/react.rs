@@ -105,6 +105,31 @@ fn react_contains_mutation_result() {
     assert!(output.contains("export interface MutationResult<K extends MutationKey>"));
 }
 
+#[test]
+fn react_errors_typed_per_procedure() {
+    let manifest = common::make_manifest(vec![
+        common::make_query(
+            "hello",
+            Some(RustType::simple("String")),
+            Some(RustType::simple("String")),
+        ),
+        common::make_mutation(
+            "create_item",
+            Some(RustType::simple("CreateInput")),
+            Some(RustType::simple("Item")),
+        ),
+    ]);
+    let output = generate_react_file(&manifest, "./rpc-client", "./rpc-types", false);
+    assert!(output.contains(
+        "type QueryError<K extends QueryKey> = Procedures[\"queries\"][K] extends { error: infer E } ? E : never;"
+    ));
+    assert!(output.contains(
+        "type MutationError<K extends MutationKey> = Procedures[\"mutations\"][K] extends { error: infer E } ? E : never;"
+    ));
+    assert!(output.contains("onError?: (error: RpcError<QueryError<K>>) => void;"));
+    assert!(output.contains("onError?: (error: RpcError<MutationError<K>>) => void;"));
+}
+
 // --- React hooks ---
 
 #[test]
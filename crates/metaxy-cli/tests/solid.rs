@@ -107,6 +107,31 @@ fn solid_contains_mutation_result() {
     assert!(output.contains("export interface MutationResult<K extends MutationKey>"));
 }
 
+#[test]
+fn solid_errors_typed_per_procedure() {
+    let manifest = common::make_manifest(vec![
+        common::make_query(
+            "hello",
+            Some(RustType::simple("String")),
+            Some(RustType::simple("String")),
+        ),
+        common::make_mutation(
+            "create_item",
+            Some(RustType::simple("CreateInput")),
+            Some(RustType::simple("Item")),
+        ),
+    ]);
+    let output = generate_solid_file(&manifest, "./rpc-client", "./rpc-types", false);
+    assert!(output.contains(
+        "type QueryError<K extends QueryKey> = Procedures[\"queries\"][K] extends { error: infer E } ? E : never;"
+    ));
+    assert!(output.contains(
+        "type MutationError<K extends MutationKey> = Procedures[\"mutations\"][K] extends { error: infer E } ? E : never;"
+    ));
+    assert!(output.contains("onError?: (error: RpcError<QueryError<K>>) => void;"));
+    assert!(output.contains("onError?: (error: RpcError<MutationError<K>>) => void;"));
+}
+
 // --- SolidJS reactivity ---
 
 #[test]
@@ -151,7 +176,7 @@ fn solid_accessor_return_type() {
     )]);
     let output = generate_solid_file(&manifest, "./rpc-client", "./rpc-types", false);
     assert!(output.contains("data: () => QueryOutput<K> | undefined"));
-    assert!(output.contains("error: () => RpcError | undefined"));
+    assert!(output.contains("error: () => RpcError<QueryError<K>> | undefined"));
     assert!(output.contains("isLoading: () => boolean"));
     assert!(output.contains("isSuccess: () => boolean"));
     assert!(output.contains("isError: () => boolean"));
@@ -98,6 +98,31 @@ fn svelte_contains_mutation_result() {
     assert!(output.contains("export interface MutationResult<K extends MutationKey>"));
 }
 
+#[test]
+fn svelte_errors_typed_per_procedure() {
+    let manifest = common::make_manifest(vec![
+        common::make_query(
+            "hello",
+            Some(RustType::simple("String")),
+            Some(RustType::simple("String")),
+        ),
+        common::make_mutation(
+            "create_item",
+            Some(RustType::simple("CreateInput")),
+            Some(RustType::simple("Item")),
+        ),
+    ]);
+    let output = generate_svelte_file(&manifest, "./rpc-client", "./rpc-types", false);
+    assert!(output.contains(
+        "type QueryError<K extends QueryKey> = Procedures[\"queries\"][K] extends { error: infer E } ? E : never;"
+    ));
+    assert!(output.contains(
+        "type MutationError<K extends MutationKey> = Procedures[\"mutations\"][K] extends { error: infer E } ? E : never;"
+    ));
+    assert!(output.contains("onError?: (error: RpcError<QueryError<K>>) => void;"));
+    assert!(output.contains("onError?: (error: RpcError<MutationError<K>>) => void;"));
+}
+
 // --- Runes ---
 
 #[test]
@@ -571,6 +596,20 @@ fn svelte_stream_type_helpers() {
     assert!(output.contains("type StreamOutput<K extends StreamKey>"));
 }
 
+#[test]
+fn svelte_stream_exposes_typed_result_and_error() {
+    let manifest = common::make_manifest(vec![common::make_stream(
+        "chat",
+        Some(RustType::simple("String")),
+        Some(RustType::simple("String")),
+    )]);
+    let output = generate_svelte_file(&manifest, "./rpc-client", "./rpc-types", false);
+    assert!(output.contains("type StreamReturn<K extends StreamKey>"));
+    assert!(output.contains("type StreamError<K extends StreamKey>"));
+    assert!(output.contains("onDone?: (result: StreamReturn<K>) => void;"));
+    assert!(output.contains("onError?: (error: RpcError<StreamError<K>>) => void;"));
+}
+
 #[test]
 fn svelte_stream_void_overload() {
     let manifest = common::make_manifest(vec![common::make_stream(
@@ -208,10 +208,16 @@ fn generates_queries_only() {
             kind: ProcedureKind::Query,
             input: None,
             output: Some(RustType::simple("String")),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/ping.rs"),
             docs: None,
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         }],
         structs: vec![],
         enums: vec![],
@@ -222,6 +228,19 @@ fn generates_queries_only() {
     assert!(!output.contains("export interface"));
 }
 
+#[test]
+fn generates_error_type_in_procedures() {
+    let mut proc = common::make_query(
+        "find_user",
+        Some(RustType::simple("u32")),
+        Some(RustType::simple("User")),
+    );
+    proc.error = Some(RustType::simple("UserError"));
+    let manifest = common::make_manifest(vec![proc]);
+    let output = generate_types_file(&manifest, false, FieldNaming::Preserve, false);
+    assert!(output.contains("    find_user: { input: number; output: User; error: UserError };"));
+}
+
 #[test]
 fn generates_complex_nested_types() {
     let manifest = Manifest {
@@ -236,10 +255,16 @@ fn generates_complex_nested_types() {
                     vec![RustType::simple("Item")],
                 )],
             )),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/search.rs"),
             docs: None,
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         }],
         structs: vec![],
         enums: vec![],
@@ -494,10 +519,16 @@ fn test_jsdoc_on_procedure() {
             kind: ProcedureKind::Query,
             input: Some(RustType::simple("String")),
             output: Some(RustType::simple("String")),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/hello.rs"),
             docs: Some("Say hello.".to_string()),
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         }],
         structs: vec![],
         enums: vec![],
@@ -516,10 +547,16 @@ fn test_jsdoc_on_mutation_procedure() {
             kind: ProcedureKind::Mutation,
             input: Some(RustType::simple("String")),
             output: Some(RustType::simple("bool")),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/update.rs"),
             docs: Some("Update item.".to_string()),
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         }],
         structs: vec![],
         enums: vec![],
@@ -538,10 +575,16 @@ fn test_no_jsdoc_when_disabled() {
             kind: ProcedureKind::Query,
             input: Some(RustType::simple("String")),
             output: Some(RustType::simple("String")),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/hello.rs"),
             docs: Some("Say hello.".to_string()),
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         }],
         structs: vec![StructDef {
             name: "Foo".to_string(),
@@ -1828,10 +1871,16 @@ fn procedure_output_preserves_generics() {
                 "Paginated",
                 vec![RustType::simple("User")],
             )),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/test.rs"),
             docs: None,
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         }],
         structs: vec![],
         enums: vec![],
@@ -1881,10 +1930,16 @@ fn snapshot_generic_struct() {
                 "Paginated",
                 vec![RustType::simple("User")],
             )),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/test.rs"),
             docs: None,
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         }],
         structs: vec![
             StructDef {
@@ -2009,10 +2064,16 @@ fn procedure_uses_newtype() {
             kind: ProcedureKind::Query,
             input: Some(RustType::simple("UserId")),
             output: Some(RustType::simple("User")),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/test.rs"),
             docs: None,
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         }],
         structs: vec![StructDef {
             name: "UserId".to_string(),
@@ -2078,10 +2139,16 @@ fn snapshot_branded_newtypes() {
             kind: ProcedureKind::Query,
             input: Some(RustType::simple("UserId")),
             output: Some(RustType::simple("User")),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/test.rs"),
             docs: None,
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         }],
         structs: vec![
             StructDef {
@@ -2439,20 +2506,32 @@ fn snapshot_type_overrides() {
                 kind: ProcedureKind::Query,
                 input: Some(RustType::simple("Uuid")),
                 output: Some(RustType::simple("User")),
+                error: None,
+                result: None,
                 source_file: PathBuf::from("api/user.rs"),
                 docs: None,
                 timeout_ms: None,
                 idempotent: false,
+                rate_limit: None,
+                etag: false,
+                stream_format: StreamFormat::Sse,
+                stream_get: false,
             },
             Procedure {
                 name: "create_event".to_string(),
                 kind: ProcedureKind::Mutation,
                 input: Some(RustType::simple("EventInput")),
                 output: Some(RustType::simple("Event")),
+                error: None,
+                result: None,
                 source_file: PathBuf::from("api/event.rs"),
                 docs: None,
                 timeout_ms: None,
                 idempotent: false,
+                rate_limit: None,
+                etag: false,
+                stream_format: StreamFormat::Sse,
+                stream_get: false,
             },
         ],
         structs: vec![
@@ -2569,10 +2648,16 @@ fn snapshot_bigint_types() {
             kind: ProcedureKind::Query,
             input: None,
             output: Some(RustType::simple("Stats")),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/stats.rs"),
             docs: None,
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         }],
         structs: vec![StructDef {
             name: "Stats".to_string(),
@@ -2611,10 +2696,16 @@ fn generates_streams_section() {
             kind: ProcedureKind::Stream,
             input: Some(RustType::simple("String")),
             output: Some(RustType::simple("String")),
+            error: None,
+            result: None,
             source_file: PathBuf::from("api/chat.rs"),
             docs: None,
             timeout_ms: None,
             idempotent: false,
+            rate_limit: None,
+            etag: false,
+            stream_format: StreamFormat::Sse,
+            stream_get: false,
         }],
         structs: vec![],
         enums: vec![],
@@ -2624,6 +2715,27 @@ fn generates_streams_section() {
     assert!(output.contains("    chat: { input: string; output: string };"));
 }
 
+#[test]
+fn stream_entry_includes_result_and_error() {
+    let mut proc = common::make_stream("import", None, Some(RustType::simple("u32")));
+    proc.result = Some(RustType::simple("Summary"));
+    proc.error = Some(RustType::simple("ImportError"));
+    let manifest = common::make_manifest(vec![proc]);
+    let output = generate_types_file(&manifest, false, FieldNaming::Preserve, false);
+    assert!(output.contains(
+        "    import: { input: void; output: number; result: Summary; error: ImportError };"
+    ));
+}
+
+#[test]
+fn bytes_stream_output_is_uint8array() {
+    let mut proc = common::make_stream("report", Some(RustType::simple("String")), None);
+    proc.stream_format = StreamFormat::Bytes;
+    let manifest = common::make_manifest(vec![proc]);
+    let output = generate_types_file(&manifest, false, FieldNaming::Preserve, false);
+    assert!(output.contains("    report: { input: string; output: Uint8Array };"));
+}
+
 #[test]
 fn generates_mixed_procedures_with_streams() {
     let manifest = Manifest {
@@ -2633,30 +2745,48 @@ fn generates_mixed_procedures_with_streams() {
                 kind: ProcedureKind::Query,
                 input: Some(RustType::simple("String")),
                 output: Some(RustType::simple("String")),
+                error: None,
+                result: None,
                 source_file: PathBuf::from("api/hello.rs"),
                 docs: None,
                 timeout_ms: None,
                 idempotent: false,
+                rate_limit: None,
+                etag: false,
+                stream_format: StreamFormat::Sse,
+                stream_get: false,
             },
             Procedure {
                 name: "echo".to_string(),
                 kind: ProcedureKind::Mutation,
                 input: Some(RustType::simple("String")),
                 output: Some(RustType::simple("String")),
+                error: None,
+                result: None,
                 source_file: PathBuf::from("api/echo.rs"),
                 docs: None,
                 timeout_ms: None,
                 idempotent: false,
+                rate_limit: None,
+                etag: false,
+                stream_format: StreamFormat::Sse,
+                stream_get: false,
             },
             Procedure {
                 name: "events".to_string(),
                 kind: ProcedureKind::Stream,
                 input: None,
                 output: Some(RustType::simple("Event")),
+                error: None,
+                result: None,
                 source_file: PathBuf::from("api/events.rs"),
                 docs: None,
                 timeout_ms: None,
                 idempotent: false,
+                rate_limit: None,
+                etag: false,
+                stream_format: StreamFormat::Sse,
+                stream_get: false,
             },
         ],
         structs: vec![],
@@ -107,6 +107,31 @@ fn vue_contains_mutation_result() {
     assert!(output.contains("export interface MutationResult<K extends MutationKey>"));
 }
 
+#[test]
+fn vue_errors_typed_per_procedure() {
+    let manifest = common::make_manifest(vec![
+        common::make_query(
+            "hello",
+            Some(RustType::simple("String")),
+            Some(RustType::simple("String")),
+        ),
+        common::make_mutation(
+            "create_item",
+            Some(RustType::simple("CreateInput")),
+            Some(RustType::simple("Item")),
+        ),
+    ]);
+    let output = generate_vue_file(&manifest, "./rpc-client", "./rpc-types", false);
+    assert!(output.contains(
+        "type QueryError<K extends QueryKey> = Procedures[\"queries\"][K] extends { error: infer E } ? E : never;"
+    ));
+    assert!(output.contains(
+        "type MutationError<K extends MutationKey> = Procedures[\"mutations\"][K] extends { error: infer E } ? E : never;"
+    ));
+    assert!(output.contains("onError?: (error: RpcError<QueryError<K>>) => void;"));
+    assert!(output.contains("onError?: (error: RpcError<MutationError<K>>) => void;"));
+}
+
 // --- Vue reactivity ---
 
 #[test]
@@ -162,7 +187,7 @@ fn vue_query_result_uses_ref_type() {
     )]);
     let output = generate_vue_file(&manifest, "./rpc-client", "./rpc-types", false);
     assert!(output.contains("readonly data: Ref<QueryOutput<K> | undefined>"));
-    assert!(output.contains("readonly error: Ref<RpcError | undefined>"));
+    assert!(output.contains("readonly error: Ref<RpcError<QueryError<K>> | undefined>"));
     assert!(output.contains("readonly isLoading: Ref<boolean>"));
     assert!(output.contains("readonly isSuccess: ComputedRef<boolean>"));
     assert!(output.contains("readonly isError: ComputedRef<boolean>"));
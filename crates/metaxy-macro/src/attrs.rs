@@ -6,20 +6,90 @@ pub(crate) struct CacheConfig {
     pub(crate) cache_control: String,
 }
 
+/// CORS policy requested via `cors(...)` or `cors = "path"`.
+#[derive(Debug)]
+pub(crate) enum CorsConfig {
+    /// Path to a user-defined `const` of type `metaxy::Cors`.
+    Path(String),
+    /// Inline policy from `cors(origins = "...", credentials, ...)`.
+    Inline {
+        origins: Vec<String>,
+        credentials: bool,
+        allow_headers: Vec<String>,
+        expose_headers: Vec<String>,
+        max_age_secs: Option<u64>,
+    },
+}
+
+/// Rate limit requested via `rate_limit = "10/m"`, with its `key` and `store`.
+#[derive(Debug)]
+pub(crate) struct RateLimitConfig {
+    pub(crate) requests: u32,
+    pub(crate) window_secs: u64,
+    pub(crate) key: RateLimitKey,
+    /// Path to a `static` implementing `metaxy::RateLimitStore`.
+    pub(crate) store: Option<String>,
+}
+
+/// What a rate limit bucket is keyed by.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum RateLimitKey {
+    /// `key = "ip"` (the default): the client IP.
+    Ip,
+    /// `key = "header:X-Api-Key"`: the value of a request header.
+    Header(String),
+    /// `key = "guard"`: the guard's value, via `metaxy::RateLimitKey`.
+    Guard,
+}
+
+/// Wire format of a stream, chosen with `format = "..."`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StreamFormat {
+    /// `format = "sse"` (the default): server-sent events via `StreamSender`.
+    #[default]
+    Sse,
+    /// `format = "ndjson"`: one JSON value per line via `NdjsonSender`.
+    Ndjson,
+    /// `format = "bytes"`: raw chunks via `ByteSender`.
+    Bytes,
+}
+
 /// Parsed attributes from `#[rpc_query(...)]` or `#[rpc_mutation(...)]`.
 #[derive(Debug, Default)]
 pub(crate) struct HandlerAttrs {
     pub(crate) cache_config: Option<CacheConfig>,
     pub(crate) init_fn: Option<String>,
+    pub(crate) guard_fn: Option<String>,
     pub(crate) timeout_secs: Option<u64>,
+    /// `heartbeat = "15s"`: interval of SSE comment lines keeping a stream open.
+    pub(crate) heartbeat_secs: Option<u64>,
+    /// `format = "ndjson"`: wire format of a stream; `None` keeps SSE.
+    pub(crate) stream_format: Option<StreamFormat>,
+    /// `method = "GET"`: serve a stream over `GET`, reading input from `?input=`;
+    /// `None` keeps `POST`.
+    pub(crate) stream_get: Option<bool>,
     pub(crate) idempotent: bool,
+    pub(crate) cors: Option<CorsConfig>,
+    pub(crate) validate: bool,
+    pub(crate) router: bool,
+    pub(crate) testable: bool,
+    pub(crate) max_body: Option<usize>,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) rate_limit: Option<RateLimitConfig>,
+    /// `compress = false` opts out of response compression; `None` keeps the default.
+    pub(crate) compress: Option<bool>,
+    /// `etag` flag: tag query responses and answer `If-None-Match` with 304.
+    pub(crate) etag: bool,
 }
 
 /// Parses handler attributes from `#[rpc_query(...)]` or `#[rpc_mutation(...)]`.
 ///
-/// Supports key-value pairs (`cache`, `stale`, `init`, `timeout`) and bare flags
-/// (`idempotent`). Returns `HandlerAttrs` with all fields at their defaults when
-/// the attribute is empty (backward compatible bare `#[rpc_query]`).
+/// Supports key-value pairs (`cache`, `stale`, `init`, `guard`, `timeout`,
+/// `heartbeat`, `format`, `method`, `cors`, `max_body`, `max_depth`, `rate_limit`, `key`, `store`), the boolean
+/// `compress`, bare flags (`idempotent`, `validate`, `router`, `testable`,
+/// `etag`) and the `cors(...)` list.
+/// Returns `HandlerAttrs` with all fields at their defaults when the attribute
+/// is empty (backward compatible bare `#[rpc_query]`).
 pub(crate) fn parse_handler_attrs(attr: TokenStream) -> Result<HandlerAttrs, syn::Error> {
     parse_handler_attrs_inner(attr.into())
 }
@@ -40,8 +110,23 @@ pub(crate) fn parse_handler_attrs_inner(
     let mut cache_value = None;
     let mut stale_value = None;
     let mut init_value = None;
+    let mut guard_value = None;
     let mut timeout_value = None;
+    let mut heartbeat_value = None;
+    let mut stream_format = None;
+    let mut method_value = None;
+    let mut max_body_value = None;
+    let mut max_depth_value = None;
+    let mut rate_limit_value = None;
+    let mut key_value = None;
+    let mut store_value = None;
     let mut idempotent = false;
+    let mut cors = None;
+    let mut validate = false;
+    let mut router = false;
+    let mut testable = false;
+    let mut compress = None;
+    let mut etag = false;
 
     for meta in &parsed {
         match meta {
@@ -58,6 +143,35 @@ pub(crate) fn parse_handler_attrs_inner(
                         ));
                     }
                     idempotent = true;
+                } else if ident == "validate" {
+                    if validate {
+                        return Err(syn::Error::new_spanned(
+                            ident,
+                            "duplicate `validate` attribute",
+                        ));
+                    }
+                    validate = true;
+                } else if ident == "router" {
+                    if router {
+                        return Err(syn::Error::new_spanned(
+                            ident,
+                            "duplicate `router` attribute",
+                        ));
+                    }
+                    router = true;
+                } else if ident == "testable" {
+                    if testable {
+                        return Err(syn::Error::new_spanned(
+                            ident,
+                            "duplicate `testable` attribute",
+                        ));
+                    }
+                    testable = true;
+                } else if ident == "etag" {
+                    if etag {
+                        return Err(syn::Error::new_spanned(ident, "duplicate `etag` attribute"));
+                    }
+                    etag = true;
                 } else {
                     return Err(syn::Error::new_spanned(
                         ident,
@@ -77,6 +191,51 @@ pub(crate) fn parse_handler_attrs_inner(
                         "`idempotent` is a bare flag and does not accept a value; use `idempotent` instead of `idempotent = \"...\"`",
                     ));
                 }
+                if key == "validate" {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "`validate` is a bare flag and does not accept a value; use `validate` instead of `validate = \"...\"`",
+                    ));
+                }
+                if key == "router" {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "`router` is a bare flag and does not accept a value; use `router` instead of `router = \"...\"`",
+                    ));
+                }
+                if key == "testable" {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "`testable` is a bare flag and does not accept a value; use `testable` instead of `testable = \"...\"`",
+                    ));
+                }
+                if key == "etag" {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "`etag` is a bare flag and does not accept a value; use `etag` instead of `etag = \"...\"`",
+                    ));
+                }
+
+                if key == "compress" {
+                    if compress.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            key,
+                            "duplicate `compress` attribute",
+                        ));
+                    }
+                    let syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Bool(enabled),
+                        ..
+                    }) = &nv.value
+                    else {
+                        return Err(syn::Error::new_spanned(
+                            &nv.value,
+                            "expected `true` or `false`",
+                        ));
+                    };
+                    compress = Some(enabled.value);
+                    continue;
+                }
 
                 let value = match &nv.value {
                     syn::Expr::Lit(expr_lit) => match &expr_lit.lit {
@@ -117,6 +276,17 @@ pub(crate) fn parse_handler_attrs_inner(
                         ));
                     }
                     init_value = Some(value);
+                } else if key == "guard" {
+                    if guard_value.is_some() {
+                        return Err(syn::Error::new_spanned(key, "duplicate `guard` attribute"));
+                    }
+                    if value.is_empty() {
+                        return Err(syn::Error::new_spanned(
+                            &nv.value,
+                            "guard function path cannot be empty",
+                        ));
+                    }
+                    guard_value = Some(value);
                 } else if key == "timeout" {
                     if timeout_value.is_some() {
                         return Err(syn::Error::new_spanned(
@@ -131,6 +301,110 @@ pub(crate) fn parse_handler_attrs_inner(
                         ));
                     }
                     timeout_value = Some(value);
+                } else if key == "heartbeat" {
+                    if heartbeat_value.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            key,
+                            "duplicate `heartbeat` attribute",
+                        ));
+                    }
+                    if value.is_empty() {
+                        return Err(syn::Error::new_spanned(
+                            &nv.value,
+                            "heartbeat interval cannot be empty",
+                        ));
+                    }
+                    heartbeat_value = Some(value);
+                } else if key == "format" {
+                    if stream_format.is_some() {
+                        return Err(syn::Error::new_spanned(key, "duplicate `format` attribute"));
+                    }
+                    stream_format = Some(
+                        parse_stream_format(&value)
+                            .map_err(|e| syn::Error::new_spanned(&nv.value, e))?,
+                    );
+                } else if key == "method" {
+                    if method_value.is_some() {
+                        return Err(syn::Error::new_spanned(key, "duplicate `method` attribute"));
+                    }
+                    method_value = Some(if value.eq_ignore_ascii_case("GET") {
+                        true
+                    } else if value.eq_ignore_ascii_case("POST") {
+                        false
+                    } else {
+                        return Err(syn::Error::new_spanned(
+                            &nv.value,
+                            format!("invalid method `{value}`, expected `GET` or `POST`"),
+                        ));
+                    });
+                } else if key == "cors" {
+                    if cors.is_some() {
+                        return Err(syn::Error::new_spanned(key, "duplicate `cors` attribute"));
+                    }
+                    if value.is_empty() {
+                        return Err(syn::Error::new_spanned(
+                            &nv.value,
+                            "cors path cannot be empty",
+                        ));
+                    }
+                    cors = Some(CorsConfig::Path(value));
+                } else if key == "max_body" {
+                    if max_body_value.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            key,
+                            "duplicate `max_body` attribute",
+                        ));
+                    }
+                    max_body_value = Some(
+                        parse_size(&value).map_err(|e| syn::Error::new_spanned(&nv.value, e))?,
+                    );
+                } else if key == "max_depth" {
+                    if max_depth_value.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            key,
+                            "duplicate `max_depth` attribute",
+                        ));
+                    }
+                    max_depth_value = Some(match value.parse::<usize>() {
+                        Ok(depth) if depth > 0 => depth,
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                &nv.value,
+                                format!(
+                                    "invalid `max_depth` `{value}`, expected a positive integer"
+                                ),
+                            ));
+                        }
+                    });
+                } else if key == "rate_limit" {
+                    if rate_limit_value.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            key,
+                            "duplicate `rate_limit` attribute",
+                        ));
+                    }
+                    rate_limit_value = Some(
+                        parse_rate(&value).map_err(|e| syn::Error::new_spanned(&nv.value, e))?,
+                    );
+                } else if key == "key" {
+                    if key_value.is_some() {
+                        return Err(syn::Error::new_spanned(key, "duplicate `key` attribute"));
+                    }
+                    key_value = Some(
+                        parse_rate_limit_key(&value)
+                            .map_err(|e| syn::Error::new_spanned(&nv.value, e))?,
+                    );
+                } else if key == "store" {
+                    if store_value.is_some() {
+                        return Err(syn::Error::new_spanned(key, "duplicate `store` attribute"));
+                    }
+                    if value.is_empty() {
+                        return Err(syn::Error::new_spanned(
+                            &nv.value,
+                            "store path cannot be empty",
+                        ));
+                    }
+                    store_value = Some(value);
                 } else {
                     return Err(syn::Error::new_spanned(
                         key,
@@ -138,6 +412,15 @@ pub(crate) fn parse_handler_attrs_inner(
                     ));
                 }
             }
+            syn::Meta::List(list) if list.path.is_ident("cors") => {
+                if cors.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        &list.path,
+                        "duplicate `cors` attribute",
+                    ));
+                }
+                cors = Some(parse_cors_list(list)?);
+            }
             syn::Meta::List(list) => {
                 return Err(syn::Error::new_spanned(
                     list,
@@ -168,14 +451,180 @@ pub(crate) fn parse_handler_attrs_inner(
         .transpose()
         .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e))?;
 
+    let heartbeat_secs = heartbeat_value
+        .map(|v| parse_duration(&v))
+        .transpose()
+        .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e))?;
+
+    let rate_limit = match rate_limit_value {
+        Some((requests, window_secs)) => {
+            let key = key_value.unwrap_or(RateLimitKey::Ip);
+            if key == RateLimitKey::Guard && guard_value.is_none() {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "`key = \"guard\"` requires a `guard` attribute",
+                ));
+            }
+            Some(RateLimitConfig {
+                requests,
+                window_secs,
+                key,
+                store: store_value,
+            })
+        }
+        None if key_value.is_some() || store_value.is_some() => {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "`key` and `store` require a `rate_limit` attribute",
+            ));
+        }
+        None => None,
+    };
+
     Ok(HandlerAttrs {
         cache_config,
         init_fn: init_value,
+        guard_fn: guard_value,
         timeout_secs,
+        heartbeat_secs,
+        stream_format,
+        stream_get: method_value,
         idempotent,
+        cors,
+        validate,
+        router,
+        testable,
+        max_body: max_body_value,
+        max_depth: max_depth_value,
+        rate_limit,
+        compress,
+        etag,
     })
 }
 
+/// Parses the contents of `cors(...)`.
+///
+/// Accepts `origins`, `headers`, `expose` (comma-separated string lists),
+/// `max_age` (duration shorthand) and the bare `credentials` flag.
+fn parse_cors_list(list: &syn::MetaList) -> Result<CorsConfig, syn::Error> {
+    let nested = list.parse_args_with(
+        syn::punctuated::Punctuated::<syn::Meta, syn::token::Comma>::parse_terminated,
+    )?;
+
+    let mut origins = None;
+    let mut credentials = false;
+    let mut allow_headers = None;
+    let mut expose_headers = None;
+    let mut max_age_secs = None;
+
+    for meta in &nested {
+        match meta {
+            syn::Meta::Path(path) if path.is_ident("credentials") => {
+                if credentials {
+                    return Err(syn::Error::new_spanned(
+                        path,
+                        "duplicate `credentials` flag",
+                    ));
+                }
+                credentials = true;
+            }
+            syn::Meta::NameValue(nv) => {
+                let key = nv.path.get_ident().ok_or_else(|| {
+                    syn::Error::new_spanned(&nv.path, "expected a simple identifier")
+                })?;
+                let value = match &nv.value {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(s),
+                        ..
+                    }) => s.value(),
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            &nv.value,
+                            "expected a string literal",
+                        ));
+                    }
+                };
+
+                let slot = if key == "origins" {
+                    &mut origins
+                } else if key == "headers" {
+                    &mut allow_headers
+                } else if key == "expose" {
+                    &mut expose_headers
+                } else if key == "max_age" {
+                    if max_age_secs.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            key,
+                            "duplicate `max_age` attribute",
+                        ));
+                    }
+                    max_age_secs = Some(
+                        parse_duration(&value)
+                            .map_err(|e| syn::Error::new_spanned(&nv.value, e))?,
+                    );
+                    continue;
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        format!("unknown cors option `{key}`"),
+                    ));
+                };
+                if slot.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        format!("duplicate `{key}` attribute"),
+                    ));
+                }
+                *slot = Some(split_list(&value));
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected `origins`, `headers`, `expose`, `max_age` or `credentials`",
+                ));
+            }
+        }
+    }
+
+    let origins = origins.unwrap_or_else(|| vec!["*".to_string()]);
+    if origins.is_empty() {
+        return Err(syn::Error::new_spanned(
+            list,
+            "cors origins cannot be empty",
+        ));
+    }
+    if origins.len() > 1 && origins.iter().any(|o| o == "*") {
+        return Err(syn::Error::new_spanned(
+            list,
+            "`*` cannot be combined with other cors origins",
+        ));
+    }
+    if credentials && origins.iter().any(|o| o == "*") {
+        return Err(syn::Error::new_spanned(
+            list,
+            "cors `credentials` requires an explicit `origins` list",
+        ));
+    }
+
+    Ok(CorsConfig::Inline {
+        origins,
+        credentials,
+        allow_headers: allow_headers.unwrap_or_default(),
+        expose_headers: expose_headers.unwrap_or_default(),
+        max_age_secs,
+    })
+}
+
+/// Splits a comma-separated attribute value into trimmed, non-empty items.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
 /// Parses human-readable duration shorthand into seconds.
 ///
 /// Supported suffixes: `s` (seconds), `m` (minutes), `h` (hours), `d` (days).
@@ -210,6 +659,101 @@ pub(crate) fn parse_duration(s: &str) -> Result<u64, String> {
     Ok(num * multiplier)
 }
 
+/// Parses a size shorthand like `"512kb"` or `"1mb"` into bytes.
+///
+/// Supported suffixes: `b`, `kb`, `mb`, `gb` (powers of 1024, case-insensitive).
+/// A bare number is a byte count.
+pub(crate) fn parse_size(s: &str) -> Result<usize, String> {
+    let lower = s.trim().to_ascii_lowercase();
+    if lower.is_empty() {
+        return Err("size cannot be empty".into());
+    }
+
+    let (num_str, multiplier) = if let Some(n) = lower.strip_suffix("kb") {
+        (n, 1024)
+    } else if let Some(n) = lower.strip_suffix("mb") {
+        (n, 1024 * 1024)
+    } else if let Some(n) = lower.strip_suffix("gb") {
+        (n, 1024 * 1024 * 1024)
+    } else if let Some(n) = lower.strip_suffix('b') {
+        (n, 1)
+    } else {
+        (lower.as_str(), 1)
+    };
+
+    let num: usize = num_str
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid size `{s}`, expected e.g. `512kb` or `1mb`"))?;
+
+    if num == 0 {
+        return Err(format!("size cannot be zero: `{s}`"));
+    }
+
+    num.checked_mul(multiplier)
+        .ok_or_else(|| format!("size is too large: `{s}`"))
+}
+
+/// Parses a rate like `"10/m"` or `"5/30s"` into `(requests, window_secs)`.
+///
+/// The window is a duration shorthand; a bare unit (`s`, `m`, `h`, `d`) means one of it.
+pub(crate) fn parse_rate(s: &str) -> Result<(u32, u64), String> {
+    let (requests, window) = s
+        .split_once('/')
+        .ok_or_else(|| format!("invalid rate limit `{s}`, expected e.g. `10/m` or `5/30s`"))?;
+    let requests: u32 = requests
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid request count in rate limit `{s}`"))?;
+    if requests == 0 {
+        return Err(format!("rate limit cannot allow zero requests: `{s}`"));
+    }
+    let window = window.trim();
+    let window_secs = if matches!(window, "s" | "m" | "h" | "d") {
+        parse_duration(&format!("1{window}"))?
+    } else {
+        parse_duration(window)?
+    };
+    Ok((requests, window_secs))
+}
+
+/// Parses the rate limit `key`: `"ip"`, `"guard"` or `"header:<name>"`.
+fn parse_rate_limit_key(s: &str) -> Result<RateLimitKey, String> {
+    match s {
+        "ip" => Ok(RateLimitKey::Ip),
+        "guard" => Ok(RateLimitKey::Guard),
+        _ => match s.strip_prefix("header:").map(str::trim) {
+            Some(name) if http_header_name_is_valid(name) => {
+                Ok(RateLimitKey::Header(name.to_ascii_lowercase()))
+            }
+            Some(name) => Err(format!("invalid header name `{name}` in rate limit key")),
+            None => Err(format!(
+                "invalid rate limit key `{s}`, expected `ip`, `guard` or `header:<name>`"
+            )),
+        },
+    }
+}
+
+/// Parses `format = "sse" | "ndjson" | "bytes"`.
+fn parse_stream_format(s: &str) -> Result<StreamFormat, String> {
+    match s {
+        "sse" => Ok(StreamFormat::Sse),
+        "ndjson" => Ok(StreamFormat::Ndjson),
+        "bytes" => Ok(StreamFormat::Bytes),
+        _ => Err(format!(
+            "invalid stream format `{s}`, expected `sse`, `ndjson` or `bytes`"
+        )),
+    }
+}
+
+/// Returns `true` for a non-empty header name made of token characters.
+fn http_header_name_is_valid(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
 /// Builds the `Cache-Control` header value from parsed `cache` and optional `stale` values.
 ///
 /// - `"1h"` → `"public, max-age=0, s-maxage=3600"`
@@ -1,7 +1,7 @@
 use quote::quote;
 use syn::{FnArg, ItemFn, ReturnType, Type};
 
-use crate::attrs::HandlerAttrs;
+use crate::attrs::{CorsConfig, HandlerAttrs, RateLimitConfig, RateLimitKey};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub(crate) enum HandlerKind {
@@ -25,8 +25,21 @@ pub(crate) fn build_handler(
     let HandlerAttrs {
         cache_config,
         init_fn,
+        guard_fn,
         timeout_secs,
         idempotent: _,
+        cors,
+        validate,
+        router,
+        testable,
+        max_body,
+        max_depth,
+        rate_limit,
+        compress,
+        etag,
+        heartbeat_secs: _,
+        stream_format: _,
+        stream_get: _,
     } = attrs;
 
     if func.sig.asyncness.is_none() {
@@ -40,7 +53,7 @@ pub(crate) fn build_handler(
     let fn_block = &func.block;
     let fn_output = &func.sig.output;
 
-    // Separate typed parameters into input, headers, and state params.
+    // Separate typed parameters into input, headers, state, guard, and extractor params.
     let typed_params: Vec<_> = func
         .sig
         .inputs
@@ -54,9 +67,20 @@ pub(crate) fn build_handler(
     let mut input_param = None;
     let mut headers_param = None;
     let mut state_param = None;
+    let mut guard_param = None;
 
     for param in &typed_params {
-        if is_headers_type(&param.ty) {
+        if is_extractor_param(param) {
+            // Extracted via `metaxy::FromRequest`; any number is allowed.
+        } else if is_guarded_type(&param.ty) {
+            if guard_param.is_some() {
+                return Err(syn::Error::new_spanned(
+                    &func.sig.inputs,
+                    "RPC handlers accept at most one Guarded parameter",
+                ));
+            }
+            guard_param = Some(*param);
+        } else if is_headers_type(&param.ty) {
             if headers_param.is_some() {
                 return Err(syn::Error::new_spanned(
                     &func.sig.inputs,
@@ -95,6 +119,20 @@ pub(crate) fn build_handler(
         ));
     }
 
+    if guard_param.is_some() && guard_fn.is_none() {
+        return Err(syn::Error::new_spanned(
+            &func.sig.inputs,
+            "Guarded parameter requires guard = \"...\" attribute",
+        ));
+    }
+
+    if validate && input_param.is_none() {
+        return Err(syn::Error::new_spanned(
+            &func.sig,
+            "validate requires an input parameter",
+        ));
+    }
+
     let input_type = if let Some(param) = input_param {
         let ty = &param.ty;
         quote! { #ty }
@@ -108,35 +146,40 @@ pub(crate) fn build_handler(
         ReturnType::Type(_, ty) => (quote! { #ty }, is_result_type(ty)),
     };
 
-    let expected_method = match kind {
-        HandlerKind::Query => "GET",
-        HandlerKind::Mutation => "POST",
+    let (expected_method, kind_name) = match kind {
+        HandlerKind::Query => ("GET", "query"),
+        HandlerKind::Mutation => ("POST", "mutation"),
+    };
+    let procedure_name = fn_name.to_string();
+
+    // Queries also accept a marked `POST` carrying the input in the body.
+    let allow_body_query = match kind {
+        HandlerKind::Query => quote! {
+            && !::metaxy::__private::is_body_query(__req.method(), __req.headers())
+        },
+        HandlerKind::Mutation => quote! {},
     };
 
     let parse_input = match kind {
         HandlerKind::Query => quote! {
             let __input: #input_type = {
-                let __url = match ::metaxy::__private::url::Url::parse(
-                    &format!("http://localhost{}", __req.uri())
-                ) {
-                    Ok(u) => u,
-                    Err(e) => return __rpc_error_response(400, &format!("Invalid URL: {}", e)),
+                // `?input=`, `?input64=` or, for inputs too long for a URL, the body.
+                let __raw = match ::metaxy::__private::read_query_input(__req, &__RPC_LIMITS).await {
+                    Ok(__raw) => __raw,
+                    Err(__e) => return __rpc_input_error_response(&__e),
                 };
 
-                let __raw = __url
-                    .query_pairs()
-                    .find(|(k, _)| k == "input")
-                    .map(|(_, v)| v.into_owned());
-
                 match __raw {
-                    Some(ref __s) => match ::metaxy::__private::serde_json::from_str(__s) {
-                        Ok(v) => v,
-                        Err(e) => return __rpc_error_response(400,
-                            &format!("Failed to deserialize input: {}", e)),
-                    },
+                    Some(ref __s) => {
+                        match ::metaxy::__private::serde_json::from_slice(__s) {
+                            Ok(v) => v,
+                            Err(e) => return __rpc_error_response(400, "BAD_INPUT",
+                                &format!("Failed to deserialize input: {}", e)),
+                        }
+                    }
                     None => match ::metaxy::__private::serde_json::from_value(::metaxy::__private::serde_json::Value::Null) {
                         Ok(v) => v,
-                        Err(e) => return __rpc_error_response(400,
+                        Err(e) => return __rpc_error_response(400, "BAD_INPUT",
                             &format!("Missing required input parameter: {}", e)),
                     },
                 }
@@ -144,23 +187,21 @@ pub(crate) fn build_handler(
         },
         HandlerKind::Mutation => quote! {
             let __input: #input_type = {
-                use ::metaxy::__private::http_body_util::BodyExt as _;
-                let __collected = __req.into_body().collect().await
-                    .map_err(|e| ::metaxy::__private::vercel_runtime::Error::from(
-                        format!("Failed to read request body: {}", e)
-                    ))?;
-                let __bytes = __collected.to_bytes();
+                let __bytes = match ::metaxy::__private::read_body_input(__req, &__RPC_LIMITS).await {
+                    Ok(__bytes) => __bytes,
+                    Err(__e) => return __rpc_input_error_response(&__e),
+                };
 
                 if __bytes.is_empty() {
                     match ::metaxy::__private::serde_json::from_value(::metaxy::__private::serde_json::Value::Null) {
                         Ok(v) => v,
-                        Err(e) => return __rpc_error_response(400,
+                        Err(e) => return __rpc_error_response(400, "BAD_INPUT",
                             &format!("Missing required request body: {}", e)),
                     }
                 } else {
                     match ::metaxy::__private::serde_json::from_slice(&__bytes) {
                         Ok(v) => v,
-                        Err(e) => return __rpc_error_response(400,
+                        Err(e) => return __rpc_error_response(400, "BAD_INPUT",
                             &format!("Failed to deserialize request body: {}", e)),
                     }
                 }
@@ -168,29 +209,40 @@ pub(crate) fn build_handler(
         },
     };
 
+    let probe_error = probe_error();
+
+    // Serialize the success value `__val` and apply `RpcResponse` metadata when present.
+    let ok_response = quote! {
+        use ::metaxy::__private::{NoResponseMeta as _, WithResponseMeta as _};
+        let __meta = (&::metaxy::__private::ResponseProbe(&__val)).__rpc_response_meta();
+        let __data = ::metaxy::__private::serde_json::to_value(&__val)
+            .map_err(|e| ::metaxy::__private::vercel_runtime::Error::from(
+                format!("Failed to serialize response: {}", e)
+            ))?;
+        let mut __res = __rpc_ok_response(__data)?;
+        if let Some(__meta) = __meta {
+            __meta.apply(&mut __res);
+        }
+        Ok(__res)
+    };
+
     // Build the result handling block depending on whether the fn returns Result<T, E>
     let result_handling = if returns_result {
         quote! {
             match __raw_result {
                 Ok(__val) => {
-                    let __data = ::metaxy::__private::serde_json::to_value(&__val)
-                        .map_err(|e| ::metaxy::__private::vercel_runtime::Error::from(
-                            format!("Failed to serialize response: {}", e)
-                        ))?;
-                    __rpc_ok_response(__data)
+                    #ok_response
                 }
                 Err(__err) => {
-                    __rpc_error_response(400, &format!("{}", __err))
+                    #probe_error
+                    __rpc_error_response_with_data(__status, "HANDLER_ERROR", &format!("{}", __err), __data)
                 }
             }
         }
     } else {
         quote! {
-            let __data = ::metaxy::__private::serde_json::to_value(&__raw_result)
-                .map_err(|e| ::metaxy::__private::vercel_runtime::Error::from(
-                    format!("Failed to serialize response: {}", e)
-                ))?;
-            __rpc_ok_response(__data)
+            let __val = __raw_result;
+            #ok_response
         }
     };
 
@@ -220,8 +272,14 @@ pub(crate) fn build_handler(
 
     let call_args: Vec<_> = typed_params
         .iter()
-        .map(|param| {
-            if is_headers_type(&param.ty) {
+        .enumerate()
+        .map(|(i, param)| {
+            if is_extractor_param(param) {
+                let ident = extractor_ident(i);
+                quote! { #ident }
+            } else if is_guarded_type(&param.ty) {
+                quote! { ::metaxy::Guarded(__guard_ctx) }
+            } else if is_headers_type(&param.ty) {
                 quote! { __headers }
             } else if is_ref_type(&param.ty) {
                 quote! { __state }
@@ -270,23 +328,129 @@ pub(crate) fn build_handler(
         quote! {}
     };
 
+    // Router and testable handlers are served through a `Procedure`, which
+    // runs `init` once on the first request (or ahead of it from `main`).
+    let in_module = router || testable;
+    let lazy_init = build_lazy_init(init_fn.as_deref(), state_param, &func.sig)?;
+    let (state_static, extract_state) = if in_module {
+        (lazy_init.statics.clone(), lazy_init.per_request.clone())
+    } else {
+        (state_static, extract_state)
+    };
+
+    let cors_const = build_cors_const(cors.as_ref(), &func.sig)?;
+
+    // Run the guard and `FromRequest` extractors on the request parts before the input is parsed.
+    let extract_request = build_request_extraction(
+        guard_fn.as_deref(),
+        state_param.is_some(),
+        &typed_params,
+        &func.sig,
+        &quote! {
+            return __rpc_error_response_with_data(__status, "HANDLER_ERROR", &format!("{}", __err), __data);
+        },
+    )?;
+    let extract_request = if extract_request.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            let (__parts, __body) = __req.into_parts();
+            #extract_request
+            let __req = ::metaxy::__private::hyper::Request::from_parts(__parts, __body);
+        }
+    };
+
+    // Throttle by IP or header before the guard runs, or by the guard's identity after it.
+    let rate_limit = build_rate_limit(
+        rate_limit.as_ref(),
+        &func.sig,
+        &quote! { __req.headers() },
+        &quote! { return Ok(__rate_limited); },
+    )?;
+    let rate_limit_statics = &rate_limit.statics;
+    let rate_limit_before_guard = &rate_limit.before_guard;
+    let rate_limit_after_guard = &rate_limit.after_guard;
+    let rate_limit_expose = &rate_limit.expose;
+
+    // Run `metaxy::Validate` on the parsed input and reject with 422 on failure.
+    let (validate_input, validation_response_fn) = if validate {
+        (
+            quote! {
+                if let Err(__errors) = ::metaxy::Validate::validate(&__input) {
+                    return __rpc_validation_response(&__errors);
+                }
+            },
+            quote! {
+                // Builds a 422 response listing every failed field in `error.fields`.
+                fn __rpc_validation_response(
+                    errors: &::metaxy::ValidationErrors,
+                ) -> Result<::metaxy::__private::vercel_runtime::Response<::metaxy::__private::serde_json::Value>, ::metaxy::__private::vercel_runtime::Error> {
+                    let builder = ::metaxy::__private::vercel_runtime::Response::builder()
+                        .status(422)
+                        .header("Content-Type", "application/json");
+
+                    let mut error = ::metaxy::__private::error_object("VALIDATION_FAILED", &errors.to_string());
+                    error["fields"] = ::metaxy::__private::serde_json::to_value(errors).unwrap_or_default();
+
+                    Ok(builder.body(::metaxy::__private::serde_json::json!({ "error": error }))?)
+                }
+            },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    // A panic in the handler body becomes a 500 instead of aborting the invocation.
+    let call = quote! {
+        ::metaxy::__private::catch_panic(#procedure_name, #fn_name(#(#call_args),*))
+    };
     let invoke_user_fn = if let Some(secs) = timeout_secs {
         quote! {
             match ::metaxy::__private::tokio::time::timeout(
                 ::std::time::Duration::from_secs(#secs),
-                #fn_name(#(#call_args),*),
+                #call,
             ).await {
+                Ok(Ok(result)) => result,
+                Ok(Err(__panic)) => return __rpc_error_response(500, "INTERNAL", &__panic),
+                Err(_) => return __rpc_error_response(504, "TIMEOUT", "Handler timed out"),
+            }
+        }
+    } else {
+        quote! {
+            match #call.await {
                 Ok(result) => result,
-                Err(_) => return __rpc_error_response(504, "Handler timed out"),
+                Err(__panic) => return __rpc_error_response(500, "INTERNAL", &__panic),
             }
         }
+    };
+
+    let request_type = if in_module {
+        quote! { ::metaxy::__private::axum::extract::Request }
     } else {
-        quote! { #fn_name(#(#call_args),*).await }
+        quote! { ::metaxy::__private::vercel_runtime::Request }
     };
 
-    let expanded = quote! {
-        #state_static
+    let compress = compress.unwrap_or(true);
 
+    // Tag successful responses and answer a matching `If-None-Match` with 304.
+    let (etag_capture, etag_apply, etag_expose) = if etag {
+        (
+            quote! {
+                let __if_none_match = __req
+                    .headers()
+                    .get(::metaxy::__private::http::header::IF_NONE_MATCH)
+                    .cloned();
+            },
+            quote! {
+                .map(|__res| ::metaxy::__private::etag_response(__if_none_match.as_ref(), __res))
+            },
+            quote! { ::metaxy::__private::expose_etag(__res.headers_mut()); },
+        )
+    } else {
+        (quote! {}, quote! {}, quote! {})
+    };
+
+    let main_fn = quote! {
         fn main() -> Result<(), ::metaxy::__private::vercel_runtime::Error> {
             ::metaxy::__private::tokio::runtime::Builder::new_current_thread()
                 .enable_all()
@@ -296,35 +460,41 @@ pub(crate) fn build_handler(
                 ))?
                 .block_on(async {
                     #init_call
-                    ::metaxy::__private::vercel_runtime::run(
-                        ::metaxy::__private::vercel_runtime::service_fn(__rpc_handler),
+                    ::metaxy::__private::run(
+                        ::metaxy::__private::vercel_runtime::service_fn(__rpc_serve),
                     ).await
                 })
         }
 
-        // Shared CORS headers applied to every response.
-        fn __rpc_cors_headers() -> [(&'static str, &'static str); 4] {
-            [
-                ("Access-Control-Allow-Origin", "*"),
-                ("Access-Control-Allow-Methods", "GET, POST, OPTIONS"),
-                ("Access-Control-Allow-Headers", "Content-Type, Authorization"),
-                ("Access-Control-Max-Age", "86400"),
-            ]
+        // Serializes the response of `__rpc_handler` in the format the client accepts,
+        // compressed when it accepts that too.
+        async fn __rpc_serve(
+            __req: ::metaxy::__private::vercel_runtime::Request,
+        ) -> Result<::metaxy::__private::vercel_runtime::Response<::metaxy::__private::vercel_runtime::ResponseBody>, ::metaxy::__private::vercel_runtime::Error> {
+            let __format = ::metaxy::__private::Format::negotiate(__req.headers());
+            let __compression = ::metaxy::__private::Compression::negotiate(__req.headers(), #compress);
+            Ok(__compression.vercel_response(__format.serialize(__rpc_handler(__req).await?)))
         }
+    };
+
+    let limits_const = build_limits_const(max_body, max_depth);
+
+    let handler_items = quote! {
+        #cors_const
+
+        #limits_const
 
-        // Builds a successful JSON response with CORS headers.
+        #rate_limit_statics
+
+        // Builds a successful JSON response.
         fn __rpc_ok_response(
             data: ::metaxy::__private::serde_json::Value,
         ) -> Result<::metaxy::__private::vercel_runtime::Response<::metaxy::__private::serde_json::Value>, ::metaxy::__private::vercel_runtime::Error> {
-            let mut builder = ::metaxy::__private::vercel_runtime::Response::builder()
+            let builder = ::metaxy::__private::vercel_runtime::Response::builder()
                 .status(200)
                 .header("Content-Type", "application/json")
                 #cache_header;
 
-            for (k, v) in __rpc_cors_headers() {
-                builder = builder.header(k, v);
-            }
-
             Ok(builder.body(::metaxy::__private::serde_json::json!({
                 "result": {
                     "type": "response",
@@ -333,44 +503,80 @@ pub(crate) fn build_handler(
             }))?)
         }
 
-        // Builds a JSON error response with CORS headers.
+        // Builds a JSON error response with a stable `error.code`.
         fn __rpc_error_response(
             status: u16,
+            code: &str,
             message: &str,
         ) -> Result<::metaxy::__private::vercel_runtime::Response<::metaxy::__private::serde_json::Value>, ::metaxy::__private::vercel_runtime::Error> {
-            let mut builder = ::metaxy::__private::vercel_runtime::Response::builder()
+            __rpc_error_response_with_data(status, code, message, None)
+        }
+
+        // Builds a JSON error response carrying the serialized handler error in `error.data`.
+        fn __rpc_error_response_with_data(
+            status: u16,
+            code: &str,
+            message: &str,
+            data: Option<::metaxy::__private::serde_json::Value>,
+        ) -> Result<::metaxy::__private::vercel_runtime::Response<::metaxy::__private::serde_json::Value>, ::metaxy::__private::vercel_runtime::Error> {
+            let builder = ::metaxy::__private::vercel_runtime::Response::builder()
                 .status(status)
                 .header("Content-Type", "application/json");
 
-            for (k, v) in __rpc_cors_headers() {
-                builder = builder.header(k, v);
+            let mut error = ::metaxy::__private::error_object(code, message);
+            if let Some(data) = data {
+                error["data"] = data;
             }
 
-            Ok(builder.body(::metaxy::__private::serde_json::json!({
-                "error": {
-                    "type": "error",
-                    "message": message
-                }
-            }))?)
+            Ok(builder.body(::metaxy::__private::serde_json::json!({ "error": error }))?)
         }
 
+        // Builds the 413/400 response for an input rejected by `__RPC_LIMITS`.
+        fn __rpc_input_error_response(
+            err: &::metaxy::__private::InputError,
+        ) -> Result<::metaxy::__private::vercel_runtime::Response<::metaxy::__private::serde_json::Value>, ::metaxy::__private::vercel_runtime::Error> {
+            Ok(::metaxy::__private::vercel_runtime::Response::builder()
+                .status(err.status())
+                .header("Content-Type", "application/json")
+                .body(err.to_json())?)
+        }
+
+        #validation_response_fn
+
+        // Runs `__rpc_dispatch` in a request span and applies the CORS policy to every response.
         async fn __rpc_handler(
-            __req: ::metaxy::__private::vercel_runtime::Request,
+            __req: #request_type,
+        ) -> Result<::metaxy::__private::vercel_runtime::Response<::metaxy::__private::serde_json::Value>, ::metaxy::__private::vercel_runtime::Error> {
+            let __span = ::metaxy::__private::RequestSpan::new(#procedure_name, #kind_name, __req.headers());
+            let __origin = __req
+                .headers()
+                .get(::metaxy::__private::http::header::ORIGIN)
+                .cloned();
+            #etag_capture
+            let mut __res = __span.finish_result(
+                __span.instrument(__rpc_dispatch(__req)).await #etag_apply,
+            )?;
+            __RPC_CORS.apply(__origin.as_ref(), __res.headers_mut());
+            #etag_expose
+            #rate_limit_expose
+            Ok(__res)
+        }
+
+        async fn __rpc_dispatch(
+            __req: #request_type,
         ) -> Result<::metaxy::__private::vercel_runtime::Response<::metaxy::__private::serde_json::Value>, ::metaxy::__private::vercel_runtime::Error> {
             // Handle CORS preflight
             if __req.method() == "OPTIONS" {
-                let mut builder = ::metaxy::__private::vercel_runtime::Response::builder()
-                    .status(204);
-                for (k, v) in __rpc_cors_headers() {
-                    builder = builder.header(k, v);
-                }
-                return Ok(builder.body(::metaxy::__private::serde_json::Value::Null)?);
+                return Ok(::metaxy::__private::vercel_runtime::Response::builder()
+                    .status(204)
+                    .body(::metaxy::__private::serde_json::Value::Null)?);
             }
 
             // Validate HTTP method
-            if __req.method() != #expected_method {
+            if __req.method() != #expected_method #allow_body_query {
                 return __rpc_error_response(
                     405,
+                    "METHOD_NOT_ALLOWED",
                     &format!(
                         "Method {} not allowed, expected {}",
                         __req.method(),
@@ -383,8 +589,16 @@ pub(crate) fn build_handler(
 
             #extract_state
 
+            #rate_limit_before_guard
+
+            #extract_request
+
+            #rate_limit_after_guard
+
             #parse_input
 
+            #validate_input
+
             async fn #fn_name(#(#inner_fn_params),*) -> #return_type
             #fn_block
 
@@ -394,7 +608,478 @@ pub(crate) fn build_handler(
         }
     };
 
-    Ok(expanded)
+    if in_module {
+        let procedure_kind = match kind {
+            HandlerKind::Query => quote! { Query },
+            HandlerKind::Mutation => quote! { Mutation },
+        };
+        let route = quote! {
+            ::metaxy::__private::axum::routing::any(
+                |__req: ::metaxy::__private::axum::extract::Request| async move {
+                    let __format = ::metaxy::__private::Format::negotiate(__req.headers());
+                    let __compression = ::metaxy::__private::Compression::negotiate(__req.headers(), #compress);
+                    let __res = ::metaxy::__private::into_axum_response(__rpc_handler(__req).await, __format);
+                    __compression.axum_response(__res).await
+                },
+            )
+        };
+        let lazy_items = &lazy_init.items;
+        let module = build_router_module(
+            &func,
+            &quote! {
+                #state_static
+                #lazy_items
+                #handler_items
+            },
+            &procedure_kind,
+            &route,
+        );
+        if router {
+            return Ok(module);
+        }
+
+        let run_init = init_fn
+            .as_ref()
+            .map(|_| quote! { #fn_name::__rpc_init().await; });
+        return Ok(quote! {
+            #module
+
+            #[cfg(not(test))]
+            fn main() -> Result<(), ::metaxy::__private::vercel_runtime::Error> {
+                ::metaxy::__private::tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|e| ::metaxy::__private::vercel_runtime::Error::from(
+                        format!("Failed to build tokio runtime: {}", e)
+                    ))?
+                    .block_on(async {
+                        #run_init
+                        ::metaxy::__private::serve_procedure(#fn_name::__rpc_procedure()).await
+                    })
+            }
+        });
+    }
+
+    Ok(quote! {
+        #state_static
+        #main_fn
+        #handler_items
+    })
+}
+
+/// `init` handling for handlers compiled into a [`build_router_module`].
+pub(crate) struct LazyInit {
+    /// Statics holding the init result.
+    pub statics: proc_macro2::TokenStream,
+    /// Per-request statement running `init` on first use and binding `__state`.
+    pub per_request: proc_macro2::TokenStream,
+    /// Module items: `__rpc_init()` to run `init` ahead of the first request
+    /// and, under `cfg(test)`, `__rpc_procedure_with_state()`.
+    pub items: proc_macro2::TokenStream,
+}
+
+/// Builds the `init` handling for router and testable modes.
+///
+/// There is no `main` to run `init` before serving, so it runs once on the
+/// first request through a `tokio::sync::OnceCell`, binding `__state` when the
+/// handler declares a state parameter. In test builds a state injected with
+/// `__rpc_procedure_with_state()` takes precedence over the cell, so tests can
+/// run against different states in parallel.
+pub(crate) fn build_lazy_init(
+    init_fn: Option<&str>,
+    state_param: Option<&syn::PatType>,
+    sig: &syn::Signature,
+) -> Result<LazyInit, syn::Error> {
+    let Some(path) = init_fn else {
+        return Ok(LazyInit {
+            statics: quote! {},
+            per_request: quote! {},
+            items: quote! {},
+        });
+    };
+    let path: proc_macro2::TokenStream = path.parse().map_err(|_| {
+        syn::Error::new_spanned(sig, format!("invalid init function path: `{path}`"))
+    })?;
+
+    let Some(Type::Reference(r)) = state_param.map(|p| &*p.ty) else {
+        return Ok(LazyInit {
+            statics: quote! {
+                static __RPC_INIT: ::metaxy::__private::tokio::sync::OnceCell<()> =
+                    ::metaxy::__private::tokio::sync::OnceCell::const_new();
+            },
+            per_request: quote! {
+                __RPC_INIT.get_or_init(|| async { #path().await; }).await;
+            },
+            items: quote! {
+                // Runs `init` before serving.
+                #[allow(dead_code)]
+                pub async fn __rpc_init() {
+                    __RPC_INIT.get_or_init(|| async { #path().await; }).await;
+                }
+            },
+        });
+    };
+    let inner_ty = &r.elem;
+
+    Ok(LazyInit {
+        statics: quote! {
+            static __RPC_STATE: ::metaxy::__private::tokio::sync::OnceCell<#inner_ty> =
+                ::metaxy::__private::tokio::sync::OnceCell::const_new();
+
+            #[cfg(test)]
+            ::metaxy::__private::tokio::task_local! {
+                static __RPC_TEST_STATE: &'static #inner_ty;
+            }
+        },
+        per_request: quote! {
+            #[cfg(test)]
+            let __state = match __RPC_TEST_STATE.try_with(|__state| *__state) {
+                Ok(__state) => __state,
+                Err(_) => __RPC_STATE.get_or_init(|| async { #path().await }).await,
+            };
+            #[cfg(not(test))]
+            let __state = __RPC_STATE.get_or_init(|| async { #path().await }).await;
+        },
+        items: quote! {
+            // Runs `init` before serving.
+            #[allow(dead_code)]
+            pub async fn __rpc_init() {
+                __RPC_STATE.get_or_init(|| async { #path().await }).await;
+            }
+
+            // Serves this procedure with `state` in place of the `init` result.
+            #[cfg(test)]
+            #[allow(dead_code)]
+            pub fn __rpc_procedure_with_state(state: #inner_ty) -> ::metaxy::__private::Procedure {
+                let __state: &'static #inner_ty = ::std::boxed::Box::leak(::std::boxed::Box::new(state));
+                __rpc_procedure().map_route(|__route| {
+                    __route.layer(::metaxy::__private::axum::middleware::from_fn(
+                        move |__req: ::metaxy::__private::axum::extract::Request,
+                              __next: ::metaxy::__private::axum::middleware::Next| {
+                            __RPC_TEST_STATE.scope(__state, __next.run(__req))
+                        },
+                    ))
+                })
+            }
+        },
+    })
+}
+
+/// Wraps the generated items of a router-mode or testable handler in a module
+/// named after the function, exposing `__rpc_procedure()` for
+/// `metaxy::router!` and `metaxy::test_client!`. `kind` names the
+/// `ProcedureKind` variant.
+///
+/// The module takes the function's visibility, so `pub async fn` handlers can
+/// be registered from other modules. Functions and modules live in different
+/// namespaces, and the user function itself is nested inside the dispatcher,
+/// so the name is free.
+pub(crate) fn build_router_module(
+    func: &ItemFn,
+    items: &proc_macro2::TokenStream,
+    kind: &proc_macro2::TokenStream,
+    route: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let vis = &func.vis;
+    let fn_name = &func.sig.ident;
+    let name = fn_name.to_string();
+    quote! {
+        #[doc(hidden)]
+        #vis mod #fn_name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            #items
+
+            // Registers this procedure with `metaxy::router!`.
+            #[allow(dead_code)]
+            pub fn __rpc_procedure() -> ::metaxy::__private::Procedure {
+                ::metaxy::__private::Procedure::new(
+                    #name,
+                    ::metaxy::__private::ProcedureKind::#kind,
+                    #route,
+                )
+            }
+        }
+    }
+}
+
+/// Builds the `__RPC_LIMITS` constant from the `max_body` and `max_depth`
+/// attributes, falling back to the crate-wide defaults.
+pub(crate) fn build_limits_const(
+    max_body: Option<usize>,
+    max_depth: Option<usize>,
+) -> proc_macro2::TokenStream {
+    let limits = if max_body.is_none() && max_depth.is_none() {
+        quote! { ::metaxy::__private::InputLimits::DEFAULT }
+    } else {
+        let max_body =
+            max_body.map_or_else(|| quote! { ::metaxy::DEFAULT_MAX_BODY }, |v| quote! { #v });
+        let max_depth =
+            max_depth.map_or_else(|| quote! { ::metaxy::DEFAULT_MAX_DEPTH }, |v| quote! { #v });
+        quote! { ::metaxy::__private::InputLimits::new(#max_body, #max_depth) }
+    };
+    quote! {
+        const __RPC_LIMITS: ::metaxy::__private::InputLimits = #limits;
+    }
+}
+
+/// Rate limit check generated from the `rate_limit` attribute.
+#[derive(Default)]
+pub(crate) struct RateLimitCheck {
+    /// The default in-memory store, when no `store` is given.
+    pub statics: proc_macro2::TokenStream,
+    /// Check keyed by IP or header, run before the guard.
+    pub before_guard: proc_macro2::TokenStream,
+    /// Check keyed by the guard value, run after it.
+    pub after_guard: proc_macro2::TokenStream,
+    /// Exposes `Retry-After` on `__res` once the CORS policy has been applied.
+    pub expose: proc_macro2::TokenStream,
+}
+
+/// Builds the rate limit check for `config`, reading request headers through
+/// `headers` (an expression of type `&HeaderMap`).
+///
+/// `reject` runs with the `__rate_limited` response in scope when the request
+/// is denied, or has no value for a header key, and must diverge. Requests
+/// without a client IP are not limited.
+pub(crate) fn build_rate_limit(
+    config: Option<&RateLimitConfig>,
+    sig: &syn::Signature,
+    headers: &proc_macro2::TokenStream,
+    reject: &proc_macro2::TokenStream,
+) -> Result<RateLimitCheck, syn::Error> {
+    let Some(config) = config else {
+        return Ok(RateLimitCheck::default());
+    };
+    let name = sig.ident.to_string();
+    let requests = config.requests;
+    let window_secs = config.window_secs;
+
+    let (statics, store) = if let Some(path) = &config.store {
+        let path: proc_macro2::TokenStream = path.parse().map_err(|_| {
+            syn::Error::new_spanned(sig, format!("invalid rate limit store path: `{path}`"))
+        })?;
+        (quote! {}, path)
+    } else {
+        (
+            quote! {
+                static __RPC_RATE_LIMIT_STORE: ::metaxy::MemoryStore = ::metaxy::MemoryStore::new();
+            },
+            quote! { __RPC_RATE_LIMIT_STORE },
+        )
+    };
+
+    let check = quote! {
+        {
+            use ::metaxy::RateLimitStore as _;
+            let __rate_key = format!("{}:{}", #name, __rate_key);
+            let __limit = ::metaxy::RateLimit::new(
+                #requests,
+                ::std::time::Duration::from_secs(#window_secs),
+            );
+            if let ::metaxy::RateLimitDecision::Deny { retry_after: __retry_after } =
+                #store.check(&__rate_key, __limit).await
+            {
+                let __rate_limited = ::metaxy::__private::rate_limited(__retry_after);
+                #reject
+            }
+        }
+    };
+    let check = match &config.key {
+        RateLimitKey::Ip => quote! {
+            if let Some(__rate_key) = ::metaxy::__private::ip_key(#headers) #check
+        },
+        RateLimitKey::Header(header) => quote! {
+            match ::metaxy::__private::header_key(#headers, #header) {
+                Some(__rate_key) => #check
+                None => {
+                    let __rate_limited = ::metaxy::__private::missing_key_header(#header);
+                    #reject
+                }
+            }
+        },
+        RateLimitKey::Guard => quote! {
+            {
+                let __rate_key = ::metaxy::RateLimitKey::rate_limit_key(&__guard_ctx);
+                #check
+            }
+        },
+    };
+
+    let expose = quote! { ::metaxy::__private::expose_retry_after(__res.headers_mut()); };
+    Ok(if config.key == RateLimitKey::Guard {
+        RateLimitCheck {
+            statics,
+            before_guard: quote! {},
+            after_guard: check,
+            expose,
+        }
+    } else {
+        RateLimitCheck {
+            statics,
+            before_guard: check,
+            after_guard: quote! {},
+            expose,
+        }
+    })
+}
+
+/// Binds `__status` and `__data` for the handler or guard error `__err`.
+///
+/// Uses autoref specialization so error types without `RpcErrorStatus` fall
+/// back to `400` and types without `Serialize` omit `error.data`.
+pub(crate) fn probe_error() -> proc_macro2::TokenStream {
+    quote! {
+        use ::metaxy::__private::{
+            CustomErrorStatus as _, DefaultErrorStatus as _,
+            NoErrorData as _, SerializeErrorData as _,
+        };
+        let __status = (&::metaxy::__private::ErrorProbe(&__err)).__rpc_error_status();
+        let __data = (&::metaxy::__private::ErrorProbe(&__err)).__rpc_error_data();
+    }
+}
+
+/// Builds the statements that run the guard and every `FromRequest` extractor
+/// against `__parts`, binding `__guard_ctx` and `__from_request_{i}`.
+///
+/// The guard receives the init state as a second argument when the handler
+/// declares a state parameter. `reject` runs on failure with `__err`,
+/// `__status` and `__data` in scope and must diverge. Returns an empty stream
+/// when there is nothing to extract.
+pub(crate) fn build_request_extraction(
+    guard_fn: Option<&str>,
+    with_state: bool,
+    typed_params: &[&syn::PatType],
+    sig: &syn::Signature,
+    reject: &proc_macro2::TokenStream,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let probe_error = probe_error();
+
+    let run_guard = if let Some(path) = guard_fn {
+        let path: proc_macro2::TokenStream = path.parse().map_err(|_| {
+            syn::Error::new_spanned(sig, format!("invalid guard function path: `{path}`"))
+        })?;
+        let state_arg = if with_state {
+            quote! { , __state }
+        } else {
+            quote! {}
+        };
+        quote! {
+            let __guard_ctx = match #path(&__parts #state_arg).await {
+                Ok(__ctx) => __ctx,
+                Err(__err) => {
+                    #probe_error
+                    #reject
+                }
+            };
+        }
+    } else {
+        quote! {}
+    };
+
+    let run_extractors = typed_params
+        .iter()
+        .enumerate()
+        .filter(|(_, param)| is_extractor_param(param))
+        .map(|(i, param)| {
+            let ident = extractor_ident(i);
+            let ty = &param.ty;
+            quote! {
+                let #ident = match <#ty as ::metaxy::FromRequest>::from_request(&__parts) {
+                    Ok(__value) => __value,
+                    Err(__err) => {
+                        #probe_error
+                        #reject
+                    }
+                };
+            }
+        });
+
+    Ok(quote! {
+        #run_guard
+        #(#run_extractors)*
+    })
+}
+
+/// Name of the local binding holding the extracted value of parameter `index`.
+pub(crate) fn extractor_ident(index: usize) -> syn::Ident {
+    quote::format_ident!("__from_request_{}", index)
+}
+
+/// Built-in `metaxy::FromRequest` types recognised by name.
+const BUILTIN_EXTRACTORS: &[&str] = &[
+    "Cookies",
+    "ClientIp",
+    "RequestInfo",
+    "VercelContext",
+    "LastEventId",
+];
+
+/// Returns `true` if the parameter is extracted through `metaxy::FromRequest`:
+/// either it is marked `#[from_request]` or its type is one of the built-in
+/// extractors (`Cookies`, `ClientIp`, `RequestInfo`, `VercelContext`,
+/// `LastEventId`).
+///
+/// **Limitation:** built-ins are matched by name only, like [`is_headers_type`].
+pub(crate) fn is_extractor_param(param: &syn::PatType) -> bool {
+    if param
+        .attrs
+        .iter()
+        .any(|a| a.path().is_ident("from_request"))
+    {
+        return true;
+    }
+    if let Type::Path(type_path) = &*param.ty
+        && let Some(segment) = type_path.path.segments.last()
+    {
+        return BUILTIN_EXTRACTORS.iter().any(|name| segment.ident == name);
+    }
+    false
+}
+
+/// Builds the `__RPC_CORS` constant consumed by the generated handler.
+///
+/// Without a `cors` attribute the permissive `metaxy::Cors::DEFAULT` is used.
+pub(crate) fn build_cors_const(
+    cors: Option<&CorsConfig>,
+    sig: &syn::Signature,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let value = match cors {
+        None => quote! { ::metaxy::Cors::DEFAULT },
+        Some(CorsConfig::Path(path)) => {
+            let path: proc_macro2::TokenStream = path.parse().map_err(|_| {
+                syn::Error::new_spanned(sig, format!("invalid cors path: `{path}`"))
+            })?;
+            quote! { #path }
+        }
+        Some(CorsConfig::Inline {
+            origins,
+            credentials,
+            allow_headers,
+            expose_headers,
+            max_age_secs,
+        }) => {
+            let max_age = match max_age_secs {
+                Some(secs) => quote! { #secs },
+                None => quote! { ::metaxy::Cors::DEFAULT.max_age },
+            };
+            quote! {
+                ::metaxy::Cors {
+                    origins: &[#(#origins),*],
+                    credentials: #credentials,
+                    allow_headers: &[#(#allow_headers),*],
+                    expose_headers: &[#(#expose_headers),*],
+                    max_age: #max_age,
+                }
+            }
+        }
+    };
+
+    Ok(quote! {
+        // CORS policy applied to every response.
+        const __RPC_CORS: ::metaxy::Cors = #value;
+    })
 }
 
 /// Returns `true` if the type syntactically ends with `Headers`
@@ -412,6 +1097,19 @@ pub(crate) fn is_headers_type(ty: &Type) -> bool {
     false
 }
 
+/// Returns `true` if the type syntactically ends with `Guarded`
+/// (e.g. `Guarded<User>`, `metaxy::Guarded<User>`).
+///
+/// **Limitation:** purely syntactic, like [`is_headers_type`].
+pub(crate) fn is_guarded_type(ty: &Type) -> bool {
+    if let Type::Path(type_path) = ty
+        && let Some(segment) = type_path.path.segments.last()
+    {
+        return segment.ident == "Guarded";
+    }
+    false
+}
+
 /// Returns `true` if the type is a shared (immutable) reference `&T`.
 pub(crate) fn is_ref_type(ty: &Type) -> bool {
     matches!(ty, Type::Reference(r) if r.mutability.is_none())
@@ -1,17 +1,69 @@
 use quote::quote;
 use syn::{FnArg, ItemFn, ReturnType, Type};
 
-use crate::attrs::HandlerAttrs;
-use crate::codegen::{is_headers_type, is_ref_type};
+use crate::attrs::{HandlerAttrs, RateLimitKey, StreamFormat};
+use crate::codegen::{
+    build_cors_const, build_lazy_init, build_limits_const, build_rate_limit,
+    build_request_extraction, build_router_module, extractor_ident, is_extractor_param,
+    is_guarded_type, is_headers_type, is_ref_type, is_result_type, probe_error,
+};
+
+/// Returns the format of a type syntactically ending with `StreamSender`,
+/// `NdjsonSender` or `ByteSender`.
+fn sender_format(ty: &Type) -> Option<StreamFormat> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident == "StreamSender" {
+        Some(StreamFormat::Sse)
+    } else if segment.ident == "NdjsonSender" {
+        Some(StreamFormat::Ndjson)
+    } else if segment.ident == "ByteSender" {
+        Some(StreamFormat::Bytes)
+    } else {
+        None
+    }
+}
 
-/// Checks whether a type syntactically ends with `StreamSender`.
+/// Checks whether a type is one of the stream sender types.
 fn is_stream_sender_type(ty: &Type) -> bool {
-    if let Type::Path(type_path) = ty
-        && let Some(segment) = type_path.path.segments.last()
-    {
-        return segment.ident == "StreamSender";
+    sender_format(ty).is_some()
+}
+
+/// Name of the sender type a stream of this format takes.
+fn sender_name(format: StreamFormat) -> &'static str {
+    match format {
+        StreamFormat::Sse => "StreamSender",
+        StreamFormat::Ndjson => "NdjsonSender",
+        StreamFormat::Bytes => "ByteSender",
     }
-    false
+}
+
+/// Returns `T` from an `impl Stream<Item = T>` return type.
+fn stream_item_type(output: &ReturnType) -> Option<&Type> {
+    let ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+    let Type::ImplTrait(impl_trait) = &**ty else {
+        return None;
+    };
+    impl_trait.bounds.iter().find_map(|bound| {
+        let syn::TypeParamBound::Trait(bound) = bound else {
+            return None;
+        };
+        let segment = bound.path.segments.last()?;
+        if segment.ident != "Stream" {
+            return None;
+        }
+        let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+            return None;
+        };
+        args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::AssocType(assoc) if assoc.ident == "Item" => Some(&assoc.ty),
+            _ => None,
+        })
+    })
 }
 
 /// Transforms a user-defined async function into a streaming Vercel handler
@@ -30,8 +82,21 @@ pub(crate) fn build_stream_handler(
     let HandlerAttrs {
         cache_config: _,
         init_fn,
+        guard_fn,
         timeout_secs,
+        heartbeat_secs,
+        stream_format,
+        stream_get,
         idempotent: _,
+        cors,
+        validate: _,
+        router,
+        testable,
+        max_body,
+        max_depth,
+        rate_limit,
+        compress,
+        etag: _,
     } = attrs;
 
     if func.sig.asyncness.is_none() {
@@ -41,17 +106,11 @@ pub(crate) fn build_stream_handler(
         ));
     }
 
-    if !matches!(func.sig.output, ReturnType::Default) {
-        return Err(syn::Error::new_spanned(
-            &func.sig.output,
-            "RPC stream handlers must not have a return type (use tx.send() instead)",
-        ));
-    }
-
     let fn_name = &func.sig.ident;
+    let fn_output = &func.sig.output;
     let fn_block = &func.block;
 
-    // Separate typed parameters into input, headers, state, and stream sender.
+    // Separate typed parameters into input, headers, state, guard, extractors, and stream sender.
     let typed_params: Vec<_> = func
         .sig
         .inputs
@@ -62,17 +121,32 @@ pub(crate) fn build_stream_handler(
         })
         .collect();
 
+    let format = stream_format.unwrap_or_default();
+    let stream_get = stream_get.unwrap_or(false);
+    let sender = sender_name(format);
+
     let mut input_param = None;
     let mut headers_param = None;
     let mut state_param = None;
     let mut sender_param = None;
+    let mut guard_param = None;
 
     for param in &typed_params {
-        if is_stream_sender_type(&param.ty) {
+        if is_extractor_param(param) {
+            // Extracted via `metaxy::FromRequest`; any number is allowed.
+        } else if is_guarded_type(&param.ty) {
+            if guard_param.is_some() {
+                return Err(syn::Error::new_spanned(
+                    &func.sig.inputs,
+                    "RPC handlers accept at most one Guarded parameter",
+                ));
+            }
+            guard_param = Some(*param);
+        } else if is_stream_sender_type(&param.ty) {
             if sender_param.is_some() {
                 return Err(syn::Error::new_spanned(
                     &func.sig.inputs,
-                    "RPC stream handlers accept at most one StreamSender parameter",
+                    format!("RPC stream handlers accept at most one {sender} parameter"),
                 ));
             }
             sender_param = Some(*param);
@@ -108,17 +182,68 @@ pub(crate) fn build_stream_handler(
         }
     }
 
-    if sender_param.is_none() {
+    // `impl Stream<Item = T>` handlers are drained into the sender by the generated code.
+    let stream_item = stream_item_type(&func.sig.output);
+    match (stream_item, sender_param) {
+        (None, None) => {
+            return Err(syn::Error::new_spanned(
+                &func.sig.inputs,
+                format!(
+                    "RPC stream handlers must accept a {sender} parameter or return impl Stream"
+                ),
+            ));
+        }
+        (Some(_), Some(param)) => {
+            return Err(syn::Error::new_spanned(
+                &param.ty,
+                format!(
+                    "RPC stream handlers returning impl Stream cannot take a {sender} parameter"
+                ),
+            ));
+        }
+        (None, Some(param)) if sender_format(&param.ty) != Some(format) => {
+            let expected = match format {
+                StreamFormat::Sse => "SSE streams",
+                StreamFormat::Ndjson => "`format = \"ndjson\"` streams",
+                StreamFormat::Bytes => "`format = \"bytes\"` streams",
+            };
+            return Err(syn::Error::new_spanned(
+                &param.ty,
+                format!("{expected} must use {sender}"),
+            ));
+        }
+        _ => {}
+    }
+
+    // NDJSON and byte streams carry nothing but chunks.
+    if format != StreamFormat::Sse {
+        if heartbeat_secs.is_some() {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "heartbeat is only valid on SSE streams",
+            ));
+        }
+        if stream_item.is_none()
+            && let ReturnType::Type(_, ty) = &func.sig.output
+        {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "only SSE streams can return a value",
+            ));
+        }
+    }
+
+    if state_param.is_some() && init_fn.is_none() {
         return Err(syn::Error::new_spanned(
             &func.sig.inputs,
-            "RPC stream handlers must accept a StreamSender parameter",
+            "state parameter requires init = \"...\" attribute",
         ));
     }
 
-    if state_param.is_some() && init_fn.is_none() {
+    if guard_param.is_some() && guard_fn.is_none() {
         return Err(syn::Error::new_spanned(
             &func.sig.inputs,
-            "state parameter requires init = \"...\" attribute",
+            "Guarded parameter requires guard = \"...\" attribute",
         ));
     }
 
@@ -141,8 +266,14 @@ pub(crate) fn build_stream_handler(
 
     let call_args: Vec<_> = typed_params
         .iter()
-        .map(|param| {
-            if is_stream_sender_type(&param.ty) {
+        .enumerate()
+        .map(|(i, param)| {
+            if is_extractor_param(param) {
+                let ident = extractor_ident(i);
+                quote! { #ident }
+            } else if is_guarded_type(&param.ty) {
+                quote! { ::metaxy::Guarded(__guard_ctx) }
+            } else if is_stream_sender_type(&param.ty) {
                 quote! { __tx }
             } else if is_headers_type(&param.ty) {
                 quote! { __headers }
@@ -191,6 +322,16 @@ pub(crate) fn build_stream_handler(
         quote! {}
     };
 
+    // Router and testable handlers are served through a `Procedure`, which
+    // runs `init` once on the first request (or ahead of it from `main`).
+    let in_module = router || testable;
+    let lazy_init = build_lazy_init(init_fn.as_deref(), state_param, &func.sig)?;
+    let (state_static, extract_state) = if in_module {
+        (lazy_init.statics.clone(), lazy_init.per_request.clone())
+    } else {
+        (state_static, extract_state)
+    };
+
     // Determine how to extract input and headers from the axum handler.
     let handler_params;
     let extract_input;
@@ -199,20 +340,72 @@ pub(crate) fn build_stream_handler(
     let has_input = input_param.is_some();
     let has_headers = headers_param.is_some();
 
+    // Read the body through `__RPC_LIMITS` and deserialize it like a mutation body,
+    // or, for `method = "GET"`, `?input=` / `?input64=` like a query.
+    let read_input = if stream_get {
+        quote! {
+            let __input: #input_type = {
+                let __raw = match ::metaxy::__private::read_query_input(__req, &__RPC_LIMITS).await {
+                    Ok(__raw) => __raw,
+                    Err(__e) => return __rpc_json_response(__e.status(), __e.to_json()),
+                };
+                let __parsed = match __raw {
+                    Some(ref __s) => ::metaxy::__private::serde_json::from_slice(__s),
+                    None => ::metaxy::__private::serde_json::from_value(::metaxy::__private::serde_json::Value::Null),
+                };
+                match __parsed {
+                    Ok(v) => v,
+                    Err(e) => return __rpc_json_response(400, ::metaxy::__private::serde_json::json!({
+                        "error": ::metaxy::__private::error_object(
+                            "BAD_INPUT",
+                            &format!("Failed to deserialize input: {}", e),
+                        )
+                    })),
+                }
+            };
+        }
+    } else {
+        quote! {
+        let __input: #input_type = {
+            let __bytes = match __RPC_LIMITS.read_body(__body).await {
+                Ok(__bytes) => __bytes,
+                Err(__e) => return __rpc_json_response(__e.status(), __e.to_json()),
+            };
+            let __parsed = if __bytes.is_empty() {
+                ::metaxy::__private::serde_json::from_value(::metaxy::__private::serde_json::Value::Null)
+            } else {
+                ::metaxy::__private::serde_json::from_slice(&__bytes)
+            };
+            match __parsed {
+                Ok(v) => v,
+                Err(e) => return __rpc_json_response(400, ::metaxy::__private::serde_json::json!({
+                    "error": ::metaxy::__private::error_object(
+                        "BAD_INPUT",
+                        &format!("Failed to deserialize request body: {}", e),
+                    )
+                })),
+            }
+        };
+        }
+    };
+    let input_handler_param = if stream_get {
+        quote! { __req: ::metaxy::__private::axum::extract::Request, }
+    } else {
+        quote! { __body: ::metaxy::__private::axum::body::Body, }
+    };
+
     match (has_input, has_headers) {
         (true, true) => {
             handler_params = quote! {
                 __header_map: ::metaxy::__private::axum::http::HeaderMap,
-                ::metaxy::__private::axum::Json(__input): ::metaxy::__private::axum::Json<#input_type>,
+                #input_handler_param
             };
-            extract_input = quote! {};
+            extract_input = read_input;
             extract_headers = quote! { let __headers = __header_map; };
         }
         (true, false) => {
-            handler_params = quote! {
-                ::metaxy::__private::axum::Json(__input): ::metaxy::__private::axum::Json<#input_type>,
-            };
-            extract_input = quote! {};
+            handler_params = input_handler_param;
+            extract_input = read_input;
             extract_headers = quote! {};
         }
         (false, true) => {
@@ -229,6 +422,74 @@ pub(crate) fn build_stream_handler(
         }
     }
 
+    let input_items = if has_input {
+        let limits_const = build_limits_const(max_body, max_depth);
+        quote! {
+            #limits_const
+
+            // Builds a JSON response for an input rejected before the stream starts.
+            fn __rpc_json_response(
+                status: u16,
+                body: ::metaxy::__private::serde_json::Value,
+            ) -> ::metaxy::__private::axum::response::Response {
+                let status = ::metaxy::__private::http::StatusCode::from_u16(status)
+                    .unwrap_or(::metaxy::__private::http::StatusCode::BAD_REQUEST);
+                ::metaxy::__private::axum::response::IntoResponse::into_response((
+                    status,
+                    ::metaxy::__private::axum::Json(body),
+                ))
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let cors_const = build_cors_const(cors.as_ref(), &func.sig)?;
+
+    // Run the guard and extractors before the stream starts; `Err` becomes a plain JSON error response.
+    let extract_request = build_request_extraction(
+        guard_fn.as_deref(),
+        state_param.is_some(),
+        &typed_params,
+        &func.sig,
+        &quote! {
+            let mut __error = ::metaxy::__private::error_object("HANDLER_ERROR", &format!("{}", __err));
+            if let Some(__data) = __data {
+                __error["data"] = __data;
+            }
+            let __status = ::metaxy::__private::http::StatusCode::from_u16(__status)
+                .unwrap_or(::metaxy::__private::http::StatusCode::BAD_REQUEST);
+            return ::metaxy::__private::axum::response::IntoResponse::into_response((
+                __status,
+                ::metaxy::__private::axum::Json(
+                    ::metaxy::__private::serde_json::json!({ "error": __error }),
+                ),
+            ));
+        },
+    )?;
+    let rate_limit_reads_headers = rate_limit
+        .as_ref()
+        .is_some_and(|config| config.key != RateLimitKey::Guard);
+    let rate_limit = build_rate_limit(
+        rate_limit.as_ref(),
+        &func.sig,
+        &quote! { &__parts.headers },
+        &quote! {
+            return ::metaxy::__private::into_axum_response(
+                Ok(__rate_limited),
+                ::metaxy::__private::Format::Json,
+            );
+        },
+    )?;
+    let rate_limit_statics = &rate_limit.statics;
+    let rate_limit_before_guard = &rate_limit.before_guard;
+    let rate_limit_after_guard = &rate_limit.after_guard;
+    let rate_limit_expose = &rate_limit.expose;
+    let parts_handler_param = if extract_request.is_empty() && !rate_limit_reads_headers {
+        quote! {}
+    } else {
+        quote! { __parts: ::metaxy::__private::axum::http::request::Parts, }
+    };
+
     let timeout_wrapper = if let Some(secs) = timeout_secs {
         quote! {
             let __timeout_dur = ::std::time::Duration::from_secs(#secs);
@@ -238,70 +499,282 @@ pub(crate) fn build_stream_handler(
         quote! {}
     };
 
-    let create_sender = if timeout_secs.is_some() {
-        quote! {
-            let __timeout_tx = __raw_tx.clone();
-            let __tx = ::metaxy::StreamSender::new(__raw_tx);
-        }
+    // Automatic event ids continue after the `Last-Event-ID` of a reconnecting client.
+    // `__end` sends heartbeats while the handler runs and its outcome after it.
+    let heartbeat = match heartbeat_secs {
+        Some(secs) => quote! {
+            let __heartbeat = __end.heartbeat(::std::time::Duration::from_secs(#secs));
+        },
+        None => quote! {},
+    };
+    let new_sender = match format {
+        StreamFormat::Sse => quote! {
+            ::metaxy::StreamSender::new(__raw_tx).resume_after(&__last_event_id)
+        },
+        StreamFormat::Ndjson => quote! { ::metaxy::NdjsonSender::new(__raw_tx) },
+        StreamFormat::Bytes => quote! { ::metaxy::ByteSender::new(__raw_tx) },
+    };
+    let create_sender = quote! {
+        let __end = ::metaxy::__private::StreamEnd::new(&__raw_tx);
+        #heartbeat
+        let __tx = #new_sender;
+    };
+
+    // Without events to carry them, errors of NDJSON and byte streams abort the body.
+    let probe_error = probe_error();
+    let (fail_err, fail_timeout, fail_panic) = match format {
+        StreamFormat::Sse => (
+            quote! {
+                #probe_error
+                __end.error(__status, "HANDLER_ERROR", &format!("{}", __err), __data).await;
+            },
+            quote! { __end.error(504, "TIMEOUT", "Handler timed out", None).await },
+            quote! { __end.error(500, "INTERNAL", &__panic, None).await },
+        ),
+        StreamFormat::Ndjson | StreamFormat::Bytes => (
+            quote! { __end.abort(&format!("{}", __err)).await; },
+            quote! { __end.abort("Handler timed out").await },
+            quote! { __end.abort(&__panic).await },
+        ),
+    };
+
+    // A returned value ends the stream with a `done` event, an `Err` with an `error` event.
+    // GET SSE streams always end with `done`, otherwise `EventSource` would reconnect.
+    let complete_unit = if stream_get && format == StreamFormat::Sse {
+        quote! { let () = __result; __end.done(&()).await; }
     } else {
-        quote! {
-            let __tx = ::metaxy::StreamSender::new(__raw_tx);
+        quote! { let () = __result; }
+    };
+    let complete = match &func.sig.output {
+        _ if stream_item.is_some() => complete_unit,
+        ReturnType::Default => complete_unit,
+        ReturnType::Type(_, ty) if is_result_type(ty) => quote! {
+            match __result {
+                Ok(__val) => __end.done(&__val).await,
+                Err(__err) => {
+                    #fail_err
+                }
+            }
+        },
+        ReturnType::Type(..) => quote! { __end.done(&__result).await; },
+    };
+
+    // A returned stream is polled only once the previous item has been sent,
+    // so a slow client holds back the producer. An `Err` item ends the stream.
+    let call = match stream_item {
+        Some(item) => {
+            let send_item = if is_result_type(item) {
+                quote! {
+                    match __item {
+                        Ok(__chunk) => {
+                            if __tx.send(__chunk).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(__err) => {
+                            #fail_err
+                            break;
+                        }
+                    }
+                }
+            } else {
+                quote! {
+                    if __tx.send(__item).await.is_err() {
+                        break;
+                    }
+                }
+            };
+            quote! {
+                async {
+                    let __stream = #fn_name(#(#call_args),*).await;
+                    let mut __stream = ::std::pin::pin!(__stream);
+                    while let Some(__item) = ::metaxy::__private::next_item(__stream.as_mut()).await {
+                        #send_item
+                    }
+                }
+            }
         }
+        None => quote! { #fn_name(#(#call_args),*) },
     };
+    // A panic in the handler or the returned stream ends the stream like an `Err`.
+    let procedure_name = fn_name.to_string();
+    let call = quote! { ::metaxy::__private::catch_panic(#procedure_name, #call) };
 
     let invoke_user_fn = if timeout_secs.is_some() {
         quote! {
             match ::metaxy::__private::tokio::time::timeout_at(
                 __deadline,
-                #fn_name(#(#call_args),*),
+                #call,
             ).await {
-                Ok(()) => {}
-                Err(_) => {
-                    let _ = __timeout_tx.send(Ok(
-                        ::metaxy::__private::hyper::body::Bytes::from("event: error\ndata: \"Handler timed out\"\n\n")
-                    )).await;
-                }
+                Ok(Ok(__result)) => { #complete }
+                Ok(Err(__panic)) => #fail_panic,
+                Err(_) => #fail_timeout,
             }
         }
     } else {
-        quote! { #fn_name(#(#call_args),*).await; }
+        quote! {
+            match #call.await {
+                Ok(__result) => { #complete }
+                Err(__panic) => #fail_panic,
+            }
+        }
     };
 
-    let expanded = quote! {
-        #state_static
+    // `stream_response` always answers `text/event-stream`.
+    let content_type = match format {
+        StreamFormat::Sse => None,
+        StreamFormat::Ndjson => Some("application/x-ndjson"),
+        StreamFormat::Bytes => Some("application/octet-stream"),
+    };
+    let set_content_type = content_type.map(|content_type| {
+        quote! {
+            __res.headers_mut().insert(
+                ::metaxy::__private::http::header::CONTENT_TYPE,
+                ::metaxy::__private::http::HeaderValue::from_static(#content_type),
+            );
+        }
+    });
 
-        #[::metaxy::__private::tokio::main]
-        async fn main() -> Result<(), ::metaxy::__private::vercel_runtime::Error> {
-            #init_call
+    let compress = compress.unwrap_or(true);
 
-            let __router = ::metaxy::__private::axum::Router::new()
-                .fallback(::metaxy::__private::axum::routing::post(__rpc_stream_handler));
+    let (route, expected_method) = if stream_get {
+        (quote! { get }, "GET")
+    } else {
+        (quote! { post }, "POST")
+    };
+    // Other methods get the same JSON `405` as queries and mutations.
+    let method_router = quote! {
+        ::metaxy::__private::axum::routing::#route(__rpc_stream_handler)
+            .options(|| async { ::metaxy::__private::http::StatusCode::NO_CONTENT })
+            .fallback(|__method: ::metaxy::__private::http::Method| async move {
+                ::metaxy::__private::method_not_allowed(&__method, #expected_method)
+            })
+    };
 
-            let __app = ::metaxy::__private::tower::ServiceBuilder::new()
-                .layer(::metaxy::__private::vercel_runtime::axum::VercelLayer::new())
-                .service(__router);
+    let handler_items = quote! {
+        #cors_const
+
+        #input_items
+
+        #rate_limit_statics
+
+        // Applies the CORS policy to every response, including preflight and rejections.
+        async fn __rpc_cors(
+            __req: ::metaxy::__private::axum::extract::Request,
+            __next: ::metaxy::__private::axum::middleware::Next,
+        ) -> ::metaxy::__private::axum::response::Response {
+            let __origin = __req
+                .headers()
+                .get(::metaxy::__private::http::header::ORIGIN)
+                .cloned();
+            let mut __res = __next.run(__req).await;
+            __RPC_CORS.apply(__origin.as_ref(), __res.headers_mut());
+            #rate_limit_expose
+            __res
+        }
 
-            ::metaxy::__private::vercel_runtime::run(__app).await
+        // Compresses the event stream and JSON rejections when the client accepts it.
+        async fn __rpc_compress(
+            __req: ::metaxy::__private::axum::extract::Request,
+            __next: ::metaxy::__private::axum::middleware::Next,
+        ) -> ::metaxy::__private::axum::response::Response {
+            ::metaxy::__private::compress(#compress, __req, __next).await
+        }
+
+        // Serves every request, including rejections, inside a request span.
+        async fn __rpc_trace(
+            __req: ::metaxy::__private::axum::extract::Request,
+            __next: ::metaxy::__private::axum::middleware::Next,
+        ) -> ::metaxy::__private::axum::response::Response {
+            ::metaxy::__private::trace_stream(#procedure_name, __req, __next).await
         }
 
         async fn __rpc_stream_handler(
+            __last_event_id: ::metaxy::LastEventId,
+            #parts_handler_param
             #handler_params
-        ) -> impl ::metaxy::__private::axum::response::IntoResponse {
-            #extract_input
+        ) -> ::metaxy::__private::axum::response::Response {
             #extract_headers
             #extract_state
+            #rate_limit_before_guard
+            #extract_request
+            #rate_limit_after_guard
+            #extract_input
             #timeout_wrapper
 
-            ::metaxy::__private::vercel_runtime::axum::stream_response(move |__raw_tx| async move {
-                #create_sender
+            let __span = ::metaxy::__private::CurrentSpan::get();
+            let mut __res = ::metaxy::__private::axum::response::IntoResponse::into_response(
+                ::metaxy::__private::vercel_runtime::axum::stream_response(move |__raw_tx| __span.instrument(async move {
+                    #create_sender
 
-                async fn #fn_name(#(#inner_fn_params),*)
-                #fn_block
+                    async fn #fn_name(#(#inner_fn_params),*) #fn_output
+                    #fn_block
 
-                #invoke_user_fn
-            })
+                    #invoke_user_fn
+                }))
+            );
+            #set_content_type
+            __res
         }
     };
 
-    Ok(expanded)
+    if in_module {
+        let route = quote! {
+            #method_router
+                .layer(::metaxy::__private::axum::middleware::from_fn(__rpc_cors))
+                .layer(::metaxy::__private::axum::middleware::from_fn(__rpc_compress))
+                .layer(::metaxy::__private::axum::middleware::from_fn(__rpc_trace))
+        };
+        let lazy_items = &lazy_init.items;
+        let module = build_router_module(
+            &func,
+            &quote! {
+                #state_static
+                #lazy_items
+                #handler_items
+            },
+            &quote! { Stream },
+            &route,
+        );
+        if router {
+            return Ok(module);
+        }
+
+        let run_init = init_fn
+            .as_ref()
+            .map(|_| quote! { #fn_name::__rpc_init().await; });
+        return Ok(quote! {
+            #module
+
+            #[cfg(not(test))]
+            #[::metaxy::__private::tokio::main]
+            async fn main() -> Result<(), ::metaxy::__private::vercel_runtime::Error> {
+                #run_init
+                ::metaxy::__private::serve_procedure(#fn_name::__rpc_procedure()).await
+            }
+        });
+    }
+
+    Ok(quote! {
+        #state_static
+
+        #[::metaxy::__private::tokio::main]
+        async fn main() -> Result<(), ::metaxy::__private::vercel_runtime::Error> {
+            #init_call
+
+            let __router = ::metaxy::__private::axum::Router::new()
+                .fallback(#method_router)
+                .layer(::metaxy::__private::axum::middleware::from_fn(__rpc_cors))
+                .layer(::metaxy::__private::axum::middleware::from_fn(__rpc_compress))
+                .layer(::metaxy::__private::axum::middleware::from_fn(__rpc_trace));
+
+            let __app = ::metaxy::__private::tower::ServiceBuilder::new()
+                .layer(::metaxy::__private::vercel_runtime::axum::VercelLayer::new())
+                .service(__router);
+
+            ::metaxy::__private::run(__app).await
+        }
+
+        #handler_items
+    })
 }
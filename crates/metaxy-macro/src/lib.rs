@@ -49,19 +49,62 @@
 //! { "result": { "type": "response", "data": <value> } }
 //! ```
 //!
-//! **Error** (HTTP 400):
+//! **Error** (HTTP 400 by default):
 //! ```json
-//! { "error": { "type": "error", "message": "<description>" } }
+//! { "error": { "type": "error", "code": "HANDLER_ERROR", "message": "<description>", "data": <error> } }
 //! ```
 //!
+//! `message` is the `Display` output of the error. When the error type
+//! implements `Serialize`, it is also serialized into `data` so the client can
+//! match on a typed error union. Errors that are only `Display` omit `data`.
+//!
+//! The status code is `400` unless the error type implements
+//! `metaxy::RpcErrorStatus`, which lets each error pick its own code
+//! (e.g. `404` for "not found", `500` for internal failures).
+//!
+//! `code` tells failures apart without matching on `message`, and is the same
+//! in stream `error` events and batch items:
+//!
+//! | `code` | Status | Cause |
+//! |---|---|---|
+//! | `BAD_INPUT` | 400 | Input is missing, malformed or cannot be deserialized |
+//! | `INPUT_TOO_COMPLEX` | 400 | Input nests deeper than `max_depth` |
+//! | `PAYLOAD_TOO_LARGE` | 413 | Input is larger than `max_body` |
+//! | `UNSUPPORTED_MEDIA_TYPE` | 415 | Body format not enabled in this build |
+//! | `METHOD_NOT_ALLOWED` | 405 | Wrong HTTP method for the procedure |
+//! | `VALIDATION_FAILED` | 422 | `validate` rejected the input |
+//! | `RATE_LIMITED` | 429 | `rate_limit` denied the request |
+//! | `FORBIDDEN` | 403 | A batched call's CORS policy rejected the origin |
+//! | `NOT_FOUND` | 404 | Unknown procedure in a batch or under `metaxy dev` |
+//! | `TIMEOUT` | 504 | The handler exceeded its `timeout` |
+//! | `HANDLER_ERROR` | any | An `Err` from the handler, its guard or an extractor |
+//! | `INTERNAL` | 500 | The handler panicked or its result failed to serialize |
+//!
+//! A panic in a handler body is caught and answered with a `500` error (an
+//! `error` event on streams), still carrying the CORS headers. The panic
+//! message is always logged but only sent as `message` in debug builds;
+//! release builds send `"Internal server error"`.
+//!
 //! # Supported Signatures
 //!
 //! | Signature | Input | Output |
 //! |-----------|-------|--------|
 //! | `async fn f() -> T` | none | `T` serialized |
 //! | `async fn f(input: I) -> T` | `I` deserialized | `T` serialized |
-//! | `async fn f() -> Result<T, E>` | none | `Ok` → 200, `Err` → 400 |
-//! | `async fn f(input: I) -> Result<T, E>` | `I` deserialized | `Ok` → 200, `Err` → 400 |
+//! | `async fn f() -> Result<T, E>` | none | `Ok` → 200, `Err` → `RpcErrorStatus` (400) |
+//! | `async fn f(input: I) -> Result<T, E>` | `I` deserialized | `Ok` → 200, `Err` → `RpcErrorStatus` (400) |
+//!
+//! `T` may be wrapped in `metaxy::RpcResponse<T>` to set the success status,
+//! add headers and cookies, or override `Cache-Control`. The body and the
+//! generated TypeScript type stay `T`:
+//!
+//! ```rust,ignore
+//! #[rpc_mutation]
+//! async fn login(input: Credentials) -> Result<RpcResponse<User>, AuthError> {
+//!     let (user, token) = authenticate(input).await?;
+//!     Ok(RpcResponse::new(user).cookie(format!("session={token}; HttpOnly; Path=/")))
+//! }
+//! ```
 //!
 //! ## Optional extra parameters
 //!
@@ -69,25 +112,177 @@
 //!
 //! - **`state: &T`** — shared state from an `init` function (requires `init = "fn_name"`).
 //! - **`headers: Headers`** — the incoming HTTP request headers.
+//! - **`ctx: Guarded<T>`** — the `Ok` value of the `guard` function (requires `guard = "fn_name"`).
+//! - **Request extractors** — any type implementing `metaxy::FromRequest`. The
+//!   built-in `Cookies`, `ClientIp`, `RequestInfo`, `VercelContext` and
+//!   `LastEventId` are recognised by name; custom extractors are marked
+//!   `#[from_request]`:
+//!
+//! ```rust,ignore
+//! #[rpc_query]
+//! async fn whoami(ip: ClientIp, cookies: Cookies, #[from_request] tenant: Tenant) -> String { /* ... */ }
+//! ```
+//!
+//! Extractors may appear any number of times. A rejection returns the same
+//! error response a handler `Err` would.
 //!
-//! These can be combined freely with an input parameter in any order:
+//! All of these can be combined freely with an input parameter in any order:
 //!
 //! ```rust,ignore
 //! #[rpc_query(init = "setup")]
 //! async fn get_user(id: u32, state: &AppState, headers: Headers) -> User { /* ... */ }
 //! ```
 //!
-//! More than one of each kind is a **compilation error**.
+//! More than one of each kind (other than extractors) is a **compilation error**.
 //!
 //! # CORS
 //!
-//! Every response includes the following headers:
+//! By default every response (queries, mutations and streams) includes:
 //!
 //! - `Access-Control-Allow-Origin: *`
 //! - `Access-Control-Allow-Methods: GET, POST, OPTIONS`
-//! - `Access-Control-Allow-Headers: Content-Type, Authorization`
+//! - `Access-Control-Allow-Headers: Content-Type, Authorization, traceparent, X-Request-Id, X-Metaxy-Input, Last-Event-ID`
 //! - `Access-Control-Max-Age: 86400`
 //!
+//! The `cors(...)` attribute replaces this policy for one handler:
+//!
+//! ```rust,ignore
+//! #[rpc_query(cors(
+//!     origins = "https://app.example.com, https://admin.example.com",
+//!     credentials,
+//!     headers = "X-Tenant",
+//!     expose = "X-Request-Id",
+//!     max_age = "10m",
+//! ))]
+//! async fn me(headers: Headers) -> User { /* ... */ }
+//! ```
+//!
+//! - `origins` — comma-separated allow-list; the matching request `Origin` is
+//!   reflected back. Defaults to `*`.
+//! - `credentials` — sends `Access-Control-Allow-Credentials: true`. Requires
+//!   an explicit `origins` list, so arbitrary sites cannot make cookie-authenticated
+//!   requests.
+//! - `headers` — request headers allowed in addition to the defaults above.
+//! - `expose` — response headers readable from client-side JavaScript.
+//! - `max_age` — preflight cache duration (`30s`, `5m`, `1h`, `1d`).
+//!
+//! To share one policy, declare a `const` of type `metaxy::Cors` and
+//! reference it with `cors = "crate::CORS"`.
+//!
+//! # Router Mode
+//!
+//! By default each handler expands to its own `fn main()`, so every procedure
+//! needs its own binary. The bare `router` flag instead emits a module named
+//! after the function whose procedure is registered with `metaxy::router!`,
+//! letting many handlers share one file and one binary:
+//!
+//! ```rust,ignore
+//! #[rpc_query(router)]
+//! async fn hello(name: String) -> String { /* ... */ }
+//!
+//! #[rpc_stream(router)]
+//! async fn ticks(tx: StreamSender<Tick>) { /* ... */ }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//!     metaxy::serve(metaxy::router![hello, ticks]).await
+//! }
+//! ```
+//!
+//! All other attributes keep working. `init` runs once, on the first request
+//! that reaches the handler, since there is no per-handler `main`.
+//!
+//! A router also serves `POST /api/_batch`, which runs several queries in
+//! one round trip. A client created with `batch: { maxSize, windowMs }`
+//! collects the queries made within `windowMs` (by default, the same tick)
+//! and sends them together. Deduplication and per-call `signal`s work as
+//! for single calls. Calls with per-call `headers` or `timeout`, and `etag`
+//! queries, are sent on their own.
+//!
+//! Batching needs router mode: standalone functions have no batch endpoint.
+//! `metaxy dev` forwards `/api/_batch` to the router binary when the project
+//! has exactly one; with several, run them behind `vercel dev` instead.
+//!
+//! # Testing
+//!
+//! The bare `testable` flag emits the same module as `router` while keeping
+//! the regular `main` outside of test builds, so a single-handler function can
+//! be called in-process with `metaxy::testing::TestClient`:
+//!
+//! ```rust,ignore
+//! #[rpc_query(testable, init = "setup")]
+//! async fn greet(name: String, state: &AppState) -> String { /* ... */ }
+//!
+//! #[tokio::test]
+//! async fn greets() {
+//!     let client = metaxy::test_client![greet = AppState::fake()];
+//!     assert_eq!(client.query("greet", &"Ada").await.data::<String>(), "Hi, Ada!");
+//! }
+//! ```
+//!
+//! `name = state` replaces the `init` result for that client only; without
+//! it `init` runs on the first request. Router-mode handlers are testable the
+//! same way.
+//!
+//! # Tracing
+//!
+//! With the `tracing` feature of `metaxy` enabled, every request runs in an
+//! `rpc` span with the procedure name and kind, the request id
+//! (`x-request-id`, else `x-vercel-id`) and the `trace_id`/`parent_id` of an
+//! incoming W3C `traceparent` header. The span records the response status and
+//! duration and logs one event per request: `info` on success, `warn` with the
+//! error message for `4xx` rejections such as malformed input, `error` for
+//! `5xx`. Events emitted by the handler, including while a stream is running,
+//! belong to the span. Install any `tracing` subscriber in `init` to collect
+//! them. `x-request-id` is echoed on the response with or without the feature.
+//!
+//! ```toml
+//! metaxy = { version = "0.1", features = ["tracing"] }
+//! ```
+//!
+//! The generated client sends both headers when created with
+//! `trace: true`, or with `trace: (procedure) => ({ traceparent, requestId })`
+//! to continue an existing trace.
+//!
+//! # Compression
+//!
+//! With the `compression` feature of `metaxy` enabled, responses are
+//! compressed with brotli or gzip, whichever the request's `Accept-Encoding`
+//! prefers (brotli on a tie). JSON responses smaller than 1 KiB are sent as
+//! is; streams are compressed as a whole, with every event flushed as soon
+//! as it is sent so clients still receive it immediately. Responses carry
+//! `Vary: Accept-Encoding` so caches keep one copy per encoding.
+//!
+//! ```toml
+//! metaxy = { version = "0.1", features = ["compression"] }
+//! ```
+//!
+//! `compress = false` opts a single handler out, e.g. for already-compressed
+//! payloads:
+//!
+//! ```rust,ignore
+//! #[rpc_query(compress = false)]
+//! async fn thumbnail(id: u32) -> String { /* base64 PNG */ }
+//! ```
+//!
+//! # Wire Formats
+//!
+//! With the `msgpack` or `cbor` feature of `metaxy` enabled, queries and
+//! mutations also speak MessagePack (`application/msgpack`) or CBOR
+//! (`application/cbor`). A request body is decoded according to its
+//! `Content-Type`, and the response is encoded in the format its `Accept`
+//! prefers, JSON when it names none. Responses carry `Vary: Accept`. Binary
+//! input is transcoded to JSON before `max_depth` is checked and the input
+//! deserialized, so it must be representable as JSON. Streams stay JSON.
+//!
+//! ```toml
+//! metaxy = { version = "0.1", features = ["msgpack"] }
+//! ```
+//!
+//! The generated client switches with `format: "msgpack"`. A binary body
+//! sent to a build without the feature is rejected with `415` and
+//! `error.code` set to `"UNSUPPORTED_MEDIA_TYPE"`.
+//!
 //! # Companion Crate
 //!
 //! [`metaxy-cli`](https://crates.io/crates/metaxy-cli) scans your
@@ -108,16 +303,20 @@ use codegen_stream::build_stream_handler;
 /// Generates a Vercel-compatible lambda handler from an async **query** function.
 ///
 /// The annotated function becomes a **GET** endpoint. Input is read from the
-/// `?input=<JSON>` query parameter, and the return value is serialized as JSON.
+/// `?input=<JSON>` query parameter (see [Large Inputs](#large-inputs) for the
+/// alternatives), and the return value is serialized as JSON.
 ///
 /// The macro generates:
 /// - A `main()` entry point with a single-threaded tokio runtime that calls `vercel_runtime::run`.
 /// - Automatic JSON deserialization of the `input` query parameter.
 /// - Automatic JSON serialization of the return value.
 /// - CORS headers on every response (including `OPTIONS` preflight → `204`).
-/// - HTTP method validation — only `GET` is accepted; other methods return `405`.
-/// - If the function returns `Result<T, E>`, `Err` is mapped to a `400` JSON
-///   error response automatically.
+/// - HTTP method validation — only `GET` (and a marked `POST`, see below) is
+///   accepted; other methods return `405`.
+/// - If the function returns `Result<T, E>`, `Err` is mapped to a JSON error
+///   response automatically — `400`, or the code chosen by
+///   `metaxy::RpcErrorStatus` when `E` implements it. When `E: Serialize`,
+///   the error value is included as `error.data`.
 ///
 /// # Examples
 ///
@@ -194,6 +393,22 @@ use codegen_stream::build_stream_handler;
 /// Duration shorthand: `30s`, `5m`, `1h`, `1d`. Error responses never receive
 /// cache headers. Mutations (`#[rpc_mutation]`) do not support caching.
 ///
+/// # Conditional Requests
+///
+/// The bare `etag` flag tags every successful response with a weak `ETag`
+/// hashed from its JSON, or keeps one the handler set via `RpcResponse::header`.
+/// A request whose `If-None-Match` matches gets an empty `304 Not Modified`.
+/// The handler still runs, but the body is not sent again:
+///
+/// ```rust,ignore
+/// #[rpc_query(etag)]
+/// async fn dashboard() -> Stats { /* ... */ }
+/// ```
+///
+/// The generated client remembers the last `ETag` and data of each query
+/// input, sends `If-None-Match` on the next call and resolves a `304` with
+/// the remembered data, so polling only downloads changed payloads.
+///
 /// # Initialization
 ///
 /// Use the `init` attribute to run an async function once at cold start.
@@ -218,6 +433,26 @@ use codegen_stream::build_stream_handler;
 /// The macro distinguishes state (`&T`) from input (`T`) by reference syntax.
 /// A `&T` parameter requires `init`; `&mut T` is rejected.
 ///
+/// # Guards
+///
+/// Use the `guard` attribute to run an async function on the request parts
+/// before the input is parsed. Its `Ok` value is injected through a
+/// `Guarded<T>` parameter; its `Err` short-circuits with the same error
+/// response a handler `Err` would produce (`RpcErrorStatus`, `error.data`):
+///
+/// ```rust,ignore
+/// async fn require_user(parts: &RequestParts) -> Result<User, AuthError> {
+///     let token = parts.headers.get("authorization").ok_or(AuthError::Missing)?;
+///     verify(token).await
+/// }
+///
+/// #[rpc_query(guard = "require_user")]
+/// async fn me(user: Guarded<User>) -> Profile { /* ... */ }
+/// ```
+///
+/// When the handler takes init state (`&T`), the guard receives it as a second
+/// argument: `async fn require_user(parts: &RequestParts, state: &T)`.
+///
 /// # Timeout
 ///
 /// Use the `timeout` attribute to enforce a per-procedure server-side timeout.
@@ -235,6 +470,98 @@ use codegen_stream::build_stream_handler;
 /// Duration shorthand: `30s`, `5m`, `1h`, `1d`. The timeout is also forwarded
 /// to the generated TypeScript client as a per-procedure default.
 ///
+/// # Validation
+///
+/// Use the bare `validate` flag to run `metaxy::Validate` on the deserialized
+/// input before the handler body. Failures return `422` with every violated
+/// field listed in `error.fields`; the handler is not called.
+///
+/// ```rust,ignore
+/// impl metaxy::Validate for SearchInput {
+///     fn validate(&self) -> Result<(), metaxy::ValidationErrors> {
+///         let mut errors = metaxy::ValidationErrors::new();
+///         if self.query.len() < 3 {
+///             errors.add("query", "must be at least 3 characters");
+///         }
+///         errors.into_result()
+///     }
+/// }
+///
+/// #[rpc_query(validate)]
+/// async fn search(input: SearchInput) -> Vec<Hit> { /* ... */ }
+/// ```
+///
+/// ```json
+/// { "error": { "type": "error", "code": "VALIDATION_FAILED",
+///              "message": "Validation failed: query: must be at least 3 characters",
+///              "fields": [{ "path": "query", "message": "must be at least 3 characters" }] } }
+/// ```
+///
+/// `validate` requires an input parameter. The generated TypeScript
+/// `RpcError` exposes the list as `fieldErrors`.
+///
+/// # Input Limits
+///
+/// Input is bounded before it is deserialized: at most `max_body` bytes
+/// (default `1mb`, i.e. `metaxy::DEFAULT_MAX_BODY`) and arrays/objects nested
+/// at most `max_depth` levels deep (default `64`). For queries the limits
+/// apply to the decoded input, for mutations and streams to the request body,
+/// which is never buffered past `max_body`.
+///
+/// ```rust,ignore
+/// #[rpc_query(max_body = "16kb", max_depth = "8")]
+/// async fn search(filter: Filter) -> Vec<Hit> { /* ... */ }
+/// ```
+///
+/// Size shorthand: `512b`, `64kb`, `1mb`, `1gb`. Oversized input returns
+/// `413` with `error.code` set to `"PAYLOAD_TOO_LARGE"`, too deeply nested
+/// input `400` with `"INPUT_TOO_COMPLEX"`; the TypeScript `RpcError` exposes
+/// it as `code`.
+///
+/// # Large Inputs
+///
+/// Input that is too long for a URL can be sent as the body of a `POST`
+/// carrying `X-Metaxy-Input: body`; the handler reads it exactly like
+/// `?input=`. The generated client switches to this once a query URL would
+/// pass `maxQueryLength` (default 2000 characters), and skips its ETag cache
+/// for such calls since nothing caches a `POST`.
+///
+/// `?input64=<base64url JSON>` is accepted as a shorter alternative to
+/// `?input=` that keeps the URL cacheable; the client sends it with
+/// `queryEncoding: "base64url"`. Invalid base64url returns `400` with
+/// `error.code` set to `"BAD_INPUT"`.
+///
+/// # Rate Limiting
+///
+/// `rate_limit = "10/m"` allows ten requests per minute per key, with bursts
+/// up to the limit. The window is a unit (`s`, `m`, `h`, `d`) or a duration
+/// (`"5/30s"`). `key` picks what is counted:
+///
+/// - `"ip"` (default) — the client IP, as read by `metaxy::ClientIp`.
+///   Requests without a client IP header are not limited; Vercel and
+///   `metaxy dev` always send one.
+/// - `"header:<name>"` — the value of a request header, e.g. an API key.
+///   Requests without it are rejected with `400` and `"BAD_INPUT"`.
+/// - `"guard"` — the guard's value through `metaxy::RateLimitKey`; checked
+///   after the guard, so unauthenticated requests are not counted.
+///
+/// ```rust,ignore
+/// #[rpc_mutation(rate_limit = "5/m", key = "ip")]
+/// async fn login(input: Credentials) -> Session { /* ... */ }
+/// ```
+///
+/// Limits are tracked in a per-instance `metaxy::MemoryStore` by default;
+/// `store = "path::to::STATIC"` names a `metaxy::RateLimitStore` shared across
+/// instances. Rejected requests return `429` with `Retry-After` and
+/// `error.code` set to `"RATE_LIMITED"`, and the generated client waits at
+/// least `Retry-After` before retrying them.
+///
+/// # Compression
+///
+/// With the `compression` feature, responses over 1 KiB are gzip or brotli
+/// compressed when the client accepts it (see the crate-level docs).
+/// `compress = false` opts this handler out.
+///
 /// # Idempotent
 ///
 /// The `idempotent` flag is **not** accepted on queries — queries are inherently
@@ -242,10 +569,11 @@ use codegen_stream::build_stream_handler;
 ///
 /// # Limitations
 ///
-/// - `Result` and `Headers` are detected by **name only** (last path segment).
-///   Type aliases like `type MyResult<T> = Result<T, MyError>` will not be
-///   recognized, and custom types named `Result` or `Headers` will be falsely
-///   matched. Use the canonical names directly.
+/// - `Result`, `Headers`, `Guarded` and the built-in extractors are detected by
+///   **name only** (last path segment). Type aliases like
+///   `type MyResult<T> = Result<T, MyError>` will not be recognized, and custom
+///   types with these names will be falsely matched. Use the canonical names
+///   directly.
 ///
 /// # Compile errors
 ///
@@ -271,6 +599,30 @@ pub fn rpc_query(attr: TokenStream, item: TokenStream) -> TokenStream {
         .to_compile_error()
         .into();
     }
+    if attrs.heartbeat_secs.is_some() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "heartbeat is only valid on rpc_stream",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if attrs.stream_format.is_some() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "format is only valid on rpc_stream",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if attrs.stream_get.is_some() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "method is only valid on rpc_stream",
+        )
+        .to_compile_error()
+        .into();
+    }
     let input_fn = parse_macro_input!(item as ItemFn);
     build_handler(input_fn, HandlerKind::Query, attrs)
         .map(Into::into)
@@ -288,8 +640,10 @@ pub fn rpc_query(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// - Automatic JSON serialization of the return value.
 /// - CORS headers on every response (including `OPTIONS` preflight → `204`).
 /// - HTTP method validation — only `POST` is accepted; other methods return `405`.
-/// - If the function returns `Result<T, E>`, `Err` is mapped to a `400` JSON
-///   error response automatically.
+/// - If the function returns `Result<T, E>`, `Err` is mapped to a JSON error
+///   response automatically — `400`, or the code chosen by
+///   `metaxy::RpcErrorStatus` when `E` implements it. When `E: Serialize`,
+///   the error value is included as `error.data`.
 ///
 /// # Examples
 ///
@@ -339,7 +693,9 @@ pub fn rpc_query(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// # Initialization
 ///
-/// Mutations support the `init` and `timeout` attributes (but not `cache`):
+/// Mutations support the `init`, `guard`, `timeout`, `cors`, `validate`,
+/// `max_body`, `max_depth`, `rate_limit` and `compress` attributes (but not
+/// `cache` or `etag`):
 ///
 /// ```rust,ignore
 /// #[rpc_mutation(init = "setup")]
@@ -390,6 +746,38 @@ pub fn rpc_mutation(attr: TokenStream, item: TokenStream) -> TokenStream {
         .to_compile_error()
         .into();
     }
+    if attrs.etag {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "rpc_mutation does not support the etag attribute",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if attrs.heartbeat_secs.is_some() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "heartbeat is only valid on rpc_stream",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if attrs.stream_format.is_some() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "format is only valid on rpc_stream",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if attrs.stream_get.is_some() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "method is only valid on rpc_stream",
+        )
+        .to_compile_error()
+        .into();
+    }
     let input_fn = parse_macro_input!(item as ItemFn);
     build_handler(input_fn, HandlerKind::Mutation, attrs)
         .map(Into::into)
@@ -435,6 +823,24 @@ pub fn rpc_mutation(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
+/// **Returning a stream:**
+///
+/// Instead of taking a `StreamSender`, a handler may return any
+/// `impl Stream<Item = T>` (`metaxy::Stream`, re-exported from
+/// `futures-core`). Each item is sent as a chunk, and the next item is only
+/// polled once the previous one is written, so a slow client holds back the
+/// producer. With `Result<T, E>` items, the first `Err` ends the stream with
+/// an `error` event:
+///
+/// ```rust,ignore
+/// use metaxy::{rpc_stream, Stream};
+///
+/// #[rpc_stream]
+/// async fn rows(query: Query) -> impl Stream<Item = Result<Row, DbError>> {
+///     db().fetch(query)
+/// }
+/// ```
+///
 /// **With timeout:**
 ///
 /// ```rust,ignore
@@ -444,18 +850,121 @@ pub fn rpc_mutation(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
+/// **Event ids and resume:**
+///
+/// Events sent with `tx.send_event(Event::new(..))` carry an `id:` — the next
+/// number of an automatic sequence unless set with `.id(..)` — and optionally
+/// an `event:` name. When the connection drops, the generated client
+/// reconnects with the last id as `Last-Event-ID`; the sequence continues
+/// after it, and a `LastEventId` parameter lets the handler skip what the
+/// client already has:
+///
+/// ```rust,ignore
+/// use metaxy::{rpc_stream, Event, LastEventId, StreamSender};
+///
+/// #[rpc_stream]
+/// async fn feed(last: LastEventId, tx: StreamSender<Item>) {
+///     for item in items_after(last.as_u64().unwrap_or(0)) {
+///         tx.send_event(Event::new(item)).await.ok();
+///     }
+/// }
+/// ```
+///
+/// **Final result and typed errors:**
+///
+/// A handler may return a value. It ends the stream as a `done` event, which
+/// the generated client returns from the stream's generator (`result` in the
+/// framework wrappers). An `Err` of a `Result` return ends it with an `error`
+/// event carrying the same `error` object (and status) as a failed query, so
+/// the client throws an `RpcError` whose `payload` is the typed error:
+///
+/// ```rust,ignore
+/// #[rpc_stream]
+/// async fn import(rows: Vec<Row>, tx: StreamSender<Progress>) -> Result<Summary, ImportError> {
+///     for (i, row) in rows.iter().enumerate() {
+///         store(row).await?;
+///         tx.send(Progress { done: i + 1 }).await.ok();
+///     }
+///     Ok(Summary { imported: rows.len() })
+/// }
+/// ```
+///
+/// **NDJSON and raw bytes:**
+///
+/// `format = "ndjson"` streams one JSON value per line
+/// (`application/x-ndjson`) through an `NdjsonSender<T>`; `format = "bytes"`
+/// streams raw chunks (`application/octet-stream`) through a `ByteSender`,
+/// e.g. for CSV downloads. The generated client yields parsed values or
+/// `Uint8Array` chunks. Neither format has events, so handlers cannot return
+/// a value, `heartbeat` is not available, and an `Err` item or timeout aborts
+/// the response body:
+///
+/// ```rust,ignore
+/// use metaxy::{rpc_stream, ByteSender};
+///
+/// #[rpc_stream(format = "bytes")]
+/// async fn report(month: String, tx: ByteSender) {
+///     tx.send("date,total\n").await.ok();
+///     for row in rows(&month).await {
+///         tx.send(format!("{},{}\n", row.date, row.total)).await.ok();
+///     }
+/// }
+/// ```
+///
+/// **GET streams and `EventSource`:**
+///
+/// `method = "GET"` serves the stream over `GET` and reads the input from
+/// `?input=` (or `?input64=`) exactly like a query, so the stream can be
+/// opened with a plain URL. The generated client can then use the browser's
+/// `EventSource` instead of `fetch` (`eventSource: true`), which reconnects
+/// automatically and resends `Last-Event-ID`. A GET SSE stream always ends
+/// with a `done` event, so `EventSource` knows not to reconnect:
+///
+/// ```rust,ignore
+/// use metaxy::{rpc_stream, StreamSender};
+///
+/// #[rpc_stream(method = "GET")]
+/// async fn prices(symbol: String, tx: StreamSender<f64>) {
+///     while let Some(price) = next_price(&symbol).await {
+///         tx.send(price).await.ok();
+///     }
+/// }
+/// ```
+///
+/// **Heartbeats and disconnects:**
+///
+/// `heartbeat = "15s"` sends an SSE comment line at that interval while the
+/// handler runs, so proxies do not cut a quiet stream. `tx.closed()` resolves
+/// when the client disconnects, letting a handler stop work it would
+/// otherwise only notice on its next failed send.
+///
 /// # Supported attributes
 ///
 /// - `init = "fn_name"` — cold-start initialization, same as query/mutation.
-/// - `timeout = "30s"` — maximum stream duration.
-///
-/// `cache`, `stale`, and `idempotent` are **not** supported on streams.
+/// - `timeout = "30s"` — maximum stream duration; ends with a `504` `error` event.
+/// - `heartbeat = "15s"` — keepalive comment interval (SSE streams only).
+/// - `format = "ndjson" | "bytes"` — wire format; SSE by default.
+/// - `method = "GET"` — serve over `GET` with the input in `?input=`; `POST`
+///   by default.
+/// - `cors(...)` / `cors = "path"` — CORS policy, same as query/mutation.
+/// - `guard = "fn_name"` — runs before the stream opens; `Err` returns a JSON
+///   error response instead of a stream.
+/// - `max_body = "1mb"` / `max_depth = "64"` — input limits, same as
+///   query/mutation.
+/// - `rate_limit = "10/m"`, `key`, `store` — rate limiting, same as
+///   query/mutation; checked before the stream opens.
+/// - `compress = false` — never compress this stream; see the crate-level docs.
+/// - `router` / `testable` — see the crate-level docs.
+///
+/// `cache`, `stale`, `etag`, `idempotent`, and `validate` are **not** supported on streams.
 ///
 /// # Compile errors
 ///
-/// - Missing `StreamSender` parameter.
-/// - Return type present (streams must return `()`).
-/// - `cache`, `stale`, or `idempotent` attribute used.
+/// - Missing `StreamSender` parameter on a handler not returning `impl Stream`
+///   (`NdjsonSender` / `ByteSender` with `format = "ndjson"` / `"bytes"`).
+/// - Sender parameter on a handler returning `impl Stream`.
+/// - Return value or `heartbeat` on an NDJSON or byte stream.
+/// - `cache`, `stale`, `etag`, `idempotent`, or `validate` attribute used.
 #[proc_macro_attribute]
 pub fn rpc_stream(attr: TokenStream, item: TokenStream) -> TokenStream {
     let attrs = match parse_handler_attrs(attr) {
@@ -478,6 +987,22 @@ pub fn rpc_stream(attr: TokenStream, item: TokenStream) -> TokenStream {
         .to_compile_error()
         .into();
     }
+    if attrs.validate {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "rpc_stream does not support the validate attribute",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if attrs.etag {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "rpc_stream does not support the etag attribute",
+        )
+        .to_compile_error()
+        .into();
+    }
     let input_fn = parse_macro_input!(item as ItemFn);
     build_stream_handler(input_fn, attrs)
         .map(Into::into)
@@ -1,7 +1,10 @@
 use quote::quote;
 
 use super::helpers::parse_type;
-use crate::attrs::{build_cache_control, parse_duration, parse_handler_attrs_inner};
+use crate::attrs::{
+    CorsConfig, RateLimitKey, StreamFormat, build_cache_control, parse_duration,
+    parse_handler_attrs_inner, parse_rate, parse_size,
+};
 use crate::codegen::{is_ref_type, is_result_type};
 
 // --- is_result_type ---
@@ -85,6 +88,50 @@ fn duration_rejects_no_number() {
     assert!(parse_duration("h").unwrap_err().contains("invalid number"));
 }
 
+// --- parse_size ---
+
+#[test]
+fn size_units() {
+    assert_eq!(parse_size("512").unwrap(), 512);
+    assert_eq!(parse_size("64b").unwrap(), 64);
+    assert_eq!(parse_size("16kb").unwrap(), 16 * 1024);
+    assert_eq!(parse_size("1MB").unwrap(), 1024 * 1024);
+    assert_eq!(parse_size("2gb").unwrap(), 2 * 1024 * 1024 * 1024);
+}
+
+#[test]
+fn size_rejects_zero() {
+    assert!(parse_size("0kb").unwrap_err().contains("zero"));
+}
+
+#[test]
+fn size_rejects_unknown_unit() {
+    assert!(parse_size("1tb").unwrap_err().contains("invalid size"));
+}
+
+// --- parse_rate ---
+
+#[test]
+fn rate_with_bare_unit() {
+    assert_eq!(parse_rate("10/m").unwrap(), (10, 60));
+    assert_eq!(parse_rate("1000/d").unwrap(), (1000, 86400));
+}
+
+#[test]
+fn rate_with_duration_window() {
+    assert_eq!(parse_rate("5/30s").unwrap(), (5, 30));
+}
+
+#[test]
+fn rate_rejects_zero_requests() {
+    assert!(parse_rate("0/m").unwrap_err().contains("zero"));
+}
+
+#[test]
+fn rate_rejects_missing_window() {
+    assert!(parse_rate("10").unwrap_err().contains("invalid rate limit"));
+}
+
 // --- build_cache_control ---
 
 #[test]
@@ -221,6 +268,37 @@ fn parse_attrs_cache_and_init_both_parsed() {
     assert_eq!(result.init_fn.as_deref(), Some("setup"));
 }
 
+// --- parse_handler_attrs: guard ---
+
+#[test]
+fn parse_attrs_guard_only() {
+    let result = parse_handler_attrs_inner(quote! { guard = "auth::require_user" }).unwrap();
+    assert_eq!(result.guard_fn.as_deref(), Some("auth::require_user"));
+}
+
+#[test]
+fn parse_attrs_guard_with_init() {
+    let result =
+        parse_handler_attrs_inner(quote! { init = "setup", guard = "require_user" }).unwrap();
+    assert_eq!(result.init_fn.as_deref(), Some("setup"));
+    assert_eq!(result.guard_fn.as_deref(), Some("require_user"));
+}
+
+#[test]
+fn parse_attrs_guard_empty_rejected() {
+    let err = parse_handler_attrs_inner(quote! { guard = "" }).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("guard function path cannot be empty")
+    );
+}
+
+#[test]
+fn parse_attrs_duplicate_guard_rejected() {
+    let err = parse_handler_attrs_inner(quote! { guard = "a", guard = "b" }).unwrap_err();
+    assert!(err.to_string().contains("duplicate `guard`"));
+}
+
 // --- parse_handler_attrs: timeout ---
 
 #[test]
@@ -278,6 +356,85 @@ fn parse_attrs_timeout_empty_rejected() {
     assert!(err.to_string().contains("empty"));
 }
 
+// --- parse_handler_attrs: heartbeat ---
+
+#[test]
+fn parse_attrs_heartbeat() {
+    let result = parse_handler_attrs_inner(quote! { heartbeat = "15s", timeout = "5m" }).unwrap();
+    assert_eq!(result.heartbeat_secs, Some(15));
+    assert_eq!(result.timeout_secs, Some(300));
+}
+
+#[test]
+fn parse_attrs_duplicate_heartbeat_rejected() {
+    let err =
+        parse_handler_attrs_inner(quote! { heartbeat = "15s", heartbeat = "30s" }).unwrap_err();
+    assert!(err.to_string().contains("duplicate"));
+}
+
+#[test]
+fn parse_attrs_heartbeat_zero_rejected() {
+    let err = parse_handler_attrs_inner(quote! { heartbeat = "0s" }).unwrap_err();
+    assert!(err.to_string().contains("zero"));
+}
+
+// --- parse_handler_attrs: format ---
+
+#[test]
+fn parse_attrs_stream_format() {
+    let result = parse_handler_attrs_inner(quote! { format = "ndjson" }).unwrap();
+    assert_eq!(result.stream_format, Some(StreamFormat::Ndjson));
+    let result = parse_handler_attrs_inner(quote! { format = "bytes" }).unwrap();
+    assert_eq!(result.stream_format, Some(StreamFormat::Bytes));
+    let result = parse_handler_attrs_inner(quote! { format = "sse" }).unwrap();
+    assert_eq!(result.stream_format, Some(StreamFormat::Sse));
+}
+
+#[test]
+fn parse_attrs_stream_format_defaults_to_none() {
+    let result = parse_handler_attrs_inner(quote! { timeout = "5s" }).unwrap();
+    assert!(result.stream_format.is_none());
+}
+
+#[test]
+fn parse_attrs_unknown_stream_format_rejected() {
+    let err = parse_handler_attrs_inner(quote! { format = "csv" }).unwrap_err();
+    assert!(err.to_string().contains("invalid stream format `csv`"));
+}
+
+#[test]
+fn parse_attrs_duplicate_stream_format_rejected() {
+    let err =
+        parse_handler_attrs_inner(quote! { format = "ndjson", format = "bytes" }).unwrap_err();
+    assert!(err.to_string().contains("duplicate"));
+}
+
+// --- parse_handler_attrs: method ---
+
+#[test]
+fn parse_attrs_stream_method() {
+    let result = parse_handler_attrs_inner(quote! { method = "GET" }).unwrap();
+    assert_eq!(result.stream_get, Some(true));
+    let result = parse_handler_attrs_inner(quote! { method = "get" }).unwrap();
+    assert_eq!(result.stream_get, Some(true));
+    let result = parse_handler_attrs_inner(quote! { method = "POST" }).unwrap();
+    assert_eq!(result.stream_get, Some(false));
+    let result = parse_handler_attrs_inner(quote! {}).unwrap();
+    assert_eq!(result.stream_get, None);
+}
+
+#[test]
+fn parse_attrs_invalid_stream_method_rejected() {
+    let err = parse_handler_attrs_inner(quote! { method = "PUT" }).unwrap_err();
+    assert!(err.to_string().contains("invalid method `PUT`"));
+}
+
+#[test]
+fn parse_attrs_duplicate_stream_method_rejected() {
+    let err = parse_handler_attrs_inner(quote! { method = "GET", method = "GET" }).unwrap_err();
+    assert!(err.to_string().contains("duplicate `method`"));
+}
+
 // --- parse_handler_attrs: idempotent ---
 
 #[test]
@@ -338,3 +495,296 @@ fn parse_attrs_empty_has_idempotent_false() {
     let result = parse_handler_attrs_inner(quote! {}).unwrap();
     assert!(!result.idempotent);
 }
+
+// --- parse_handler_attrs: cors ---
+
+#[test]
+fn parse_attrs_cors_path() {
+    let result = parse_handler_attrs_inner(quote! { cors = "crate::CORS" }).unwrap();
+    assert!(matches!(result.cors, Some(CorsConfig::Path(p)) if p == "crate::CORS"));
+}
+
+#[test]
+fn parse_attrs_cors_inline() {
+    let result = parse_handler_attrs_inner(quote! {
+        cors(
+            origins = "https://a.com, https://b.com",
+            credentials,
+            headers = "X-Tenant",
+            expose = "X-Request-Id",
+            max_age = "10m"
+        )
+    })
+    .unwrap();
+    let Some(CorsConfig::Inline {
+        origins,
+        credentials,
+        allow_headers,
+        expose_headers,
+        max_age_secs,
+    }) = result.cors
+    else {
+        panic!("expected inline cors config");
+    };
+    assert_eq!(origins, ["https://a.com", "https://b.com"]);
+    assert!(credentials);
+    assert_eq!(allow_headers, ["X-Tenant"]);
+    assert_eq!(expose_headers, ["X-Request-Id"]);
+    assert_eq!(max_age_secs, Some(600));
+}
+
+#[test]
+fn parse_attrs_cors_inline_defaults_to_any_origin() {
+    let result = parse_handler_attrs_inner(quote! { cors(headers = "X-Tenant") }).unwrap();
+    let Some(CorsConfig::Inline { origins, .. }) = result.cors else {
+        panic!("expected inline cors config");
+    };
+    assert_eq!(origins, ["*"]);
+}
+
+#[test]
+fn parse_attrs_cors_credentials_require_origins() {
+    let err = parse_handler_attrs_inner(quote! { cors(credentials) }).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("requires an explicit `origins` list")
+    );
+    let err = parse_handler_attrs_inner(quote! { cors(origins = "*", credentials) }).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("requires an explicit `origins` list")
+    );
+}
+
+#[test]
+fn parse_attrs_cors_with_other_attrs() {
+    let result =
+        parse_handler_attrs_inner(quote! { cache = "1h", cors(origins = "https://a.com") })
+            .unwrap();
+    assert!(result.cache_config.is_some());
+    assert!(result.cors.is_some());
+}
+
+#[test]
+fn parse_attrs_duplicate_cors_rejected() {
+    let err =
+        parse_handler_attrs_inner(quote! { cors = "crate::CORS", cors(credentials) }).unwrap_err();
+    assert!(err.to_string().contains("duplicate `cors`"));
+}
+
+#[test]
+fn parse_attrs_cors_unknown_option_rejected() {
+    let err = parse_handler_attrs_inner(quote! { cors(methods = "GET") }).unwrap_err();
+    assert!(err.to_string().contains("unknown cors option"));
+}
+
+#[test]
+fn parse_attrs_cors_wildcard_mixed_rejected() {
+    let err = parse_handler_attrs_inner(quote! { cors(origins = "*, https://a.com") }).unwrap_err();
+    assert!(err.to_string().contains("cannot be combined"));
+}
+
+#[test]
+fn parse_attrs_cors_invalid_max_age_rejected() {
+    let err = parse_handler_attrs_inner(quote! { cors(max_age = "soon") }).unwrap_err();
+    assert!(err.to_string().contains("invalid duration"));
+}
+
+#[test]
+fn parse_attrs_other_list_rejected() {
+    let err = parse_handler_attrs_inner(quote! { cache(ttl = "1h") }).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("expected `key = \"value\"` or bare flag")
+    );
+}
+
+// --- parse_handler_attrs: validate ---
+
+#[test]
+fn parse_attrs_validate_flag() {
+    let result = parse_handler_attrs_inner(quote! { validate }).unwrap();
+    assert!(result.validate);
+}
+
+#[test]
+fn parse_attrs_validate_with_others() {
+    let result =
+        parse_handler_attrs_inner(quote! { validate, idempotent, timeout = "5s" }).unwrap();
+    assert!(result.validate);
+    assert!(result.idempotent);
+    assert_eq!(result.timeout_secs, Some(5));
+}
+
+#[test]
+fn parse_attrs_duplicate_validate() {
+    let err = parse_handler_attrs_inner(quote! { validate, validate }).unwrap_err();
+    assert!(err.to_string().contains("duplicate `validate`"));
+}
+
+#[test]
+fn parse_attrs_validate_rejects_value() {
+    let err = parse_handler_attrs_inner(quote! { validate = "yes" }).unwrap_err();
+    assert!(err.to_string().contains("bare flag"));
+}
+
+#[test]
+fn parse_attrs_empty_has_validate_false() {
+    let result = parse_handler_attrs_inner(quote! {}).unwrap();
+    assert!(!result.validate);
+}
+
+#[test]
+fn parse_attrs_router_flag() {
+    let result = parse_handler_attrs_inner(quote! { router, init = "setup" }).unwrap();
+    assert!(result.router);
+    assert_eq!(result.init_fn.as_deref(), Some("setup"));
+}
+
+#[test]
+fn parse_attrs_duplicate_router() {
+    let err = parse_handler_attrs_inner(quote! { router, router }).unwrap_err();
+    assert!(err.to_string().contains("duplicate `router`"));
+}
+
+#[test]
+fn parse_attrs_router_rejects_value() {
+    let err = parse_handler_attrs_inner(quote! { router = "yes" }).unwrap_err();
+    assert!(err.to_string().contains("bare flag"));
+}
+
+#[test]
+fn parse_attrs_testable_flag() {
+    let result = parse_handler_attrs_inner(quote! { testable, timeout = "5s" }).unwrap();
+    assert!(result.testable);
+    assert!(!result.router);
+}
+
+#[test]
+fn parse_attrs_duplicate_testable() {
+    let err = parse_handler_attrs_inner(quote! { testable, testable }).unwrap_err();
+    assert!(err.to_string().contains("duplicate `testable`"));
+}
+
+#[test]
+fn parse_attrs_testable_rejects_value() {
+    let err = parse_handler_attrs_inner(quote! { testable = "yes" }).unwrap_err();
+    assert!(err.to_string().contains("bare flag"));
+}
+
+// --- parse_handler_attrs: limits ---
+
+#[test]
+fn parse_attrs_limits() {
+    let result =
+        parse_handler_attrs_inner(quote! { max_body = "256kb", max_depth = "16" }).unwrap();
+    assert_eq!(result.max_body, Some(256 * 1024));
+    assert_eq!(result.max_depth, Some(16));
+}
+
+#[test]
+fn parse_attrs_limits_default_to_none() {
+    let result = parse_handler_attrs_inner(quote! { timeout = "5s" }).unwrap();
+    assert_eq!(result.max_body, None);
+    assert_eq!(result.max_depth, None);
+}
+
+#[test]
+fn parse_attrs_duplicate_max_body() {
+    let err = parse_handler_attrs_inner(quote! { max_body = "1mb", max_body = "2mb" }).unwrap_err();
+    assert!(err.to_string().contains("duplicate `max_body`"));
+}
+
+#[test]
+fn parse_attrs_invalid_max_body() {
+    let err = parse_handler_attrs_inner(quote! { max_body = "lots" }).unwrap_err();
+    assert!(err.to_string().contains("invalid size"));
+}
+
+#[test]
+fn parse_attrs_invalid_max_depth() {
+    let err = parse_handler_attrs_inner(quote! { max_depth = "0" }).unwrap_err();
+    assert!(err.to_string().contains("positive integer"));
+}
+
+#[test]
+fn parse_attrs_rate_limit_defaults_to_ip() {
+    let result = parse_handler_attrs_inner(quote! { rate_limit = "10/m" }).unwrap();
+    let config = result.rate_limit.unwrap();
+    assert_eq!((config.requests, config.window_secs), (10, 60));
+    assert_eq!(config.key, RateLimitKey::Ip);
+    assert_eq!(config.store, None);
+}
+
+#[test]
+fn parse_attrs_rate_limit_header_key_and_store() {
+    let result = parse_handler_attrs_inner(
+        quote! { rate_limit = "5/30s", key = "header:X-Api-Key", store = "crate::LIMITS" },
+    )
+    .unwrap();
+    let config = result.rate_limit.unwrap();
+    assert_eq!(config.key, RateLimitKey::Header("x-api-key".into()));
+    assert_eq!(config.store.as_deref(), Some("crate::LIMITS"));
+}
+
+#[test]
+fn parse_attrs_rate_limit_guard_key_requires_guard() {
+    let err = parse_handler_attrs_inner(quote! { rate_limit = "10/m", key = "guard" }).unwrap_err();
+    assert!(err.to_string().contains("requires a `guard` attribute"));
+
+    let result =
+        parse_handler_attrs_inner(quote! { guard = "auth", rate_limit = "10/m", key = "guard" })
+            .unwrap();
+    assert_eq!(result.rate_limit.unwrap().key, RateLimitKey::Guard);
+}
+
+#[test]
+fn parse_attrs_key_without_rate_limit() {
+    let err = parse_handler_attrs_inner(quote! { key = "ip" }).unwrap_err();
+    assert!(err.to_string().contains("require a `rate_limit` attribute"));
+}
+
+#[test]
+fn parse_attrs_invalid_rate_limit_key() {
+    let err = parse_handler_attrs_inner(quote! { rate_limit = "10/m", key = "user" }).unwrap_err();
+    assert!(err.to_string().contains("invalid rate limit key"));
+}
+
+#[test]
+fn parse_attrs_compress() {
+    let result = parse_handler_attrs_inner(quote! { compress = false }).unwrap();
+    assert_eq!(result.compress, Some(false));
+    let result = parse_handler_attrs_inner(quote! {}).unwrap();
+    assert_eq!(result.compress, None);
+}
+
+#[test]
+fn parse_attrs_compress_requires_bool() {
+    let err = parse_handler_attrs_inner(quote! { compress = "no" }).unwrap_err();
+    assert!(err.to_string().contains("expected `true` or `false`"));
+}
+
+#[test]
+fn parse_attrs_duplicate_compress() {
+    let err = parse_handler_attrs_inner(quote! { compress = false, compress = true }).unwrap_err();
+    assert!(err.to_string().contains("duplicate `compress`"));
+}
+
+#[test]
+fn parse_attrs_etag() {
+    let result = parse_handler_attrs_inner(quote! { etag, cache = "1m" }).unwrap();
+    assert!(result.etag);
+    assert!(result.cache_config.is_some());
+}
+
+#[test]
+fn parse_attrs_duplicate_etag() {
+    let err = parse_handler_attrs_inner(quote! { etag, etag }).unwrap_err();
+    assert!(err.to_string().contains("duplicate `etag`"));
+}
+
+#[test]
+fn parse_attrs_etag_with_value_rejected() {
+    let err = parse_handler_attrs_inner(quote! { etag = "strong" }).unwrap_err();
+    assert!(err.to_string().contains("bare flag"));
+}
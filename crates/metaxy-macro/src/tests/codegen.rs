@@ -1,5 +1,7 @@
 use super::helpers::{no_attrs, parse_fn};
-use crate::attrs::{CacheConfig, HandlerAttrs};
+use crate::attrs::{
+    CacheConfig, CorsConfig, HandlerAttrs, RateLimitConfig, RateLimitKey, StreamFormat,
+};
 use crate::codegen::{HandlerKind, build_handler};
 use crate::codegen_stream::build_stream_handler;
 use syn::ItemFn;
@@ -30,11 +32,529 @@ fn query_returns_result() {
     let func = parse_fn("async fn fetch(id: u32) -> Result<String, String> { Ok(\"ok\".into()) }");
     let tokens = build_handler(func, HandlerKind::Query, no_attrs()).unwrap();
     let code = tokens.to_string();
-    assert!(code.contains("__rpc_error_response (400"));
+    assert!(code.contains("__rpc_error_response_with_data (__status"));
     assert!(code.contains("Ok (__val)"));
     assert!(code.contains("Err (__err)"));
 }
 
+#[test]
+fn result_error_serialized_into_data() {
+    let func = parse_fn("async fn fetch(id: u32) -> Result<String, MyError> { Ok(\"ok\".into()) }");
+    let code = build_handler(func, HandlerKind::Query, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(code.contains("ErrorProbe (& __err)"));
+    assert!(code.contains("__rpc_error_data ()"));
+    assert!(code.contains("error [\"data\"] = data"));
+}
+
+#[test]
+fn result_error_status_from_trait() {
+    let func = parse_fn("async fn fetch(id: u32) -> Result<String, MyError> { Ok(\"ok\".into()) }");
+    let code = build_handler(func, HandlerKind::Query, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(code.contains("CustomErrorStatus as _"));
+    assert!(code.contains("DefaultErrorStatus as _"));
+    assert!(code.contains(
+        "let __status = (& :: metaxy :: __private :: ErrorProbe (& __err)) . __rpc_error_status ()"
+    ));
+}
+
+#[test]
+fn plain_return_has_no_error_probe() {
+    let func = parse_fn("async fn fetch(id: u32) -> String { String::new() }");
+    let code = build_handler(func, HandlerKind::Query, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(!code.contains("ErrorProbe"));
+}
+
+#[test]
+fn success_applies_response_meta() {
+    let func =
+        parse_fn("async fn login(id: u32) -> Result<RpcResponse<String>, String> { todo!() }");
+    let code = build_handler(func, HandlerKind::Mutation, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(code.contains("WithResponseMeta as _"));
+    assert!(code.contains("ResponseProbe (& __val)) . __rpc_response_meta ()"));
+    assert!(code.contains("__meta . apply (& mut __res)"));
+}
+
+#[test]
+fn router_mode_emits_module_instead_of_main() {
+    let func = parse_fn("pub async fn hello(name: String) -> String { name }");
+    let attrs = HandlerAttrs {
+        router: true,
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Query, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains("pub mod hello"));
+    assert!(code.contains("use super :: * ;"));
+    assert!(code.contains("pub fn __rpc_procedure () -> :: metaxy :: __private :: Procedure"));
+    assert!(code.contains("Procedure :: new (\"hello\""));
+    assert!(code.contains("__req : :: metaxy :: __private :: axum :: extract :: Request"));
+    assert!(!code.contains("fn main"));
+}
+
+#[test]
+fn router_mode_runs_init_lazily() {
+    let func = parse_fn("async fn hello(state: &AppState) -> String { String::new() }");
+    let attrs = HandlerAttrs {
+        router: true,
+        init_fn: Some("setup".into()),
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Query, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains(
+        "static __RPC_STATE : :: metaxy :: __private :: tokio :: sync :: OnceCell < AppState >"
+    ));
+    assert!(code.contains("__RPC_STATE . get_or_init (|| async { setup () . await }) . await"));
+    assert!(!code.contains("OnceLock"));
+}
+
+#[test]
+fn router_mode_init_without_state() {
+    let func = parse_fn("async fn hello() -> String { String::new() }");
+    let attrs = HandlerAttrs {
+        router: true,
+        init_fn: Some("setup".into()),
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Mutation, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains("__RPC_INIT . get_or_init (|| async { setup () . await ; }) . await"));
+}
+
+#[test]
+fn router_mode_injects_test_state() {
+    let func = parse_fn("async fn hello(state: &AppState) -> String { String::new() }");
+    let attrs = HandlerAttrs {
+        router: true,
+        init_fn: Some("setup".into()),
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Query, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains("static __RPC_TEST_STATE : & 'static AppState ;"));
+    assert!(code.contains("__RPC_TEST_STATE . try_with"));
+    assert!(code.contains(
+        "# [cfg (test)] # [allow (dead_code)] pub fn __rpc_procedure_with_state (state : AppState)"
+    ));
+    assert!(code.contains("__RPC_TEST_STATE . scope (__state , __next . run (__req))"));
+}
+
+#[test]
+fn testable_emits_module_and_main() {
+    let func = parse_fn("async fn hello(name: String) -> String { name }");
+    let attrs = HandlerAttrs {
+        testable: true,
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Query, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains("mod hello"));
+    assert!(code.contains("pub fn __rpc_procedure ()"));
+    assert!(code.contains("# [cfg (not (test))] fn main ()"));
+    assert!(code.contains("serve_procedure (hello :: __rpc_procedure ())"));
+    assert!(!code.contains("service_fn"));
+}
+
+#[test]
+fn testable_main_runs_init_eagerly() {
+    let func = parse_fn("async fn hello(state: &AppState) -> String { String::new() }");
+    let attrs = HandlerAttrs {
+        testable: true,
+        init_fn: Some("setup".into()),
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Query, attrs)
+        .unwrap()
+        .to_string();
+    let init_pos = code
+        .find("hello :: __rpc_init () . await ;")
+        .expect("init call");
+    let serve_pos = code.find("serve_procedure").expect("serve call");
+    assert!(init_pos < serve_pos);
+    assert!(code.contains("pub async fn __rpc_init ()"));
+}
+
+#[test]
+fn default_input_limits() {
+    let func = parse_fn("async fn hello(name: String) -> String { name }");
+    let code = build_handler(func, HandlerKind::Query, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(code.contains(
+        "const __RPC_LIMITS : :: metaxy :: __private :: InputLimits = :: metaxy :: __private :: InputLimits :: DEFAULT ;"
+    ));
+    let read_pos = code
+        .find("read_query_input (__req , & __RPC_LIMITS) . await")
+        .expect("limits check");
+    let parse_pos = code.find("serde_json :: from_slice (__s)").expect("parse");
+    assert!(read_pos < parse_pos);
+    assert!(code.contains("Err (__e) => return __rpc_input_error_response (& __e)"));
+}
+
+#[test]
+fn query_accepts_marked_post_body() {
+    let func = parse_fn("async fn hello(name: String) -> String { name }");
+    let code = build_handler(func, HandlerKind::Query, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(code.contains(
+        "if __req . method () != \"GET\" && ! :: metaxy :: __private :: is_body_query (__req . method () , __req . headers ())"
+    ));
+
+    let func = parse_fn("async fn echo(input: Input) -> Input { input }");
+    let code = build_handler(func, HandlerKind::Mutation, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(!code.contains("is_body_query"));
+}
+
+#[test]
+fn mutation_reads_body_through_limits() {
+    let func = parse_fn("async fn echo(input: Input) -> Input { input }");
+    let attrs = HandlerAttrs {
+        max_body: Some(1024),
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Mutation, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains("InputLimits :: new (1024usize , :: metaxy :: DEFAULT_MAX_DEPTH)"));
+    assert!(code.contains("read_body_input (__req , & __RPC_LIMITS) . await"));
+    assert!(!code.contains("collect ()"));
+    assert!(code.contains(". status (err . status ())"));
+}
+
+fn rate_limit(key: RateLimitKey, store: Option<&str>) -> HandlerAttrs {
+    HandlerAttrs {
+        guard_fn: Some("auth".into()),
+        rate_limit: Some(RateLimitConfig {
+            requests: 10,
+            window_secs: 60,
+            key,
+            store: store.map(Into::into),
+        }),
+        ..HandlerAttrs::default()
+    }
+}
+
+#[test]
+fn ip_rate_limit_runs_before_guard() {
+    let func = parse_fn("async fn login(input: String) -> String { input }");
+    let attrs = rate_limit(RateLimitKey::Ip, None);
+    let code = build_handler(func, HandlerKind::Mutation, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains(
+        "static __RPC_RATE_LIMIT_STORE : :: metaxy :: MemoryStore = :: metaxy :: MemoryStore :: new () ;"
+    ));
+    assert!(code.contains(
+        "if let Some (__rate_key) = :: metaxy :: __private :: ip_key (__req . headers ())"
+    ));
+    assert!(code.contains("format ! (\"{}:{}\" , \"login\" , __rate_key)"));
+    assert!(
+        code.contains(
+            "RateLimit :: new (10u32 , :: std :: time :: Duration :: from_secs (60u64) ,)"
+        )
+    );
+    let check_pos = code
+        .find("__RPC_RATE_LIMIT_STORE . check")
+        .expect("rate limit check");
+    let guard_pos = code.find("auth (& __parts)").expect("guard");
+    assert!(check_pos < guard_pos);
+    assert!(
+        code.contains(
+            "let __rate_limited = :: metaxy :: __private :: rate_limited (__retry_after) ;"
+        )
+    );
+    assert!(code.contains("return Ok (__rate_limited) ;"));
+    assert!(
+        code.contains(":: metaxy :: __private :: expose_retry_after (__res . headers_mut ()) ;")
+    );
+}
+
+#[test]
+fn guard_rate_limit_runs_after_guard_with_custom_store() {
+    let func = parse_fn("async fn report() -> String { String::new() }");
+    let attrs = rate_limit(RateLimitKey::Guard, Some("crate::LIMITS"));
+    let code = build_handler(func, HandlerKind::Query, attrs)
+        .unwrap()
+        .to_string();
+    assert!(!code.contains("__RPC_RATE_LIMIT_STORE"));
+    assert!(code.contains(":: metaxy :: RateLimitKey :: rate_limit_key (& __guard_ctx)"));
+    let guard_pos = code.find("auth (& __parts)").expect("guard");
+    let check_pos = code
+        .find("crate :: LIMITS . check")
+        .expect("rate limit check");
+    assert!(guard_pos < check_pos);
+}
+
+#[test]
+fn stream_header_rate_limit_reads_parts() {
+    let func = parse_fn("async fn feed(tx: StreamSender<u32>) {}");
+    let attrs = HandlerAttrs {
+        guard_fn: None,
+        ..rate_limit(RateLimitKey::Header("x-api-key".into()), None)
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(code.contains("__parts : :: metaxy :: __private :: axum :: http :: request :: Parts"));
+    assert!(code.contains("header_key (& __parts . headers , \"x-api-key\")"));
+    assert!(code.contains(
+        "let __rate_limited = :: metaxy :: __private :: missing_key_header (\"x-api-key\") ;"
+    ));
+}
+
+#[test]
+fn query_response_negotiates_compression() {
+    let func = parse_fn("async fn report() -> String { String::new() }");
+    let code = build_handler(func, HandlerKind::Query, HandlerAttrs::default())
+        .unwrap()
+        .to_string();
+    assert!(code.contains("service_fn (__rpc_serve)"));
+    assert!(code.contains("Compression :: negotiate (__req . headers () , true)"));
+}
+
+#[test]
+fn response_format_is_negotiated() {
+    let func = parse_fn("async fn report() -> Vec<f64> { vec![] }");
+    let code = build_handler(func, HandlerKind::Query, HandlerAttrs::default())
+        .unwrap()
+        .to_string();
+    assert!(code.contains(
+        "let __format = :: metaxy :: __private :: Format :: negotiate (__req . headers ()) ;"
+    ));
+    assert!(
+        code.contains("vercel_response (__format . serialize (__rpc_handler (__req) . await ?))")
+    );
+
+    let func = parse_fn("async fn report() -> Vec<f64> { vec![] }");
+    let attrs = HandlerAttrs {
+        router: true,
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Mutation, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains("into_axum_response (__rpc_handler (__req) . await , __format)"));
+}
+
+#[test]
+fn stream_compress_false_disables_compression() {
+    let func = parse_fn("async fn feed(tx: StreamSender<u32>) {}");
+    let attrs = HandlerAttrs {
+        compress: Some(false),
+        ..HandlerAttrs::default()
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(code.contains(":: metaxy :: __private :: compress (false , __req , __next)"));
+}
+
+#[test]
+fn etag_query_checks_if_none_match() {
+    let func = parse_fn("async fn stats() -> u32 { 1 }");
+    let attrs = HandlerAttrs {
+        etag: true,
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Query, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains("header :: IF_NONE_MATCH"));
+    assert!(code.contains("etag_response (__if_none_match . as_ref () , __res)"));
+    assert!(code.contains(":: metaxy :: __private :: expose_etag (__res . headers_mut ()) ;"));
+
+    let func = parse_fn("async fn stats() -> u32 { 1 }");
+    let code = build_handler(func, HandlerKind::Query, HandlerAttrs::default())
+        .unwrap()
+        .to_string();
+    assert!(!code.contains("etag_response"));
+}
+
+#[test]
+fn validate_runs_before_handler() {
+    let func = parse_fn("async fn signup(input: SignupInput) -> String { String::new() }");
+    let attrs = HandlerAttrs {
+        validate: true,
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Mutation, attrs)
+        .unwrap()
+        .to_string();
+    let validate_pos = code
+        .find(":: metaxy :: Validate :: validate (& __input)")
+        .expect("validate call");
+    let call_pos = code
+        .find("signup (__input)) . await")
+        .expect("handler call");
+    assert!(validate_pos < call_pos);
+    assert!(code.contains("__rpc_validation_response (& __errors)"));
+    assert!(code.contains(". status (422)"));
+    assert!(code.contains("error_object (\"VALIDATION_FAILED\" , & errors . to_string ())"));
+    assert!(code.contains(
+        "error [\"fields\"] = :: metaxy :: __private :: serde_json :: to_value (errors)"
+    ));
+}
+
+#[test]
+fn no_validate_no_validation_response() {
+    let func = parse_fn("async fn signup(input: SignupInput) -> String { String::new() }");
+    let code = build_handler(func, HandlerKind::Mutation, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(!code.contains("Validate"));
+    assert!(!code.contains("__rpc_validation_response"));
+}
+
+#[test]
+fn validate_without_input_rejected() {
+    let func = parse_fn("async fn ping() -> String { String::new() }");
+    let attrs = HandlerAttrs {
+        validate: true,
+        ..HandlerAttrs::default()
+    };
+    let err = build_handler(func, HandlerKind::Query, attrs).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("validate requires an input parameter")
+    );
+}
+
+#[test]
+fn guard_runs_before_input_parsing() {
+    let func = parse_fn("async fn rename(user: Guarded<User>, name: String) -> String { name }");
+    let attrs = HandlerAttrs {
+        guard_fn: Some("require_user".into()),
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Mutation, attrs)
+        .unwrap()
+        .to_string();
+    let guard_pos = code
+        .find("require_user (& __parts) . await")
+        .expect("guard call");
+    let body_pos = code.find("read_body_input (__req").expect("body read");
+    assert!(guard_pos < body_pos);
+    assert!(code.contains("__req . into_parts ()"));
+    assert!(code.contains("Request :: from_parts (__parts , __body)"));
+    assert!(code.contains("rename (:: metaxy :: Guarded (__guard_ctx) , __input)"));
+}
+
+#[test]
+fn guard_error_uses_error_probe() {
+    let func = parse_fn("async fn me(user: Guarded<User>) -> String { String::new() }");
+    let attrs = HandlerAttrs {
+        guard_fn: Some("require_user".into()),
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Query, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains(
+        "return __rpc_error_response_with_data (__status , \"HANDLER_ERROR\" , & format ! (\"{}\" , __err) , __data)"
+    ));
+}
+
+#[test]
+fn guard_receives_state() {
+    let func =
+        parse_fn("async fn me(state: &AppState, user: Guarded<User>) -> String { String::new() }");
+    let attrs = HandlerAttrs {
+        init_fn: Some("setup".into()),
+        guard_fn: Some("require_user".into()),
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Query, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains("require_user (& __parts , __state) . await"));
+}
+
+#[test]
+fn guard_without_guarded_param() {
+    let func = parse_fn("async fn ping() -> String { String::new() }");
+    let attrs = HandlerAttrs {
+        guard_fn: Some("require_user".into()),
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Query, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains("require_user (& __parts) . await"));
+    assert!(code.contains("ping ()) . await"));
+}
+
+#[test]
+fn guarded_param_requires_guard_attr() {
+    let func = parse_fn("async fn me(user: Guarded<User>) -> String { String::new() }");
+    let err = build_handler(func, HandlerKind::Query, no_attrs()).unwrap_err();
+    assert!(err.to_string().contains("requires guard"));
+}
+
+#[test]
+fn duplicate_guarded_param_rejected() {
+    let func =
+        parse_fn("async fn me(a: Guarded<User>, b: Guarded<User>) -> String { String::new() }");
+    let attrs = HandlerAttrs {
+        guard_fn: Some("require_user".into()),
+        ..HandlerAttrs::default()
+    };
+    let err = build_handler(func, HandlerKind::Query, attrs).unwrap_err();
+    assert!(err.to_string().contains("at most one Guarded"));
+}
+
+#[test]
+fn builtin_extractors_from_request_parts() {
+    let func = parse_fn(
+        "async fn whoami(ip: ClientIp, name: String, cookies: metaxy::Cookies) -> String { name }",
+    );
+    let code = build_handler(func, HandlerKind::Query, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(code.contains("__req . into_parts ()"));
+    assert!(code.contains(
+        "let __from_request_0 = match < ClientIp as :: metaxy :: FromRequest > :: from_request (& __parts)"
+    ));
+    assert!(code.contains(
+        "let __from_request_2 = match < metaxy :: Cookies as :: metaxy :: FromRequest > :: from_request (& __parts)"
+    ));
+    assert!(code.contains("whoami (__from_request_0 , __input , __from_request_2)"));
+}
+
+#[test]
+fn from_request_attr_marks_extractor() {
+    let func = parse_fn(
+        "async fn rename(#[from_request] tenant: Tenant, name: String) -> String { name }",
+    );
+    let code = build_handler(func, HandlerKind::Mutation, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(code.contains("< Tenant as :: metaxy :: FromRequest > :: from_request (& __parts)"));
+    assert!(code.contains("let __input : String"));
+    assert!(!code.contains("# [from_request]"));
+}
+
+#[test]
+fn no_extractors_keeps_request_whole() {
+    let func = parse_fn("async fn hello(name: String) -> String { name }");
+    let code = build_handler(func, HandlerKind::Query, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(!code.contains("into_parts"));
+    assert!(!code.contains("FromRequest"));
+}
+
 #[test]
 fn query_no_return_type() {
     let func = parse_fn("async fn ping() {}");
@@ -51,7 +571,7 @@ fn mutation_with_input() {
     let tokens = build_handler(func, HandlerKind::Mutation, no_attrs()).unwrap();
     let code = tokens.to_string();
     assert!(code.contains("\"POST\""));
-    assert!(code.contains("into_body"));
+    assert!(code.contains("read_body_input"));
 }
 
 #[test]
@@ -96,9 +616,61 @@ fn generates_cors_headers() {
     let code = build_handler(func, HandlerKind::Query, no_attrs())
         .unwrap()
         .to_string();
-    assert!(code.contains("Access-Control-Allow-Origin"));
-    assert!(code.contains("Access-Control-Allow-Methods"));
-    assert!(code.contains("Access-Control-Max-Age"));
+    assert!(code.contains("const __RPC_CORS : :: metaxy :: Cors = :: metaxy :: Cors :: DEFAULT"));
+    assert!(code.contains("__RPC_CORS . apply (__origin . as_ref () , __res . headers_mut ())"));
+}
+
+#[test]
+fn generates_inline_cors_policy() {
+    let func = parse_fn("async fn ping() -> String { \"pong\".into() }");
+    let attrs = HandlerAttrs {
+        cors: Some(CorsConfig::Inline {
+            origins: vec!["https://a.com".into(), "https://b.com".into()],
+            credentials: true,
+            allow_headers: vec!["X-Tenant".into()],
+            expose_headers: vec![],
+            max_age_secs: Some(600),
+        }),
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Query, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains("origins : & [\"https://a.com\" , \"https://b.com\"]"));
+    assert!(code.contains("credentials : true"));
+    assert!(code.contains("allow_headers : & [\"X-Tenant\"]"));
+    assert!(code.contains("expose_headers : & []"));
+    assert!(code.contains("max_age : 600u64"));
+}
+
+#[test]
+fn inline_cors_defaults_max_age() {
+    let func = parse_fn("async fn ping() -> String { \"pong\".into() }");
+    let attrs = HandlerAttrs {
+        cors: Some(CorsConfig::Inline {
+            origins: vec!["*".into()],
+            credentials: false,
+            allow_headers: vec![],
+            expose_headers: vec![],
+            max_age_secs: None,
+        }),
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Mutation, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains("max_age : :: metaxy :: Cors :: DEFAULT . max_age"));
+}
+
+#[test]
+fn invalid_cors_path_rejected() {
+    let func = parse_fn("async fn ping() -> String { \"pong\".into() }");
+    let attrs = HandlerAttrs {
+        cors: Some(CorsConfig::Path("not a path (".into())),
+        ..HandlerAttrs::default()
+    };
+    let err = build_handler(func, HandlerKind::Query, attrs).unwrap_err();
+    assert!(err.to_string().contains("invalid cors path"));
 }
 
 #[test]
@@ -121,6 +693,16 @@ fn generates_current_thread_runtime() {
     assert!(!code.contains("tokio :: main"));
 }
 
+#[test]
+fn serves_through_dev_aware_runtime() {
+    let func = parse_fn("async fn ping() -> String { \"pong\".into() }");
+    let code = build_handler(func, HandlerKind::Query, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(code.contains(":: metaxy :: __private :: run ("));
+    assert!(!code.contains("vercel_runtime :: run"));
+}
+
 #[test]
 fn generates_method_not_allowed() {
     let func = parse_fn("async fn ping() -> String { \"pong\".into() }");
@@ -404,6 +986,50 @@ fn multiple_state_params_rejected() {
     assert!(err.to_string().contains("at most one state parameter"));
 }
 
+// --- generate_handler: panics ---
+
+#[test]
+fn handler_panic_is_a_500() {
+    let func = parse_fn("async fn boom() -> String { String::new() }");
+    let code = build_handler(func, HandlerKind::Query, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(
+        code.contains("match :: metaxy :: __private :: catch_panic (\"boom\" , boom ()) . await")
+    );
+    assert!(
+        code.contains(
+            "Err (__panic) => return __rpc_error_response (500 , \"INTERNAL\" , & __panic)"
+        )
+    );
+}
+
+#[test]
+fn handler_panic_inside_timeout_is_a_500() {
+    let func = parse_fn("async fn slow() -> String { String::new() }");
+    let attrs = HandlerAttrs {
+        timeout_secs: Some(30),
+        ..HandlerAttrs::default()
+    };
+    let code = build_handler(func, HandlerKind::Query, attrs)
+        .unwrap()
+        .to_string();
+    assert!(code.contains(
+        "Ok (Err (__panic)) => return __rpc_error_response (500 , \"INTERNAL\" , & __panic)"
+    ));
+}
+
+#[test]
+fn error_responses_carry_codes() {
+    let func = parse_fn("async fn lookup(id: u32) -> Result<String, String> { todo!() }");
+    let code = build_handler(func, HandlerKind::Query, no_attrs())
+        .unwrap()
+        .to_string();
+    assert!(code.contains("__rpc_error_response (405 , \"METHOD_NOT_ALLOWED\""));
+    assert!(code.contains("__rpc_error_response (400 , \"BAD_INPUT\""));
+    assert!(code.contains("error_object (code , message)"));
+}
+
 // --- generate_handler: timeout ---
 
 #[test]
@@ -488,6 +1114,35 @@ fn mutation_idempotent_no_codegen_diff() {
 
 // --- build_stream_handler: valid streams ---
 
+#[test]
+fn stream_router_mode_registers_method_router() {
+    let func = parse_fn("async fn ticks(tx: StreamSender<Tick>) {}");
+    let attrs = HandlerAttrs {
+        router: true,
+        ..HandlerAttrs::default()
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(code.contains("mod ticks"));
+    assert!(code.contains("Procedure :: new (\"ticks\""));
+    assert!(code.contains("post (__rpc_stream_handler)"));
+    assert!(code.contains("from_fn (__rpc_cors)"));
+    assert!(!code.contains("fn main"));
+    assert!(!code.contains("VercelLayer"));
+}
+
+#[test]
+fn stream_testable_emits_module_and_main() {
+    let func = parse_fn("async fn ticks(tx: StreamSender<Tick>) {}");
+    let attrs = HandlerAttrs {
+        testable: true,
+        ..HandlerAttrs::default()
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(code.contains("mod ticks"));
+    assert!(code.contains("# [cfg (not (test))] # [:: metaxy :: __private :: tokio :: main]"));
+    assert!(code.contains("serve_procedure (ticks :: __rpc_procedure ())"));
+}
+
 #[test]
 fn stream_basic_with_sender() {
     let func = parse_fn("async fn events(tx: StreamSender) {}");
@@ -500,14 +1155,36 @@ fn stream_basic_with_sender() {
     assert!(code.contains("StreamSender :: new"));
 }
 
+#[test]
+fn stream_sender_resumes_after_last_event_id() {
+    let func = parse_fn("async fn events(tx: StreamSender) {}");
+    let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
+    assert!(code.contains("__last_event_id : :: metaxy :: LastEventId"));
+    assert!(code.contains("StreamSender :: new (__raw_tx) . resume_after (& __last_event_id)"));
+}
+
 #[test]
 fn stream_with_input_and_sender() {
     let func = parse_fn("async fn chat(input: ChatInput, tx: StreamSender) {}");
     let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
-    assert!(code.contains("Json"));
+    assert!(code.contains("__body : :: metaxy :: __private :: axum :: body :: Body"));
+    assert!(code.contains("__RPC_LIMITS . read_body (__body) . await"));
+    assert!(code.contains("__rpc_json_response (__e . status () , __e . to_json ())"));
     assert!(code.contains("__input"));
 }
 
+#[test]
+fn stream_without_input_skips_limits() {
+    let func = parse_fn("async fn events(tx: StreamSender) {}");
+    let attrs = HandlerAttrs {
+        max_depth: Some(8),
+        ..HandlerAttrs::default()
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(!code.contains("__RPC_LIMITS"));
+    assert!(!code.contains("__rpc_json_response"));
+}
+
 #[test]
 fn stream_with_headers_and_sender() {
     let func = parse_fn("async fn events(headers: Headers, tx: StreamSender) {}");
@@ -543,7 +1220,7 @@ fn stream_with_timeout() {
     let code = build_stream_handler(func, attrs).unwrap().to_string();
     assert!(code.contains("timeout_at"));
     assert!(code.contains("Duration :: from_secs (30u64)"));
-    assert!(code.contains("event: error"));
+    assert!(code.contains("__end . error (504 , \"TIMEOUT\" , \"Handler timed out\" , None)"));
 }
 
 #[test]
@@ -581,7 +1258,7 @@ fn stream_with_timeout_and_input() {
     assert!(code.contains("__input"));
     assert!(code.contains("timeout_at"));
     assert!(code.contains("Duration :: from_secs (60u64)"));
-    assert!(code.contains("event: error"));
+    assert!(code.contains("__end . error (504 , \"TIMEOUT\" , \"Handler timed out\" , None)"));
 }
 
 #[test]
@@ -596,7 +1273,7 @@ fn stream_with_init_and_timeout() {
     assert!(code.contains("setup () . await"));
     assert!(code.contains("timeout_at"));
     assert!(code.contains("Duration :: from_secs (45u64)"));
-    assert!(code.contains("event: error"));
+    assert!(code.contains("__end . error (504 , \"TIMEOUT\" , \"Handler timed out\" , None)"));
 }
 
 #[test]
@@ -624,11 +1301,185 @@ fn stream_with_all_attrs_and_params() {
         "must use 120s"
     );
     assert!(
-        code.contains("event: error"),
+        code.contains("__end . error (504"),
         "timeout must send SSE error event"
     );
 }
 
+#[test]
+fn stream_return_value_is_sent_as_done() {
+    let func = parse_fn("async fn events(tx: StreamSender) -> u32 { 1 }");
+    let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
+    assert!(code.contains("async fn events (tx : StreamSender) -> u32"));
+    assert!(code.contains("__end . done (& __result) . await"));
+}
+
+#[test]
+fn stream_result_err_is_sent_as_error_event() {
+    let func = parse_fn("async fn events(tx: StreamSender) -> Result<u32, String> { Ok(1) }");
+    let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
+    assert!(code.contains("Ok (__val) => __end . done (& __val) . await"));
+    assert!(code.contains("__rpc_error_status"));
+    assert!(
+        code.contains("__end . error (__status , \"HANDLER_ERROR\" , & format ! (\"{}\" , __err) , __data) . await")
+    );
+}
+
+#[test]
+fn stream_without_return_value_sends_no_done() {
+    let func = parse_fn("async fn events(tx: StreamSender) {}");
+    let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
+    assert!(code.contains("let () = __result ;"));
+    assert!(!code.contains("__end . done"));
+    assert!(!code.contains("heartbeat"));
+}
+
+#[test]
+fn stream_heartbeat_runs_alongside_handler() {
+    let func = parse_fn("async fn events(tx: StreamSender) {}");
+    let attrs = HandlerAttrs {
+        heartbeat_secs: Some(15),
+        ..HandlerAttrs::default()
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(code.contains(
+        "let __heartbeat = __end . heartbeat (:: std :: time :: Duration :: from_secs (15u64)) ;"
+    ));
+}
+
+#[test]
+fn stream_returned_stream_is_forwarded_to_sender() {
+    let func = parse_fn("async fn ticks(every: u64) -> impl Stream<Item = Tick> { todo!() }");
+    let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
+    assert!(code.contains("let __stream = ticks (__input) . await ;"));
+    assert!(code.contains(":: metaxy :: __private :: next_item (__stream . as_mut ()) . await"));
+    assert!(code.contains("if __tx . send (__item) . await . is_err () { break ; }"));
+    assert!(!code.contains("__end . done"));
+}
+
+#[test]
+fn stream_returned_result_items_end_with_error_event() {
+    let func = parse_fn(
+        "async fn rows(query: String) -> impl Stream<Item = Result<Row, DbError>> { todo!() }",
+    );
+    let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
+    assert!(code.contains("Ok (__chunk) => { if __tx . send (__chunk) . await . is_err ()"));
+    assert!(code.contains(
+        "__end . error (__status , \"HANDLER_ERROR\" , & format ! (\"{}\" , __err) , __data) . await ; break ;"
+    ));
+}
+
+#[test]
+fn stream_returned_stream_respects_timeout() {
+    let func = parse_fn("async fn ticks() -> impl Stream<Item = Tick> { todo!() }");
+    let attrs = HandlerAttrs {
+        timeout_secs: Some(5),
+        ..HandlerAttrs::default()
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(code.contains("timeout_at (__deadline , :: metaxy :: __private :: catch_panic (\"ticks\" , async { let __stream = ticks () . await ;"));
+}
+
+#[test]
+fn stream_ndjson_uses_ndjson_sender_and_content_type() {
+    let func = parse_fn("async fn export(tx: NdjsonSender<Order>) {}");
+    let attrs = HandlerAttrs {
+        stream_format: Some(StreamFormat::Ndjson),
+        ..HandlerAttrs::default()
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(code.contains("let __tx = :: metaxy :: NdjsonSender :: new (__raw_tx) ;"));
+    assert!(code.contains("HeaderValue :: from_static (\"application/x-ndjson\")"));
+    assert!(!code.contains("resume_after"));
+}
+
+#[test]
+fn stream_bytes_uses_byte_sender_and_content_type() {
+    let func = parse_fn("async fn report(tx: ByteSender) {}");
+    let attrs = HandlerAttrs {
+        stream_format: Some(StreamFormat::Bytes),
+        ..HandlerAttrs::default()
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(code.contains("let __tx = :: metaxy :: ByteSender :: new (__raw_tx) ;"));
+    assert!(code.contains("HeaderValue :: from_static (\"application/octet-stream\")"));
+}
+
+#[test]
+fn stream_sse_keeps_default_content_type() {
+    let func = parse_fn("async fn events(tx: StreamSender) {}");
+    let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
+    assert!(!code.contains("CONTENT_TYPE"));
+}
+
+#[test]
+fn stream_ndjson_errors_abort_the_body() {
+    let func = parse_fn(
+        "async fn rows(query: String) -> impl Stream<Item = Result<Row, DbError>> { todo!() }",
+    );
+    let attrs = HandlerAttrs {
+        stream_format: Some(StreamFormat::Ndjson),
+        timeout_secs: Some(5),
+        ..HandlerAttrs::default()
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(code.contains("__end . abort (& format ! (\"{}\" , __err)) . await ;"));
+    assert!(code.contains("Err (_) => __end . abort (\"Handler timed out\") . await"));
+    assert!(!code.contains("__end . error"));
+}
+
+#[test]
+fn stream_get_reads_query_input() {
+    let func = parse_fn("async fn ticks(from: u32, tx: StreamSender<u32>) {}");
+    let attrs = HandlerAttrs {
+        stream_get: Some(true),
+        ..HandlerAttrs::default()
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(code.contains("routing :: get (__rpc_stream_handler)"));
+    assert!(code.contains("__req : :: metaxy :: __private :: axum :: extract :: Request"));
+    assert!(code.contains("read_query_input (__req , & __RPC_LIMITS)"));
+    assert!(!code.contains("__body"));
+}
+
+#[test]
+fn stream_get_unit_return_ends_with_done() {
+    let func = parse_fn("async fn clock(tx: StreamSender) {}");
+    let attrs = HandlerAttrs {
+        stream_get: Some(true),
+        ..HandlerAttrs::default()
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(code.contains("let () = __result ; __end . done (& ()) . await ;"));
+}
+
+#[test]
+fn stream_post_is_the_default() {
+    let func = parse_fn("async fn events(input: String, tx: StreamSender) {}");
+    let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
+    assert!(code.contains("routing :: post (__rpc_stream_handler)"));
+    assert!(!code.contains("read_query_input"));
+    assert!(!code.contains("__end . done (& ())"));
+}
+
+#[test]
+fn stream_panic_ends_with_error_event() {
+    let func = parse_fn("async fn events(tx: StreamSender) {}");
+    let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
+    assert!(code.contains("catch_panic (\"events\" , events (__tx)) . await"));
+    assert!(code.contains(
+        "Err (__panic) => __end . error (500 , \"INTERNAL\" , & __panic , None) . await"
+    ));
+}
+
+#[test]
+fn stream_other_methods_get_json_405() {
+    let func = parse_fn("async fn events(tx: StreamSender) {}");
+    let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
+    assert!(code.contains(". fallback (| __method : :: metaxy :: __private :: http :: Method |"));
+    assert!(code.contains("method_not_allowed (& __method , \"POST\")"));
+}
+
 // --- build_stream_handler: error cases ---
 
 #[test]
@@ -639,17 +1490,60 @@ fn stream_rejects_non_async() {
 }
 
 #[test]
-fn stream_rejects_return_type() {
-    let func = parse_fn("async fn events(tx: StreamSender) -> String { String::new() }");
+fn stream_rejects_missing_sender() {
+    let func = parse_fn("async fn events(input: String) {}");
     let err = build_stream_handler(func, no_attrs()).unwrap_err();
-    assert!(err.to_string().contains("must not have a return type"));
+    assert!(err.to_string().contains("must accept a StreamSender"));
 }
 
 #[test]
-fn stream_rejects_missing_sender() {
-    let func = parse_fn("async fn events(input: String) {}");
+fn stream_rejects_sender_of_other_format() {
+    let func = parse_fn("async fn export(tx: StreamSender<Order>) {}");
+    let attrs = HandlerAttrs {
+        stream_format: Some(StreamFormat::Ndjson),
+        ..HandlerAttrs::default()
+    };
+    let err = build_stream_handler(func, attrs).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("`format = \"ndjson\"` streams must use NdjsonSender")
+    );
+}
+
+#[test]
+fn stream_rejects_heartbeat_on_ndjson() {
+    let func = parse_fn("async fn export(tx: NdjsonSender<Order>) {}");
+    let attrs = HandlerAttrs {
+        stream_format: Some(StreamFormat::Ndjson),
+        heartbeat_secs: Some(15),
+        ..HandlerAttrs::default()
+    };
+    let err = build_stream_handler(func, attrs).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("heartbeat is only valid on SSE streams")
+    );
+}
+
+#[test]
+fn stream_rejects_return_value_on_bytes() {
+    let func = parse_fn("async fn report(tx: ByteSender) -> usize { 0 }");
+    let attrs = HandlerAttrs {
+        stream_format: Some(StreamFormat::Bytes),
+        ..HandlerAttrs::default()
+    };
+    let err = build_stream_handler(func, attrs).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("only SSE streams can return a value")
+    );
+}
+
+#[test]
+fn stream_rejects_sender_with_returned_stream() {
+    let func = parse_fn("async fn events(tx: StreamSender<u32>) -> impl Stream<Item = u32> {}");
     let err = build_stream_handler(func, no_attrs()).unwrap_err();
-    assert!(err.to_string().contains("must accept a StreamSender"));
+    assert!(err.to_string().contains("cannot take a StreamSender"));
 }
 
 #[test]
@@ -698,16 +1592,62 @@ fn stream_rejects_multiple_state() {
 // --- build_stream_handler: incompatible attrs ---
 
 #[test]
-fn stream_no_cors_headers() {
+fn stream_applies_cors_layer() {
     let func = parse_fn("async fn events(tx: StreamSender) {}");
     let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
-    assert!(!code.contains("Access-Control-Allow-Origin"));
+    assert!(code.contains("middleware :: from_fn (__rpc_cors)"));
+    assert!(code.contains("const __RPC_CORS : :: metaxy :: Cors = :: metaxy :: Cors :: DEFAULT"));
+    assert!(code.contains("__RPC_CORS . apply (__origin . as_ref () , __res . headers_mut ())"));
 }
 
 #[test]
-fn stream_no_options_handler() {
+fn stream_preflight_handled_by_router() {
     let func = parse_fn("async fn events(tx: StreamSender) {}");
     let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
+    assert!(code.contains(
+        ". options (|| async { :: metaxy :: __private :: http :: StatusCode :: NO_CONTENT })"
+    ));
     assert!(!code.contains("\"OPTIONS\""));
     assert!(!code.contains("405"));
 }
+
+#[test]
+fn stream_with_guard() {
+    let func = parse_fn("async fn events(user: Guarded<User>, tx: StreamSender<Event>) {}");
+    let attrs = HandlerAttrs {
+        guard_fn: Some("require_user".into()),
+        ..HandlerAttrs::default()
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(code.contains("__parts : :: metaxy :: __private :: axum :: http :: request :: Parts"));
+    assert!(code.contains("require_user (& __parts) . await"));
+    assert!(code.contains("events (:: metaxy :: Guarded (__guard_ctx) , __tx)"));
+    assert!(code.contains("StatusCode :: from_u16 (__status)"));
+}
+
+#[test]
+fn stream_with_extractor() {
+    let func = parse_fn("async fn events(ip: ClientIp, tx: StreamSender<Event>) {}");
+    let code = build_stream_handler(func, no_attrs()).unwrap().to_string();
+    assert!(code.contains("__parts : :: metaxy :: __private :: axum :: http :: request :: Parts"));
+    assert!(code.contains("< ClientIp as :: metaxy :: FromRequest > :: from_request (& __parts)"));
+    assert!(code.contains("events (__from_request_0 , __tx)"));
+}
+
+#[test]
+fn stream_guarded_param_requires_guard_attr() {
+    let func = parse_fn("async fn events(user: Guarded<User>, tx: StreamSender<Event>) {}");
+    let err = build_stream_handler(func, no_attrs()).unwrap_err();
+    assert!(err.to_string().contains("requires guard"));
+}
+
+#[test]
+fn stream_with_cors_path() {
+    let func = parse_fn("async fn events(tx: StreamSender) {}");
+    let attrs = HandlerAttrs {
+        cors: Some(CorsConfig::Path("crate::CORS".into())),
+        ..HandlerAttrs::default()
+    };
+    let code = build_stream_handler(func, attrs).unwrap().to_string();
+    assert!(code.contains("const __RPC_CORS : :: metaxy :: Cors = crate :: CORS"));
+}
@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::Router;
+use axum::body::Body;
+use axum::extract::Request;
+use axum::response::Response;
+use http::header::{
+    ACCEPT, ACCEPT_ENCODING, ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_LENGTH, CONTENT_TYPE,
+    IF_NONE_MATCH, ORIGIN, TRANSFER_ENCODING,
+};
+use http::{HeaderValue, Method, StatusCode};
+use http_body_util::BodyExt;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+use crate::compress::Compression;
+use crate::cors::Cors;
+use crate::error::error_object;
+use crate::limits::InputLimits;
+use crate::router::ProcedureKind;
+
+/// Most calls accepted in one batch request.
+const MAX_BATCH_SIZE: usize = 100;
+
+/// One entry of a batch request body.
+#[derive(Debug, Deserialize)]
+struct BatchCall {
+    procedure: String,
+    #[serde(default)]
+    input: Option<Value>,
+}
+
+/// Serves `POST /_batch` for a [`router!`](crate::router).
+///
+/// The body is a JSON array of `{ "procedure": "<name>", "input": <json> }`.
+/// Every call naming a query in `kinds` is run concurrently as
+/// `GET /<name>?input=...` against `router`, with the batch request's headers;
+/// mutations and streams are answered with a `405` item without being called.
+/// The response is
+/// an array in the same order holding each call's `result` or `error`
+/// together with its `status`. Headers set by individual calls (cookies,
+/// `Cache-Control`) are not forwarded. The batch response answers with `cors`,
+/// the policy given to [`router!`](crate::router).
+pub(crate) async fn serve_batch(
+    router: Router,
+    kinds: Arc<HashMap<&'static str, ProcedureKind>>,
+    cors: Cors,
+    req: Request,
+) -> Response {
+    let origin = req.headers().get(ORIGIN).cloned();
+    let compression = Compression::negotiate(req.headers(), true);
+    let mut res = if req.method() == Method::OPTIONS {
+        let mut res = Response::new(Body::empty());
+        *res.status_mut() = StatusCode::NO_CONTENT;
+        res
+    } else {
+        run_batch(router, &kinds, req).await
+    };
+    cors.apply(origin.as_ref(), res.headers_mut());
+    compression.axum_response(res).await
+}
+
+async fn run_batch(
+    router: Router,
+    kinds: &HashMap<&'static str, ProcedureKind>,
+    req: Request,
+) -> Response {
+    if req.method() != Method::POST {
+        let message = format!("Method {} not allowed, expected POST", req.method());
+        return json_response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            &error_body("METHOD_NOT_ALLOWED", &message),
+        );
+    }
+
+    let (parts, body) = req.into_parts();
+    let bytes = match InputLimits::DEFAULT.read_body(body).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            let status = StatusCode::from_u16(err.status()).unwrap_or(StatusCode::BAD_REQUEST);
+            return json_response(status, &err.to_json());
+        }
+    };
+    let calls: Vec<BatchCall> = match serde_json::from_slice(&bytes) {
+        Ok(calls) => calls,
+        Err(e) => {
+            let message = format!("Failed to deserialize batch: {e}");
+            return json_response(StatusCode::BAD_REQUEST, &error_body("BAD_INPUT", &message));
+        }
+    };
+    if calls.len() > MAX_BATCH_SIZE {
+        let message = format!("Batch exceeds the limit of {MAX_BATCH_SIZE} calls");
+        return json_response(StatusCode::BAD_REQUEST, &error_body("BAD_INPUT", &message));
+    }
+
+    let mut headers = parts.headers;
+    // Calls are answered as plain JSON, whatever format the batch request accepts.
+    for name in [
+        ACCEPT,
+        CONTENT_TYPE,
+        CONTENT_LENGTH,
+        TRANSFER_ENCODING,
+        ACCEPT_ENCODING,
+        IF_NONE_MATCH,
+    ] {
+        headers.remove(name);
+    }
+    let check_origin = headers.contains_key(ORIGIN);
+
+    let mut tasks = tokio::task::JoinSet::new();
+    for (index, call) in calls.into_iter().enumerate() {
+        let router = router.clone();
+        let headers = headers.clone();
+        let kind = kinds.get(call.procedure.as_str()).copied();
+        tasks.spawn(async move {
+            let item = run_call(router, headers, call, kind, check_origin).await;
+            (index, item)
+        });
+    }
+    let mut items = vec![Value::Null; tasks.len()];
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((index, item)) = joined {
+            items[index] = item;
+        }
+    }
+    for item in &mut items {
+        if item.is_null() {
+            *item = error_item(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL",
+                "Handler panicked",
+            );
+        }
+    }
+    json_response(StatusCode::OK, &Value::Array(items))
+}
+
+/// Runs one call and turns its response into a batch item.
+async fn run_call(
+    router: Router,
+    headers: http::HeaderMap,
+    call: BatchCall,
+    kind: Option<ProcedureKind>,
+    check_origin: bool,
+) -> Value {
+    match kind {
+        Some(ProcedureKind::Query) => {}
+        // Streams never finish collecting, and mutations are not sent as `GET`.
+        Some(_) => {
+            let message = format!("Procedure \"{}\" is not a query", call.procedure);
+            return error_item(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "METHOD_NOT_ALLOWED",
+                &message,
+            );
+        }
+        None => {
+            let message = format!("Unknown procedure \"{}\"", call.procedure);
+            return error_item(StatusCode::NOT_FOUND, "NOT_FOUND", &message);
+        }
+    }
+
+    let mut uri = format!("/{}", call.procedure);
+    if let Some(input) = &call.input {
+        let input = input.to_string();
+        uri.push_str("?input=");
+        uri.extend(utf8_percent_encode(&input, NON_ALPHANUMERIC));
+    }
+    let mut req = Request::new(Body::empty());
+    *req.uri_mut() = match uri.parse() {
+        Ok(uri) => uri,
+        Err(_) => return error_item(StatusCode::BAD_REQUEST, "BAD_INPUT", "Invalid batch call"),
+    };
+    *req.headers_mut() = headers;
+
+    let Ok(res) = router.oneshot(req).await;
+    let status = res.status();
+    // The procedure's CORS policy rejected the origin, so its result must not leak
+    // through the batch endpoint's policy.
+    if check_origin && !res.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN) {
+        return error_item(StatusCode::FORBIDDEN, "FORBIDDEN", "Origin not allowed");
+    }
+    let bytes = res
+        .into_body()
+        .collect()
+        .await
+        .map(|body| body.to_bytes())
+        .unwrap_or_default();
+    match serde_json::from_slice(&bytes) {
+        Ok(Value::Object(mut item)) => {
+            item.insert("status".into(), status.as_u16().into());
+            Value::Object(item)
+        }
+        _ if status == StatusCode::NOT_FOUND => {
+            let message = format!("Unknown procedure \"{}\"", call.procedure);
+            error_item(status, "NOT_FOUND", &message)
+        }
+        // A response that is not an error envelope, e.g. axum's bare 405 for a stream.
+        _ => {
+            let code = match status {
+                StatusCode::METHOD_NOT_ALLOWED => "METHOD_NOT_ALLOWED",
+                _ => "INTERNAL",
+            };
+            error_item(status, code, status.canonical_reason().unwrap_or("Error"))
+        }
+    }
+}
+
+fn error_body(code: &str, message: &str) -> Value {
+    json!({ "error": error_object(code, message) })
+}
+
+fn error_item(status: StatusCode, code: &str, message: &str) -> Value {
+    let mut item = error_body(code, message);
+    item["status"] = status.as_u16().into();
+    item
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response {
+    let mut res = Response::new(Body::from(serde_json::to_vec(body).unwrap_or_default()));
+    *res.status_mut() = status;
+    res.headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::Router;
+    use axum::body::Body;
+    use axum::extract::Query;
+    use axum::routing::get;
+    use http::{Request, StatusCode};
+    use http_body_util::BodyExt;
+    use serde_json::{Value, json};
+    use tower::ServiceExt;
+
+    use crate::cors::Cors;
+    use crate::router::{Procedure, ProcedureKind, build_router};
+
+    const CREDENTIALED: Cors = Cors {
+        origins: &["https://app.example.com"],
+        credentials: true,
+        ..Cors::DEFAULT
+    };
+
+    fn router() -> Router {
+        router_with(Cors::DEFAULT)
+    }
+
+    fn router_with(cors: Cors) -> Router {
+        build_router(
+            cors,
+            [
+                Procedure::new(
+                    "echo",
+                    ProcedureKind::Query,
+                    get(|Query(q): Query<Vec<(String, String)>>| async move {
+                        axum::Json(json!({ "result": { "type": "response", "data": q } }))
+                    }),
+                ),
+                Procedure::new(
+                    "fail",
+                    ProcedureKind::Query,
+                    get(|| async {
+                        (
+                            StatusCode::BAD_REQUEST,
+                            axum::Json(json!({ "error": { "type": "error", "message": "nope" } })),
+                        )
+                    }),
+                ),
+                // Never answers, so batching it would hang.
+                Procedure::new(
+                    "ticks",
+                    ProcedureKind::Stream,
+                    get(std::future::pending::<&'static str>),
+                ),
+            ],
+        )
+    }
+
+    async fn batch(method: &str, body: &str) -> (StatusCode, Value) {
+        let res = router()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri("/api/_batch")
+                    .body(Body::from(body.to_owned()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = res.status();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    #[tokio::test]
+    async fn returns_results_in_order() {
+        let (status, items) = batch(
+            "POST",
+            r#"[{"procedure":"echo","input":"a b"},{"procedure":"fail"},{"procedure":"echo"}]"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(items[0]["status"], 200);
+        assert_eq!(items[0]["result"]["data"], json!([["input", "\"a b\""]]));
+        assert_eq!(items[1]["status"], 400);
+        assert_eq!(items[1]["error"]["message"], "nope");
+        assert_eq!(items[2]["result"]["data"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn unknown_procedures_are_404_items() {
+        let (_, items) = batch(
+            "POST",
+            r#"[{"procedure":"nope"},{"procedure":"../echo"},{"procedure":"echo?x=1"}]"#,
+        )
+        .await;
+        for item in items.as_array().unwrap() {
+            assert_eq!(item["status"], 404);
+        }
+        assert_eq!(items[0]["error"]["message"], "Unknown procedure \"nope\"");
+        assert_eq!(items[0]["error"]["code"], "NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn streams_are_not_called() {
+        let (_, items) = batch("POST", r#"[{"procedure":"ticks"},{"procedure":"echo"}]"#).await;
+        assert_eq!(items[0]["status"], 405);
+        assert_eq!(items[0]["error"]["code"], "METHOD_NOT_ALLOWED");
+        assert_eq!(
+            items[0]["error"]["message"],
+            "Procedure \"ticks\" is not a query"
+        );
+        assert_eq!(items[1]["status"], 200);
+    }
+
+    #[tokio::test]
+    async fn rejects_malformed_batches() {
+        assert_eq!(batch("GET", "").await.0, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(batch("POST", "{}").await.0, StatusCode::BAD_REQUEST);
+        let calls = vec![json!({ "procedure": "echo" }); 101];
+        let (status, body) = batch("POST", &Value::Array(calls).to_string()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body["error"]["message"],
+            "Batch exceeds the limit of 100 calls"
+        );
+        assert_eq!(body["error"]["code"], "BAD_INPUT");
+    }
+
+    #[tokio::test]
+    async fn applies_router_cors_policy() {
+        let preflight = |origin: &str| {
+            router_with(CREDENTIALED).oneshot(
+                Request::builder()
+                    .method("OPTIONS")
+                    .uri("/api/_batch")
+                    .header("origin", origin)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let res = preflight("https://app.example.com").await.unwrap();
+        assert_eq!(
+            res.headers()["access-control-allow-origin"],
+            "https://app.example.com"
+        );
+        assert_eq!(res.headers()["access-control-allow-credentials"], "true");
+
+        let res = preflight("https://evil.example.com").await.unwrap();
+        assert!(!res.headers().contains_key("access-control-allow-origin"));
+    }
+}
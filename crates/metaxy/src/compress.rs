@@ -0,0 +1,396 @@
+use axum::body::Body;
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use http::HeaderMap;
+use http::header::{ACCEPT_ENCODING, CONTENT_TYPE, VARY};
+use hyper::body::Bytes;
+use vercel_runtime::ResponseBody;
+
+use crate::format::Format;
+use crate::stream::{BYTES_CONTENT_TYPE, NDJSON_CONTENT_TYPE};
+
+/// Bodies smaller than this are sent uncompressed.
+#[cfg_attr(not(feature = "compression"), allow(dead_code))]
+const MIN_COMPRESS_BYTES: usize = 1024;
+
+/// Responses compressed chunk by chunk instead of as a whole.
+const STREAM_MEDIA_TYPES: [&str; 3] =
+    ["text/event-stream", NDJSON_CONTENT_TYPE, BYTES_CONTENT_TYPE];
+
+/// A content coding the client accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Gzip,
+    Brotli,
+}
+
+impl Encoding {
+    #[cfg_attr(not(feature = "compression"), allow(dead_code))]
+    fn name(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Brotli => "br",
+        }
+    }
+}
+
+/// Picks the preferred coding from `Accept-Encoding`: the highest `q` wins,
+/// brotli over gzip on a tie.
+fn preferred_encoding(accept: &str) -> Option<Encoding> {
+    let mut best: Option<(f32, Encoding)> = None;
+    for item in accept.split(',') {
+        let mut params = item.split(';');
+        let encoding = match params.next().unwrap_or_default().trim() {
+            name if name.eq_ignore_ascii_case("br") => Encoding::Brotli,
+            name if name.eq_ignore_ascii_case("gzip") || name.eq_ignore_ascii_case("x-gzip") => {
+                Encoding::Gzip
+            }
+            _ => continue,
+        };
+        let q = params
+            .find_map(|p| p.trim().strip_prefix("q="))
+            .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())
+            .unwrap_or(0.0);
+        let better = match best {
+            None => true,
+            Some((best_q, best_encoding)) => {
+                q > best_q
+                    || (q == best_q && encoding == Encoding::Brotli && best_encoding != encoding)
+            }
+        };
+        if q > 0.0 && better {
+            best = Some((q, encoding));
+        }
+    }
+    best.map(|(_, encoding)| encoding)
+}
+
+/// Response compression negotiated for one request.
+///
+/// Only active with the `compression` feature; `enabled` is `false` for
+/// handlers marked `compress = false`.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct Compression {
+    enabled: bool,
+    encoding: Option<Encoding>,
+}
+
+impl Compression {
+    pub fn negotiate(headers: &HeaderMap, enabled: bool) -> Self {
+        let enabled = enabled && cfg!(feature = "compression");
+        let encoding = if enabled {
+            headers
+                .get(ACCEPT_ENCODING)
+                .and_then(|v| v.to_str().ok())
+                .and_then(preferred_encoding)
+        } else {
+            None
+        };
+        Self { enabled, encoding }
+    }
+
+    /// Turns a serialized query/mutation response into one for
+    /// `vercel_runtime`, compressing it when negotiated.
+    pub fn vercel_response(self, res: http::Response<Bytes>) -> http::Response<ResponseBody> {
+        let (mut parts, body) = res.into_parts();
+        self.vary(&mut parts.headers);
+        let body = self.encode_body(&mut parts.headers, body);
+        http::Response::from_parts(parts, ResponseBody::from(body))
+    }
+
+    /// Compresses an axum response: JSON (or MessagePack/CBOR) bodies at once,
+    /// streams (SSE, NDJSON, bytes) chunk by chunk so every chunk is flushed to the client as it is sent.
+    pub async fn axum_response(self, res: Response) -> Response {
+        let (mut parts, body) = res.into_parts();
+        self.vary(&mut parts.headers);
+        if self.encoding.is_none() || parts.headers.contains_key(http::header::CONTENT_ENCODING) {
+            return Response::from_parts(parts, body);
+        }
+        let content_type = parts
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        if STREAM_MEDIA_TYPES
+            .iter()
+            .any(|media_type| content_type.starts_with(media_type))
+        {
+            let body = self.encode_stream(&mut parts.headers, body);
+            return Response::from_parts(parts, body);
+        }
+        if Format::from_media_type(content_type).is_none() {
+            return Response::from_parts(parts, body);
+        }
+        match http_body_util::BodyExt::collect(body).await {
+            Ok(body) => {
+                let body = self.encode_body(&mut parts.headers, body.to_bytes());
+                Response::from_parts(parts, Body::from(body))
+            }
+            Err(e) => {
+                let mut res = Response::new(Body::from(e.to_string()));
+                *res.status_mut() = http::StatusCode::INTERNAL_SERVER_ERROR;
+                res
+            }
+        }
+    }
+
+    /// Responses of a compressing handler depend on `Accept-Encoding`, which
+    /// caches must take into account even when this one was not compressed.
+    fn vary(self, headers: &mut HeaderMap) {
+        if self.enabled {
+            headers.append(VARY, http::HeaderValue::from_static("accept-encoding"));
+        }
+    }
+
+    /// Compresses a complete body above the size threshold.
+    fn encode_body(self, headers: &mut HeaderMap, body: Bytes) -> Bytes {
+        #[cfg(feature = "compression")]
+        if let Some(encoding) = self.encoding
+            && body.len() >= MIN_COMPRESS_BYTES
+            && let Ok(compressed) = codec::compress(encoding, &body)
+        {
+            set_encoding(headers, encoding);
+            return compressed;
+        }
+        #[cfg(not(feature = "compression"))]
+        let _ = headers;
+        body
+    }
+
+    fn encode_stream(self, headers: &mut HeaderMap, body: Body) -> Body {
+        #[cfg(feature = "compression")]
+        if let Some(encoding) = self.encoding {
+            set_encoding(headers, encoding);
+            return Body::new(codec::CompressedBody::new(body, encoding));
+        }
+        #[cfg(not(feature = "compression"))]
+        let _ = headers;
+        body
+    }
+}
+
+#[cfg(feature = "compression")]
+fn set_encoding(headers: &mut HeaderMap, encoding: Encoding) {
+    headers.insert(
+        http::header::CONTENT_ENCODING,
+        http::HeaderValue::from_static(encoding.name()),
+    );
+    headers.remove(http::header::CONTENT_LENGTH);
+}
+
+/// Compresses every response of a route, for handlers served through axum.
+#[doc(hidden)]
+pub async fn compress(enabled: bool, req: Request, next: Next) -> Response {
+    let compression = Compression::negotiate(req.headers(), enabled);
+    let res = next.run(req).await;
+    compression.axum_response(res).await
+}
+
+#[cfg(feature = "compression")]
+mod codec {
+    use std::io::{self, Write};
+    use std::pin::Pin;
+    use std::task::{Context, Poll, ready};
+
+    use axum::body::Body;
+    use hyper::body::{Bytes, Frame};
+
+    use super::Encoding;
+
+    /// Brotli quality for dynamic responses; 11 is too slow to run per request.
+    const BROTLI_QUALITY: u32 = 5;
+    const BROTLI_WINDOW: u32 = 22;
+
+    enum Encoder {
+        Gzip(flate2::write::GzEncoder<Vec<u8>>),
+        Brotli(Box<brotli::CompressorWriter<Vec<u8>>>),
+    }
+
+    impl Encoder {
+        fn new(encoding: Encoding) -> Self {
+            match encoding {
+                Encoding::Gzip => Self::Gzip(flate2::write::GzEncoder::new(
+                    Vec::new(),
+                    flate2::Compression::default(),
+                )),
+                Encoding::Brotli => Self::Brotli(Box::new(brotli::CompressorWriter::new(
+                    Vec::new(),
+                    4096,
+                    BROTLI_QUALITY,
+                    BROTLI_WINDOW,
+                ))),
+            }
+        }
+
+        /// Compresses `data` and flushes, returning everything encoded so far.
+        fn write_flush(&mut self, data: &[u8]) -> io::Result<Bytes> {
+            let buf = match self {
+                Self::Gzip(e) => {
+                    e.write_all(data)?;
+                    e.flush()?;
+                    e.get_mut()
+                }
+                Self::Brotli(e) => {
+                    e.write_all(data)?;
+                    e.flush()?;
+                    e.get_mut()
+                }
+            };
+            Ok(Bytes::from(std::mem::take(buf)))
+        }
+
+        fn finish(self) -> io::Result<Bytes> {
+            let buf = match self {
+                Self::Gzip(e) => e.finish()?,
+                Self::Brotli(e) => e.into_inner(),
+            };
+            Ok(Bytes::from(buf))
+        }
+    }
+
+    pub(super) fn compress(encoding: Encoding, data: &[u8]) -> io::Result<Bytes> {
+        let mut encoder = Encoder::new(encoding);
+        let mut out = encoder.write_flush(data)?.to_vec();
+        out.extend_from_slice(&encoder.finish()?);
+        Ok(Bytes::from(out))
+    }
+
+    /// Body compressing each data frame of `inner` as it arrives.
+    pub(super) struct CompressedBody {
+        inner: Body,
+        encoder: Option<Encoder>,
+    }
+
+    impl CompressedBody {
+        pub(super) fn new(inner: Body, encoding: Encoding) -> Self {
+            Self {
+                inner,
+                encoder: Some(Encoder::new(encoding)),
+            }
+        }
+    }
+
+    impl hyper::body::Body for CompressedBody {
+        type Data = Bytes;
+        type Error = axum::Error;
+
+        fn poll_frame(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Frame<Bytes>, Self::Error>>> {
+            let this = &mut *self;
+            loop {
+                let Some(encoder) = this.encoder.as_mut() else {
+                    return Poll::Ready(None);
+                };
+                let frame = match ready!(Pin::new(&mut this.inner).poll_frame(cx)) {
+                    Some(Ok(frame)) => frame,
+                    Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                    None => {
+                        let tail = this.encoder.take().map(Encoder::finish);
+                        return Poll::Ready(
+                            tail.map(|tail| tail.map(Frame::data).map_err(axum::Error::new)),
+                        );
+                    }
+                };
+                let data = match frame.into_data() {
+                    Ok(data) => data,
+                    Err(frame) => return Poll::Ready(Some(Ok(frame))),
+                };
+                match encoder.write_flush(&data) {
+                    Ok(out) if out.is_empty() => {}
+                    Ok(out) => return Poll::Ready(Some(Ok(Frame::data(out)))),
+                    Err(e) => return Poll::Ready(Some(Err(axum::Error::new(e)))),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Encoding, preferred_encoding};
+
+    #[test]
+    fn prefers_brotli_on_equal_quality() {
+        assert_eq!(
+            preferred_encoding("gzip, deflate, br"),
+            Some(Encoding::Brotli)
+        );
+        assert_eq!(preferred_encoding("gzip"), Some(Encoding::Gzip));
+    }
+
+    #[test]
+    fn honours_quality_values() {
+        assert_eq!(
+            preferred_encoding("br;q=0.5, gzip;q=0.8"),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(preferred_encoding("br;q=0, identity"), None);
+        assert_eq!(preferred_encoding(""), None);
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn compresses_large_json_only() {
+        use std::io::Read;
+
+        use http::header::{ACCEPT_ENCODING, CONTENT_ENCODING, VARY};
+
+        use super::Compression;
+
+        let mut headers = http::HeaderMap::new();
+        headers.insert(ACCEPT_ENCODING, "gzip".parse().unwrap());
+        let compression = Compression::negotiate(&headers, true);
+
+        let json = |value: &serde_json::Value| http::Response::new(value.to_string().into());
+        let small = compression.vercel_response(json(&serde_json::json!([1])));
+        assert!(small.headers().get(CONTENT_ENCODING).is_none());
+        assert_eq!(small.headers()[VARY], "accept-encoding");
+
+        let large = serde_json::json!(vec!["metaxy"; 500]);
+        let res = compression.vercel_response(json(&large));
+        assert_eq!(res.headers()[CONTENT_ENCODING], "gzip");
+        let body = http_body_util::BodyExt::collect(res.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        let mut json = String::new();
+        flate2::read::GzDecoder::new(&body[..])
+            .read_to_string(&mut json)
+            .unwrap();
+        assert_eq!(json, large.to_string());
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn stream_chunks_are_flushed() {
+        use std::io::Read;
+
+        use axum::body::Body;
+        use http_body_util::BodyExt;
+
+        let mut body = Body::new(super::codec::CompressedBody::new(
+            Body::from("data: 1\n\n"),
+            Encoding::Brotli,
+        ));
+        let first = body.frame().await.unwrap().unwrap().into_data().unwrap();
+        let mut rest = body.collect().await.unwrap().to_bytes().to_vec();
+        let mut all = first.to_vec();
+        all.append(&mut rest);
+
+        let mut text = String::new();
+        brotli::Decompressor::new(&first[..], 4096)
+            .read_to_string(&mut text)
+            .ok();
+        assert_eq!(text, "data: 1\n\n");
+
+        text.clear();
+        brotli::Decompressor::new(&all[..], 4096)
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "data: 1\n\n");
+    }
+}
@@ -0,0 +1,224 @@
+use http::header::{
+    ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE, VARY,
+};
+use http::{HeaderMap, HeaderValue};
+
+/// Request headers that are always allowed, in addition to [`Cors::allow_headers`].
+const BASE_ALLOW_HEADERS: &[&str] = &[
+    "Content-Type",
+    "Authorization",
+    "traceparent",
+    "X-Request-Id",
+    "X-Metaxy-Input",
+    "Last-Event-ID",
+];
+
+/// CORS policy applied to every response of a generated handler.
+///
+/// The policy is usually configured inline on the macro:
+///
+/// ```rust,ignore
+/// #[rpc_query(cors(origins = "https://app.example.com", credentials, headers = "X-Tenant"))]
+/// async fn me(headers: Headers) -> User { /* ... */ }
+/// ```
+///
+/// To share one policy across several handlers, declare it as a `const` and
+/// reference it by path:
+///
+/// ```rust,ignore
+/// pub const CORS: metaxy::Cors = metaxy::Cors {
+///     origins: &["https://app.example.com"],
+///     credentials: true,
+///     ..metaxy::Cors::DEFAULT
+/// };
+///
+/// #[rpc_query(cors = "crate::CORS")]
+/// async fn me(headers: Headers) -> User { /* ... */ }
+/// ```
+///
+/// Origin handling:
+///
+/// - `["*"]` (the default) allows any origin without credentials. Combined
+///   with `credentials` it allows no origin at all: echoing every `Origin`
+///   would let any site make cookie-authenticated requests. The macro rejects
+///   that combination; a `const` policy needs an explicit list instead.
+/// - An explicit list reflects the request `Origin` when it is in the list and
+///   omits `Access-Control-Allow-Origin` otherwise, so the browser blocks it.
+#[derive(Debug, Clone, Copy)]
+pub struct Cors {
+    /// Allowed origins, or `["*"]` for any origin.
+    pub origins: &'static [&'static str],
+    /// Sends `Access-Control-Allow-Credentials: true` so cookies are accepted.
+    pub credentials: bool,
+    /// Extra request headers allowed in addition to `Content-Type`, `Authorization`,
+    /// `traceparent`, `X-Request-Id`, `X-Metaxy-Input` and `Last-Event-ID`.
+    pub allow_headers: &'static [&'static str],
+    /// Response headers exposed to client-side JavaScript.
+    pub expose_headers: &'static [&'static str],
+    /// How long (in seconds) browsers may cache the preflight response.
+    pub max_age: u64,
+}
+
+impl Cors {
+    /// Any origin, no credentials, no extra headers, preflight cached for a day.
+    pub const DEFAULT: Cors = Cors {
+        origins: &["*"],
+        credentials: false,
+        allow_headers: &[],
+        expose_headers: &[],
+        max_age: 86400,
+    };
+
+    /// Writes the CORS headers for a request with the given `Origin` into `headers`.
+    #[doc(hidden)]
+    pub fn apply(&self, origin: Option<&HeaderValue>, headers: &mut HeaderMap) {
+        let any_origin = self.origins.contains(&"*");
+        let allow_origin = if any_origin {
+            // Never reflect an arbitrary origin on a credentialed policy.
+            (!self.credentials).then(|| HeaderValue::from_static("*"))
+        } else {
+            origin
+                .filter(|o| o.to_str().is_ok_and(|o| self.origins.contains(&o)))
+                .cloned()
+        };
+
+        if !any_origin {
+            headers.append(VARY, HeaderValue::from_static("Origin"));
+        }
+        let Some(allow_origin) = allow_origin else {
+            return;
+        };
+
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        headers.insert(
+            ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_static("GET, POST, OPTIONS"),
+        );
+        let allow_headers = BASE_ALLOW_HEADERS
+            .iter()
+            .chain(self.allow_headers)
+            .copied()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if let Ok(value) = HeaderValue::from_str(&allow_headers) {
+            headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, value);
+        }
+        if !self.expose_headers.is_empty()
+            && let Ok(value) = HeaderValue::from_str(&self.expose_headers.join(", "))
+        {
+            headers.insert(ACCESS_CONTROL_EXPOSE_HEADERS, value);
+        }
+        headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from(self.max_age));
+        if self.credentials {
+            headers.insert(
+                ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+    }
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{HeaderMap, HeaderValue};
+
+    use super::Cors;
+
+    fn apply(cors: &Cors, origin: Option<&str>) -> HeaderMap {
+        let origin = origin.map(|o| HeaderValue::from_str(o).unwrap());
+        let mut headers = HeaderMap::new();
+        cors.apply(origin.as_ref(), &mut headers);
+        headers
+    }
+
+    #[test]
+    fn default_allows_any_origin() {
+        let headers = apply(&Cors::DEFAULT, Some("https://a.com"));
+        assert_eq!(headers["access-control-allow-origin"], "*");
+        assert_eq!(
+            headers["access-control-allow-headers"],
+            "Content-Type, Authorization, traceparent, X-Request-Id, X-Metaxy-Input, Last-Event-ID"
+        );
+        assert_eq!(headers["access-control-max-age"], "86400");
+        assert!(headers.get("access-control-allow-credentials").is_none());
+        assert!(headers.get("vary").is_none());
+    }
+
+    #[test]
+    fn listed_origin_is_reflected() {
+        let cors = Cors {
+            origins: &["https://a.com", "https://b.com"],
+            ..Cors::DEFAULT
+        };
+        let headers = apply(&cors, Some("https://b.com"));
+        assert_eq!(headers["access-control-allow-origin"], "https://b.com");
+        assert_eq!(headers["vary"], "Origin");
+    }
+
+    #[test]
+    fn unlisted_origin_gets_no_cors_headers() {
+        let cors = Cors {
+            origins: &["https://a.com"],
+            ..Cors::DEFAULT
+        };
+        let headers = apply(&cors, Some("https://evil.com"));
+        assert!(headers.get("access-control-allow-origin").is_none());
+        assert!(headers.get("access-control-allow-methods").is_none());
+        assert_eq!(headers["vary"], "Origin");
+    }
+
+    #[test]
+    fn wildcard_with_credentials_allows_no_origin() {
+        let cors = Cors {
+            credentials: true,
+            ..Cors::DEFAULT
+        };
+        let headers = apply(&cors, Some("https://a.com"));
+        assert!(headers.get("access-control-allow-origin").is_none());
+        assert!(headers.get("access-control-allow-credentials").is_none());
+    }
+
+    #[test]
+    fn credentials_reflect_only_listed_origins() {
+        let cors = Cors {
+            origins: &["https://a.com"],
+            credentials: true,
+            ..Cors::DEFAULT
+        };
+        let headers = apply(&cors, Some("https://a.com"));
+        assert_eq!(headers["access-control-allow-origin"], "https://a.com");
+        assert_eq!(headers["access-control-allow-credentials"], "true");
+
+        let headers = apply(&cors, Some("https://evil.com"));
+        assert!(headers.get("access-control-allow-origin").is_none());
+        assert!(headers.get("access-control-allow-credentials").is_none());
+        assert_eq!(headers["vary"], "Origin");
+    }
+
+    #[test]
+    fn extra_and_exposed_headers() {
+        let cors = Cors {
+            allow_headers: &["X-Tenant"],
+            expose_headers: &["X-Request-Id", "ETag"],
+            max_age: 600,
+            ..Cors::DEFAULT
+        };
+        let headers = apply(&cors, None);
+        assert_eq!(
+            headers["access-control-allow-headers"],
+            "Content-Type, Authorization, traceparent, X-Request-Id, X-Metaxy-Input, Last-Event-ID, X-Tenant"
+        );
+        assert_eq!(
+            headers["access-control-expose-headers"],
+            "X-Request-Id, ETag"
+        );
+        assert_eq!(headers["access-control-max-age"], "600");
+    }
+}
@@ -0,0 +1,217 @@
+use axum::response::{IntoResponse, Response};
+use http::{Method, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Wraps a handler error so generated code can probe its capabilities.
+///
+/// Generated handlers call `(&ErrorProbe(&err)).__rpc_error_data()` with both
+/// [`SerializeErrorData`] and [`NoErrorData`] in scope. Method resolution picks
+/// the `Serialize`-based impl when the error type supports it and falls back to
+/// `None` otherwise, so `Display`-only errors keep compiling.
+#[doc(hidden)]
+pub struct ErrorProbe<'a, E>(pub &'a E);
+
+/// Serializes the error value into the `error.data` field of the response.
+#[doc(hidden)]
+pub trait SerializeErrorData {
+    fn __rpc_error_data(&self) -> Option<Value>;
+}
+
+impl<E: Serialize> SerializeErrorData for ErrorProbe<'_, E> {
+    fn __rpc_error_data(&self) -> Option<Value> {
+        serde_json::to_value(self.0).ok()
+    }
+}
+
+/// Fallback for error types that do not implement `Serialize`.
+#[doc(hidden)]
+pub trait NoErrorData {
+    fn __rpc_error_data(&self) -> Option<Value> {
+        None
+    }
+}
+
+impl<E> NoErrorData for &ErrorProbe<'_, E> {}
+
+/// Chooses the HTTP status code for a handler error.
+///
+/// By default every `Err` returned from a query or mutation becomes a `400`
+/// response. Implement this trait on your error type to map variants to more
+/// specific codes:
+///
+/// ```rust,ignore
+/// use metaxy::{rpc_query, RpcErrorStatus};
+///
+/// #[derive(Debug, serde::Serialize)]
+/// enum UserError {
+///     NotFound,
+///     Database(String),
+/// }
+///
+/// impl RpcErrorStatus for UserError {
+///     fn status(&self) -> u16 {
+///         match self {
+///             UserError::NotFound => 404,
+///             UserError::Database(_) => 500,
+///         }
+///     }
+/// }
+/// ```
+///
+/// Error types that do not implement the trait keep the `400` default.
+///
+/// Only client and server error codes (`400..=599`) are used as given; any
+/// other value, e.g. a `2xx` that would report the error as a success or a
+/// number that is not a valid status, becomes `500`.
+pub trait RpcErrorStatus {
+    /// HTTP status code of the error response. Defaults to `400`.
+    fn status(&self) -> u16 {
+        400
+    }
+}
+
+impl RpcErrorStatus for String {}
+
+impl RpcErrorStatus for &str {}
+
+/// Uses the [`RpcErrorStatus`] impl of the error type.
+#[doc(hidden)]
+pub trait CustomErrorStatus {
+    fn __rpc_error_status(&self) -> u16;
+}
+
+impl<E: RpcErrorStatus> CustomErrorStatus for ErrorProbe<'_, E> {
+    fn __rpc_error_status(&self) -> u16 {
+        match self.0.status() {
+            status @ 400..=599 => status,
+            _ => 500,
+        }
+    }
+}
+
+/// Fallback for error types that do not implement [`RpcErrorStatus`].
+#[doc(hidden)]
+pub trait DefaultErrorStatus {
+    fn __rpc_error_status(&self) -> u16 {
+        400
+    }
+}
+
+impl<E> DefaultErrorStatus for &ErrorProbe<'_, E> {}
+
+/// Builds the `error` object of an error response: `{ type, code, message }`.
+///
+/// `code` is one of the stable codes listed in the crate docs, e.g.
+/// `BAD_INPUT` or `HANDLER_ERROR`, so clients can tell failures apart
+/// without matching on `message`.
+#[doc(hidden)]
+pub fn error_object(code: &str, message: &str) -> Value {
+    serde_json::json!({ "type": "error", "code": code, "message": message })
+}
+
+/// The `405` response of a stream called with the wrong method, matching the
+/// one queries and mutations answer with.
+#[doc(hidden)]
+pub fn method_not_allowed(method: &Method, expected: &str) -> Response {
+    let message = format!("Method {method} not allowed, expected {expected}");
+    (
+        StatusCode::METHOD_NOT_ALLOWED,
+        axum::Json(serde_json::json!({ "error": error_object("METHOD_NOT_ALLOWED", &message) })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CustomErrorStatus, DefaultErrorStatus, ErrorProbe, NoErrorData, RpcErrorStatus,
+        SerializeErrorData, error_object,
+    };
+
+    #[test]
+    fn error_object_carries_code() {
+        assert_eq!(
+            error_object("TIMEOUT", "Handler timed out"),
+            serde_json::json!({ "type": "error", "code": "TIMEOUT", "message": "Handler timed out" })
+        );
+    }
+
+    #[test]
+    fn serializable_error_produces_data() {
+        #[derive(serde::Serialize)]
+        enum MyError {
+            NotFound { id: u32 },
+        }
+
+        let err = MyError::NotFound { id: 7 };
+        let data = ErrorProbe(&err).__rpc_error_data();
+        assert_eq!(data, Some(serde_json::json!({ "NotFound": { "id": 7 } })));
+    }
+
+    #[test]
+    fn string_error_produces_string_data() {
+        let err = String::from("boom");
+        let data = ErrorProbe(&err).__rpc_error_data();
+        assert_eq!(data, Some(serde_json::json!("boom")));
+    }
+
+    #[test]
+    fn non_serializable_error_produces_no_data() {
+        struct Opaque;
+
+        let err = Opaque;
+        let data = (&ErrorProbe(&err)).__rpc_error_data();
+        assert_eq!(data, None);
+    }
+
+    #[test]
+    fn custom_status_is_used() {
+        enum MyError {
+            NotFound,
+            Internal,
+        }
+
+        impl RpcErrorStatus for MyError {
+            fn status(&self) -> u16 {
+                match self {
+                    MyError::NotFound => 404,
+                    MyError::Internal => 500,
+                }
+            }
+        }
+
+        assert_eq!(ErrorProbe(&MyError::NotFound).__rpc_error_status(), 404);
+        assert_eq!(ErrorProbe(&MyError::Internal).__rpc_error_status(), 500);
+    }
+
+    #[test]
+    fn non_error_status_becomes_500() {
+        struct Status(u16);
+
+        impl RpcErrorStatus for Status {
+            fn status(&self) -> u16 {
+                self.0
+            }
+        }
+
+        for status in [0, 200, 204, 302, 399, 600, 1000] {
+            assert_eq!(ErrorProbe(&Status(status)).__rpc_error_status(), 500);
+        }
+        assert_eq!(ErrorProbe(&Status(599)).__rpc_error_status(), 599);
+    }
+
+    #[test]
+    fn string_error_uses_default_status() {
+        let err = String::from("boom");
+        assert_eq!(ErrorProbe(&err).__rpc_error_status(), 400);
+    }
+
+    #[test]
+    fn error_without_impl_falls_back_to_400() {
+        struct Opaque;
+
+        let err = Opaque;
+        assert_eq!((&ErrorProbe(&err)).__rpc_error_status(), 400);
+    }
+}
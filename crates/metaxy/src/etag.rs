@@ -0,0 +1,168 @@
+use http::header::{
+    ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS,
+    CONTENT_TYPE, ETAG,
+};
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use serde_json::Value;
+
+/// Applies `#[rpc_query(etag)]` to a handler response.
+///
+/// A `200` response gets a weak `ETag` hashed from its serialized body, unless
+/// the handler already set one through `RpcResponse::header`. When the tag
+/// matches the request's `If-None-Match`, the response becomes an empty
+/// `304 Not Modified` keeping its other headers (`Cache-Control`, cookies).
+#[doc(hidden)]
+pub fn etag_response(
+    if_none_match: Option<&HeaderValue>,
+    mut res: http::Response<Value>,
+) -> http::Response<Value> {
+    if res.status() != StatusCode::OK {
+        return res;
+    }
+    let tag = match res.headers().get(ETAG) {
+        Some(tag) => tag.clone(),
+        None => {
+            let tag = weak_etag(res.body());
+            res.headers_mut().insert(ETAG, tag.clone());
+            tag
+        }
+    };
+    if if_none_match.is_some_and(|header| matches(header, &tag)) {
+        *res.status_mut() = StatusCode::NOT_MODIFIED;
+        *res.body_mut() = Value::Null;
+        res.headers_mut().remove(CONTENT_TYPE);
+    }
+    res
+}
+
+/// Lets cross-origin clients send `If-None-Match` and read `ETag`, on
+/// responses whose origin the CORS policy allowed.
+#[doc(hidden)]
+pub fn expose_etag(headers: &mut HeaderMap) {
+    if !headers.contains_key(ACCESS_CONTROL_ALLOW_ORIGIN) {
+        return;
+    }
+    append_list(headers, ACCESS_CONTROL_ALLOW_HEADERS, "If-None-Match");
+    append_list(headers, ACCESS_CONTROL_EXPOSE_HEADERS, "ETag");
+}
+
+/// Adds `item` to the comma-separated list in header `name`.
+pub(crate) fn append_list(headers: &mut HeaderMap, name: HeaderName, item: &str) {
+    let value = match headers.get(&name).and_then(|v| v.to_str().ok()) {
+        Some(list) if !list.is_empty() => format!("{list}, {item}"),
+        _ => item.to_owned(),
+    };
+    if let Ok(value) = HeaderValue::from_str(&value) {
+        headers.insert(name, value);
+    }
+}
+
+/// `W/"<hash>"` of the serialized body. Weak, because compression changes the
+/// bytes on the wire but not the JSON they carry.
+fn weak_etag(body: &Value) -> HeaderValue {
+    let bytes = serde_json::to_vec(body).unwrap_or_default();
+    let hash = fnv1a(&bytes);
+    HeaderValue::from_str(&format!("W/\"{hash:016x}\"")).expect("hex is a valid header value")
+}
+
+/// 64-bit FNV-1a, stable across builds so every instance tags the same body alike.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Weak comparison of `tag` against an `If-None-Match` list, per RFC 9110.
+fn matches(if_none_match: &HeaderValue, tag: &HeaderValue) -> bool {
+    let (Ok(list), Ok(tag)) = (if_none_match.to_str(), tag.to_str()) else {
+        return false;
+    };
+    let opaque = |t: &str| t.trim().trim_start_matches("W/").to_owned();
+    let tag = opaque(tag);
+    list.split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{HeaderMap, HeaderValue};
+    use serde_json::json;
+
+    use super::{etag_response, expose_etag};
+
+    fn ok(body: serde_json::Value) -> http::Response<serde_json::Value> {
+        let mut res = http::Response::new(body);
+        res.headers_mut()
+            .insert("content-type", HeaderValue::from_static("application/json"));
+        res
+    }
+
+    #[test]
+    fn tags_ok_responses() {
+        let a = etag_response(None, ok(json!({ "n": 1 })));
+        let b = etag_response(None, ok(json!({ "n": 1 })));
+        let c = etag_response(None, ok(json!({ "n": 2 })));
+        let tag = &a.headers()["etag"];
+        assert!(tag.to_str().unwrap().starts_with("W/\""));
+        assert_eq!(tag, &b.headers()["etag"]);
+        assert_ne!(tag, &c.headers()["etag"]);
+    }
+
+    #[test]
+    fn matching_if_none_match_is_304() {
+        let tag = etag_response(None, ok(json!([1, 2]))).headers()["etag"].clone();
+        let strong = HeaderValue::from_str(tag.to_str().unwrap().trim_start_matches("W/")).unwrap();
+        for header in [
+            tag.clone(),
+            strong,
+            HeaderValue::from_str(&format!("\"other\", {}", tag.to_str().unwrap())).unwrap(),
+            HeaderValue::from_static("*"),
+        ] {
+            let res = etag_response(Some(&header), ok(json!([1, 2])));
+            assert_eq!(res.status(), 304, "{header:?}");
+            assert_eq!(res.body(), &serde_json::Value::Null);
+            assert!(res.headers().get("content-type").is_none());
+            assert_eq!(res.headers()["etag"], tag);
+        }
+
+        let res = etag_response(
+            Some(&HeaderValue::from_static("W/\"0\"")),
+            ok(json!([1, 2])),
+        );
+        assert_eq!(res.status(), 200);
+    }
+
+    #[test]
+    fn keeps_handler_etag_and_skips_errors() {
+        let mut res = ok(json!(1));
+        res.headers_mut()
+            .insert("etag", HeaderValue::from_static("\"v7\""));
+        let res = etag_response(Some(&HeaderValue::from_static("\"v7\"")), res);
+        assert_eq!(res.status(), 304);
+
+        let mut res = ok(json!({ "error": {} }));
+        *res.status_mut() = http::StatusCode::BAD_REQUEST;
+        let res = etag_response(Some(&HeaderValue::from_static("*")), res);
+        assert_eq!(res.status(), 400);
+        assert!(res.headers().get("etag").is_none());
+    }
+
+    #[test]
+    fn exposes_etag_to_allowed_origins() {
+        let mut headers = HeaderMap::new();
+        expose_etag(&mut headers);
+        assert!(headers.is_empty());
+
+        headers.insert("access-control-allow-origin", HeaderValue::from_static("*"));
+        headers.insert(
+            "access-control-allow-headers",
+            HeaderValue::from_static("Content-Type"),
+        );
+        expose_etag(&mut headers);
+        assert_eq!(
+            headers["access-control-allow-headers"],
+            "Content-Type, If-None-Match"
+        );
+        assert_eq!(headers["access-control-expose-headers"], "ETag");
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::net::IpAddr;
+
+use http::{Method, Uri};
+
+use crate::RequestParts;
+
+/// Extracts a handler parameter from the incoming request.
+///
+/// The built-in [`Cookies`], [`ClientIp`], [`RequestInfo`], [`VercelContext`]
+/// and [`LastEventId`] types are recognised by name. Custom extractors are marked with
+/// `#[from_request]` so the macro (and the CLI) do not mistake them for the
+/// RPC input:
+///
+/// ```rust,ignore
+/// use metaxy::{rpc_query, FromRequest, RequestParts};
+///
+/// struct Tenant(String);
+///
+/// impl FromRequest for Tenant {
+///     type Rejection = &'static str;
+///
+///     fn from_request(parts: &RequestParts) -> Result<Self, Self::Rejection> {
+///         parts
+///             .headers
+///             .get("x-tenant")
+///             .and_then(|v| v.to_str().ok())
+///             .map(|v| Tenant(v.to_owned()))
+///             .ok_or("missing X-Tenant header")
+///     }
+/// }
+///
+/// #[rpc_query]
+/// async fn dashboard(#[from_request] tenant: Tenant) -> String {
+///     tenant.0
+/// }
+/// ```
+///
+/// A rejection short-circuits the request with the same error response a
+/// handler `Err` would produce (honouring [`RpcErrorStatus`](crate::RpcErrorStatus)
+/// and `Serialize`).
+pub trait FromRequest: Sized {
+    /// Error returned when the value cannot be extracted.
+    type Rejection: fmt::Display;
+
+    /// Builds the value from the request metadata.
+    fn from_request(parts: &RequestParts) -> Result<Self, Self::Rejection>;
+}
+
+/// Cookies sent with the request, parsed from every `Cookie` header.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cookies(HashMap<String, String>);
+
+impl Cookies {
+    /// Returns the value of the cookie `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    /// Iterates over `(name, value)` pairs in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Number of cookies.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` when the request carried no cookies.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromRequest for Cookies {
+    type Rejection = Infallible;
+
+    fn from_request(parts: &RequestParts) -> Result<Self, Self::Rejection> {
+        let cookies = parts
+            .headers
+            .get_all(http::header::COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(';'))
+            .filter_map(|pair| {
+                let (name, value) = pair.trim().split_once('=')?;
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .unwrap_or(value);
+                Some((name.trim().to_owned(), value.to_owned()))
+            })
+            .collect();
+        Ok(Self(cookies))
+    }
+}
+
+/// IP address of the client, taken from the proxy headers set by Vercel.
+///
+/// Checks `x-vercel-forwarded-for`, `x-real-ip`, then the first entry of
+/// `x-forwarded-for`. `None` when no header holds a valid address (e.g. in
+/// local development without a proxy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl FromRequest for ClientIp {
+    type Rejection = Infallible;
+
+    fn from_request(parts: &RequestParts) -> Result<Self, Self::Rejection> {
+        Ok(Self(client_ip(&parts.headers)))
+    }
+}
+
+/// Reads the client IP from the proxy headers, in [`ClientIp`] order.
+pub(crate) fn client_ip(headers: &http::HeaderMap) -> Option<IpAddr> {
+    ["x-vercel-forwarded-for", "x-real-ip", "x-forwarded-for"]
+        .iter()
+        .filter_map(|name| headers.get(*name)?.to_str().ok())
+        .find_map(|v| v.split(',').next()?.trim().parse().ok())
+}
+
+/// Method, URI and raw query string of the request.
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+    /// HTTP method.
+    pub method: Method,
+    /// Full request URI (path and query).
+    pub uri: Uri,
+    /// Raw query string without the leading `?`, if any.
+    pub query: Option<String>,
+}
+
+impl FromRequest for RequestInfo {
+    type Rejection = Infallible;
+
+    fn from_request(parts: &RequestParts) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            method: parts.method.clone(),
+            uri: parts.uri.clone(),
+            query: parts.uri.query().map(str::to_owned),
+        })
+    }
+}
+
+/// Geolocation and deployment metadata added by Vercel's edge network.
+///
+/// Every field is `None` when the header is absent, e.g. when running locally.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VercelContext {
+    /// `x-vercel-id` — unique request id, prefixed with the edge and function regions.
+    pub request_id: Option<String>,
+    /// `VERCEL_REGION` — region the function is running in (e.g. `iad1`).
+    pub region: Option<String>,
+    /// `x-vercel-ip-country` — ISO 3166-1 country code.
+    pub country: Option<String>,
+    /// `x-vercel-ip-country-region` — ISO 3166-2 region code.
+    pub country_region: Option<String>,
+    /// `x-vercel-ip-city` — city name, URL-decoded.
+    pub city: Option<String>,
+    /// `x-vercel-ip-latitude`.
+    pub latitude: Option<f64>,
+    /// `x-vercel-ip-longitude`.
+    pub longitude: Option<f64>,
+    /// `x-vercel-ip-timezone` — IANA time zone name.
+    pub timezone: Option<String>,
+    /// `x-vercel-ip-postal-code`.
+    pub postal_code: Option<String>,
+}
+
+impl FromRequest for VercelContext {
+    type Rejection = Infallible;
+
+    fn from_request(parts: &RequestParts) -> Result<Self, Self::Rejection> {
+        let header = |name: &str| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_owned)
+        };
+        Ok(Self {
+            request_id: header("x-vercel-id"),
+            region: std::env::var("VERCEL_REGION").ok(),
+            country: header("x-vercel-ip-country"),
+            country_region: header("x-vercel-ip-country-region"),
+            city: header("x-vercel-ip-city").map(|city| {
+                percent_encoding::percent_decode_str(&city)
+                    .decode_utf8_lossy()
+                    .into_owned()
+            }),
+            latitude: header("x-vercel-ip-latitude").and_then(|v| v.parse().ok()),
+            longitude: header("x-vercel-ip-longitude").and_then(|v| v.parse().ok()),
+            timezone: header("x-vercel-ip-timezone"),
+            postal_code: header("x-vercel-ip-postal-code"),
+        })
+    }
+}
+
+/// The `Last-Event-ID` header a reconnecting stream client sends: the id of
+/// the last [`Event`](crate::Event) it received. `None` on a first connection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LastEventId(pub Option<String>);
+
+impl LastEventId {
+    /// The id as a number, e.g. a position in the automatic event sequence.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.0.as_deref().and_then(|id| id.trim().parse().ok())
+    }
+}
+
+impl FromRequest for LastEventId {
+    type Rejection = Infallible;
+
+    fn from_request(parts: &RequestParts) -> Result<Self, Self::Rejection> {
+        Ok(Self(
+            parts
+                .headers
+                .get("last-event-id")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_owned),
+        ))
+    }
+}
+
+// Lets generated stream handlers resume the event sequence without going
+// through the extractor machinery.
+impl<S: Send + Sync> axum::extract::FromRequestParts<S> for LastEventId {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut RequestParts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        <Self as FromRequest>::from_request(parts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use super::{ClientIp, Cookies, FromRequest, LastEventId, RequestInfo, VercelContext};
+    use crate::RequestParts;
+
+    fn parts(uri: &str, headers: &[(&str, &str)]) -> RequestParts {
+        let mut builder = http::Request::builder().uri(uri);
+        for (k, v) in headers {
+            builder = builder.header(*k, *v);
+        }
+        builder.body(()).unwrap().into_parts().0
+    }
+
+    #[test]
+    fn cookies_parsed_from_all_headers() {
+        let parts = parts(
+            "/",
+            &[
+                ("cookie", "session=abc; theme=\"dark\""),
+                ("cookie", "lang=en"),
+            ],
+        );
+        let cookies = Cookies::from_request(&parts).unwrap();
+        assert_eq!(cookies.get("session"), Some("abc"));
+        assert_eq!(cookies.get("theme"), Some("dark"));
+        assert_eq!(cookies.get("lang"), Some("en"));
+        assert_eq!(cookies.len(), 3);
+    }
+
+    #[test]
+    fn no_cookies_is_empty() {
+        let cookies = Cookies::from_request(&parts("/", &[])).unwrap();
+        assert!(cookies.is_empty());
+    }
+
+    #[test]
+    fn client_ip_prefers_vercel_header() {
+        let parts = parts(
+            "/",
+            &[
+                ("x-forwarded-for", "10.0.0.1, 10.0.0.2"),
+                ("x-vercel-forwarded-for", "203.0.113.7"),
+            ],
+        );
+        let ip = ClientIp::from_request(&parts).unwrap();
+        assert_eq!(ip.0, Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7))));
+    }
+
+    #[test]
+    fn client_ip_falls_back_to_forwarded_for() {
+        let parts = parts("/", &[("x-forwarded-for", "10.0.0.1, 10.0.0.2")]);
+        let ip = ClientIp::from_request(&parts).unwrap();
+        assert_eq!(ip.0, Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))));
+    }
+
+    #[test]
+    fn client_ip_missing() {
+        let ip = ClientIp::from_request(&parts("/", &[])).unwrap();
+        assert_eq!(ip.0, None);
+    }
+
+    #[test]
+    fn request_info_exposes_raw_query() {
+        let info = RequestInfo::from_request(&parts("/api/hello?input=%22x%22", &[])).unwrap();
+        assert_eq!(info.method, http::Method::GET);
+        assert_eq!(info.uri.path(), "/api/hello");
+        assert_eq!(info.query.as_deref(), Some("input=%22x%22"));
+    }
+
+    #[test]
+    fn vercel_context_reads_geo_headers() {
+        let parts = parts(
+            "/",
+            &[
+                ("x-vercel-id", "cdg1::iad1::abc"),
+                ("x-vercel-ip-country", "FR"),
+                ("x-vercel-ip-city", "Saint-%C3%89tienne"),
+                ("x-vercel-ip-latitude", "45.43"),
+            ],
+        );
+        let ctx = VercelContext::from_request(&parts).unwrap();
+        assert_eq!(ctx.request_id.as_deref(), Some("cdg1::iad1::abc"));
+        assert_eq!(ctx.country.as_deref(), Some("FR"));
+        assert_eq!(ctx.city.as_deref(), Some("Saint-Étienne"));
+        assert_eq!(ctx.latitude, Some(45.43));
+        assert_eq!(ctx.longitude, None);
+    }
+
+    #[test]
+    fn last_event_id_from_header() {
+        let resumed = LastEventId::from_request(&parts("/", &[("last-event-id", "17")])).unwrap();
+        assert_eq!(resumed.0.as_deref(), Some("17"));
+        assert_eq!(resumed.as_u64(), Some(17));
+
+        let named = LastEventId::from_request(&parts("/", &[("last-event-id", "a1")])).unwrap();
+        assert_eq!(named.as_u64(), None);
+        assert_eq!(
+            LastEventId::from_request(&parts("/", &[])).unwrap(),
+            LastEventId(None)
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use http::header::{ACCEPT, CONTENT_TYPE, VARY};
+use http::{HeaderMap, HeaderValue, StatusCode};
+use hyper::body::{Body, Bytes};
+use serde_json::Value;
+
+use crate::limits::{InputError, InputLimits};
+
+/// Media types recognized for each format in `Content-Type` and `Accept`.
+const MEDIA_TYPES: &[(&str, Format)] = &[
+    ("application/json", Format::Json),
+    ("application/msgpack", Format::MsgPack),
+    ("application/x-msgpack", Format::MsgPack),
+    ("application/vnd.msgpack", Format::MsgPack),
+    ("application/cbor", Format::Cbor),
+];
+
+/// Wire format of a request or response body.
+///
+/// JSON is always available; MessagePack and CBOR need the `msgpack` and
+/// `cbor` features. Binary input is transcoded to JSON before it is checked
+/// and deserialized, so it must be representable as JSON (no byte strings or
+/// non-string map keys).
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    MsgPack,
+    Cbor,
+}
+
+impl Format {
+    /// Whether a binary format is compiled in, making responses depend on `Accept`.
+    const NEGOTIATED: bool = cfg!(any(feature = "msgpack", feature = "cbor"));
+
+    /// The format of a `Content-Type` or `Accept` item, ignoring parameters.
+    pub(crate) fn from_media_type(media_type: &str) -> Option<Self> {
+        let essence = media_type.split(';').next().unwrap_or_default().trim();
+        MEDIA_TYPES
+            .iter()
+            .find(|(name, _)| essence.eq_ignore_ascii_case(name))
+            .map(|&(_, format)| format)
+    }
+
+    fn enabled(self) -> bool {
+        match self {
+            Self::Json => true,
+            Self::MsgPack => cfg!(feature = "msgpack"),
+            Self::Cbor => cfg!(feature = "cbor"),
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::MsgPack => "application/msgpack",
+            Self::Cbor => "application/cbor",
+        }
+    }
+
+    /// The format of a request body, from its `Content-Type`. Bodies that are
+    /// not MessagePack or CBOR are read as JSON whatever their `Content-Type`.
+    pub fn of_request(headers: &HeaderMap) -> Result<Self, InputError> {
+        let Some(content_type) = headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok()) else {
+            return Ok(Self::Json);
+        };
+        match Self::from_media_type(content_type) {
+            Some(format) if !format.enabled() => {
+                Err(InputError::UnsupportedFormat(content_type.to_owned()))
+            }
+            format => Ok(format.unwrap_or(Self::Json)),
+        }
+    }
+
+    /// Picks the response format from `Accept`: the enabled format with the
+    /// highest `q`, the first listed on a tie, JSON when none is accepted.
+    pub fn negotiate(headers: &HeaderMap) -> Self {
+        if !Self::NEGOTIATED {
+            return Self::Json;
+        }
+        let Some(accept) = headers.get(ACCEPT).and_then(|v| v.to_str().ok()) else {
+            return Self::Json;
+        };
+        let mut best: Option<(f32, Self)> = None;
+        for item in accept.split(',') {
+            let Some(format) = Self::from_media_type(item).filter(|f| f.enabled()) else {
+                continue;
+            };
+            let q = item
+                .split(';')
+                .skip(1)
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())
+                .unwrap_or(0.0);
+            if q > 0.0 && best.is_none_or(|(best_q, _)| q > best_q) {
+                best = Some((q, format));
+            }
+        }
+        best.map_or(Self::Json, |(_, format)| format)
+    }
+
+    /// Reads a request body in this format as JSON checked against `limits`.
+    /// `max_body` applies to the body as sent, `max_depth` to the decoded value.
+    pub(crate) async fn read_body<B>(
+        self,
+        body: B,
+        limits: &InputLimits,
+    ) -> Result<Bytes, InputError>
+    where
+        B: Body,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        if self == Self::Json {
+            return limits.read_body(body).await;
+        }
+        let raw = limits.read_raw(body).await?;
+        if raw.is_empty() {
+            return Ok(raw);
+        }
+        let json = serde_json::to_vec(&self.decode(&raw)?)
+            .map_err(|e| InputError::Decode(e.to_string()))?;
+        limits.check_depth(&json)?;
+        Ok(Bytes::from(json))
+    }
+
+    fn decode(self, bytes: &[u8]) -> Result<Value, InputError> {
+        let decoded = match self {
+            Self::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+            #[cfg(feature = "msgpack")]
+            Self::MsgPack => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+            #[cfg(feature = "cbor")]
+            Self::Cbor => ciborium::from_reader(bytes).map_err(|e| e.to_string()),
+            #[cfg(not(all(feature = "msgpack", feature = "cbor")))]
+            _ => Err(format!("{} is not enabled", self.content_type())),
+        };
+        decoded.map_err(InputError::Decode)
+    }
+
+    fn encode(self, body: &Value) -> Option<Vec<u8>> {
+        match self {
+            Self::Json => serde_json::to_vec(body).ok(),
+            #[cfg(feature = "msgpack")]
+            Self::MsgPack => rmp_serde::to_vec_named(body).ok(),
+            #[cfg(feature = "cbor")]
+            Self::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(body, &mut bytes).ok().map(|()| bytes)
+            }
+            #[cfg(not(all(feature = "msgpack", feature = "cbor")))]
+            _ => None,
+        }
+    }
+
+    /// Serializes a handler response. JSON bodies are sent in this format;
+    /// anything else (an empty preflight) is left as it is, and a `304` is
+    /// sent without a body or `Content-Type`.
+    pub fn serialize(self, res: http::Response<Value>) -> http::Response<Bytes> {
+        let (mut parts, body) = res.into_parts();
+        if Self::NEGOTIATED {
+            parts
+                .headers
+                .append(VARY, HeaderValue::from_static("accept"));
+        }
+        if parts.status == StatusCode::NOT_MODIFIED {
+            parts.headers.remove(CONTENT_TYPE);
+            return http::Response::from_parts(parts, Bytes::new());
+        }
+        let is_json = parts
+            .headers
+            .get(CONTENT_TYPE)
+            .is_some_and(|v| v.as_bytes().starts_with(b"application/json"));
+        let encoded = match self {
+            Self::Json => None,
+            format if is_json => format.encode(&body).map(|bytes| (format, bytes)),
+            _ => None,
+        };
+        let bytes = match encoded {
+            Some((format, bytes)) => {
+                let content_type = HeaderValue::from_static(format.content_type());
+                parts.headers.insert(CONTENT_TYPE, content_type);
+                bytes
+            }
+            None => serde_json::to_vec(&body).unwrap_or_default(),
+        };
+        http::Response::from_parts(parts, Bytes::from(bytes))
+    }
+}
+
+/// Reads a mutation body, or a query's marked `POST` body, in the format
+/// named by its `Content-Type`, as JSON checked against `limits`.
+#[doc(hidden)]
+pub async fn read_body_input<B>(
+    req: http::Request<B>,
+    limits: &InputLimits,
+) -> Result<Bytes, InputError>
+where
+    B: Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let format = Format::of_request(req.headers())?;
+    format.read_body(req.into_body(), limits).await
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderMap;
+    use serde_json::json;
+
+    use super::Format;
+    use crate::limits::InputError;
+
+    fn headers(name: &str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            http::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+            value.parse().unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn request_format_from_content_type() {
+        assert_eq!(Format::of_request(&HeaderMap::new()).unwrap(), Format::Json);
+        let json = headers("content-type", "application/json; charset=utf-8");
+        assert_eq!(Format::of_request(&json).unwrap(), Format::Json);
+        let text = headers("content-type", "text/plain");
+        assert_eq!(Format::of_request(&text).unwrap(), Format::Json);
+
+        let msgpack = headers("content-type", "application/x-msgpack");
+        #[cfg(feature = "msgpack")]
+        assert_eq!(Format::of_request(&msgpack).unwrap(), Format::MsgPack);
+        #[cfg(not(feature = "msgpack"))]
+        {
+            let err = Format::of_request(&msgpack).unwrap_err();
+            assert!(matches!(err, InputError::UnsupportedFormat(_)));
+            assert_eq!(err.status(), 415);
+            assert_eq!(err.code(), "UNSUPPORTED_MEDIA_TYPE");
+        }
+    }
+
+    #[test]
+    fn json_response_without_negotiation() {
+        let res = http::Response::builder()
+            .header("content-type", "application/json")
+            .body(json!({ "result": 1 }))
+            .unwrap();
+        let res = Format::Json.serialize(res);
+        assert_eq!(res.headers()["content-type"], "application/json");
+        assert_eq!(&res.body()[..], br#"{"result":1}"#);
+    }
+
+    #[test]
+    fn not_modified_has_no_body() {
+        let res = http::Response::builder()
+            .status(304)
+            .header("content-type", "application/json")
+            .header("etag", "W/\"1\"")
+            .body(serde_json::Value::Null)
+            .unwrap();
+        let res = Format::Json.serialize(res);
+        assert!(!res.headers().contains_key("content-type"));
+        assert_eq!(res.headers()["etag"], "W/\"1\"");
+        assert!(res.body().is_empty());
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn negotiates_by_accept_quality() {
+        let negotiate = |accept: &str| Format::negotiate(&headers("accept", accept));
+        assert_eq!(negotiate("application/msgpack"), Format::MsgPack);
+        assert_eq!(
+            negotiate("application/json, application/msgpack"),
+            Format::Json
+        );
+        assert_eq!(
+            negotiate("application/json;q=0.5, application/msgpack"),
+            Format::MsgPack
+        );
+        assert_eq!(negotiate("application/msgpack;q=0"), Format::Json);
+        assert_eq!(negotiate("*/*"), Format::Json);
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[tokio::test]
+    async fn msgpack_round_trip() {
+        let value = json!({ "points": [1, 2.5, -3], "name": "ü" });
+        let body = rmp_serde::to_vec_named(&value).unwrap();
+        let limits = crate::limits::InputLimits::new(64, 2);
+        let json = Format::MsgPack
+            .read_body(
+                http_body_util::Full::new(hyper::body::Bytes::from(body)),
+                &limits,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&json).unwrap(),
+            value
+        );
+
+        let deep = rmp_serde::to_vec(&json!([[[1]]])).unwrap();
+        let err = Format::MsgPack
+            .read_body(
+                http_body_util::Full::new(hyper::body::Bytes::from(deep)),
+                &limits,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, InputError::TooDeep { limit: 2 }));
+
+        let res = http::Response::builder()
+            .header("content-type", "application/json")
+            .body(value.clone())
+            .unwrap();
+        let res = Format::MsgPack.serialize(res);
+        assert_eq!(res.headers()["content-type"], "application/msgpack");
+        assert_eq!(res.headers()["vary"], "accept");
+        let decoded: serde_json::Value = rmp_serde::from_slice(res.body()).unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    #[cfg(feature = "cbor")]
+    #[tokio::test]
+    async fn cbor_round_trip() {
+        let value = json!({ "matrix": [[1, 0], [0, 1]] });
+        let mut body = Vec::new();
+        ciborium::into_writer(&value, &mut body).unwrap();
+        let json = Format::Cbor
+            .read_body(
+                http_body_util::Full::new(hyper::body::Bytes::from(body)),
+                &crate::limits::InputLimits::DEFAULT,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&json).unwrap(),
+            value
+        );
+        assert_eq!(
+            Format::negotiate(&headers("accept", "application/cbor")),
+            Format::Cbor
+        );
+    }
+}
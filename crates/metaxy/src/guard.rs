@@ -0,0 +1,65 @@
+use std::ops::{Deref, DerefMut};
+
+/// Request metadata (method, URI, headers, extensions) passed to guard functions.
+pub type RequestParts = http::request::Parts;
+
+/// Value produced by the handler's `guard` function.
+///
+/// Set `guard = "path::to::fn"` on the macro and add a `Guarded<T>` parameter
+/// to receive the guard's `Ok` value. The guard runs before input parsing; an
+/// `Err` short-circuits the request with the same error response a handler
+/// `Err` would produce (honouring [`RpcErrorStatus`](crate::RpcErrorStatus)
+/// and `Serialize` on the error type).
+///
+/// ```rust,ignore
+/// use metaxy::{rpc_query, Guarded, RequestParts, RpcErrorStatus};
+///
+/// struct User { id: u32 }
+///
+/// #[derive(Debug, serde::Serialize)]
+/// struct Unauthorized;
+///
+/// impl std::fmt::Display for Unauthorized { /* ... */ }
+///
+/// impl RpcErrorStatus for Unauthorized {
+///     fn status(&self) -> u16 { 401 }
+/// }
+///
+/// async fn require_user(parts: &RequestParts) -> Result<User, Unauthorized> {
+///     match parts.headers.get("authorization") {
+///         Some(_) => Ok(User { id: 1 }),
+///         None => Err(Unauthorized),
+///     }
+/// }
+///
+/// #[rpc_query(guard = "require_user")]
+/// async fn me(user: Guarded<User>) -> u32 {
+///     user.id
+/// }
+/// ```
+///
+/// When the handler also takes init state (`&T`), the guard receives it as a
+/// second argument: `async fn require_user(parts: &RequestParts, state: &T)`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Guarded<T>(pub T);
+
+impl<T> Guarded<T> {
+    /// Unwraps the guard value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Guarded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Guarded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
@@ -20,6 +20,16 @@
 //!     format!("Hello, {}!", name)
 //! }
 //! ```
+//!
+//! # Features
+//!
+//! - `compression` — gzip/brotli compression of JSON responses and streams,
+//!   negotiated via `Accept-Encoding`.
+//! - `msgpack` / `cbor` — MessagePack / CBOR request and response bodies for
+//!   queries and mutations, negotiated via `Content-Type` and `Accept`.
+//! - `tracing` — per-request spans and logs through the
+//!   [`tracing`](https://docs.rs/tracing) crate, linked to an incoming W3C
+//!   `traceparent` header.
 
 pub use metaxy_macro::{rpc_mutation, rpc_query, rpc_stream};
 
@@ -45,14 +55,63 @@ pub use serde::{Deserialize, Serialize};
 /// ```
 pub type Headers = http::HeaderMap;
 
+mod batch;
+mod compress;
+mod cors;
+mod error;
+mod etag;
+mod extract;
+mod format;
+mod guard;
+mod limits;
+mod panic;
+mod query;
+mod rate_limit;
+mod response;
+mod router;
+mod runtime;
 mod stream;
-pub use stream::{SendError, StreamSender};
+pub mod testing;
+mod trace;
+mod validate;
+pub use cors::Cors;
+pub use error::RpcErrorStatus;
+pub use extract::{ClientIp, Cookies, FromRequest, LastEventId, RequestInfo, VercelContext};
+pub use futures_core::Stream;
+pub use guard::{Guarded, RequestParts};
+pub use limits::{DEFAULT_MAX_BODY, DEFAULT_MAX_DEPTH};
+pub use rate_limit::{MemoryStore, RateLimit, RateLimitDecision, RateLimitKey, RateLimitStore};
+pub use response::RpcResponse;
+pub use router::serve;
+pub use stream::{ByteSender, Event, NdjsonSender, SendError, StreamSender};
+pub use validate::{FieldError, Validate, ValidationErrors};
 
 /// Hidden re-exports consumed by code generated by the proc macros.
 /// Not part of the public API — do not depend on these directly.
 #[doc(hidden)]
 pub mod __private {
+    pub use crate::compress::{Compression, compress};
+    pub use crate::error::{
+        CustomErrorStatus, DefaultErrorStatus, ErrorProbe, NoErrorData, SerializeErrorData,
+        error_object, method_not_allowed,
+    };
+    pub use crate::etag::{etag_response, expose_etag};
+    pub use crate::format::{Format, read_body_input};
+    pub use crate::limits::{InputError, InputLimits};
+    pub use crate::panic::catch_panic;
+    pub use crate::query::{is_body_query, read_query_input};
+    pub use crate::rate_limit::{
+        expose_retry_after, header_key, ip_key, missing_key_header, rate_limited,
+    };
+    pub use crate::response::{NoResponseMeta, ResponseMeta, ResponseProbe, WithResponseMeta};
+    pub use crate::router::{
+        Procedure, ProcedureKind, build_router, into_axum_response, serve_procedure,
+    };
+    pub use crate::runtime::{DEV_PORT_ENV, run};
+    pub use crate::stream::{Heartbeat, StreamEnd, next_item};
+    pub use crate::trace::{CurrentSpan, RequestSpan, trace_stream};
     pub use axum;
+    pub use http;
     pub use http_body_util;
     pub use hyper;
     pub use serde_json;
@@ -0,0 +1,249 @@
+use std::fmt;
+
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use hyper::body::{Body, Bytes};
+
+/// Default `max_body`: 1 MiB.
+pub const DEFAULT_MAX_BODY: usize = 1024 * 1024;
+
+/// Default `max_depth`: JSON arrays and objects nested 64 levels deep.
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// Size and nesting bounds enforced on a handler's input before it is
+/// deserialized, set with the `max_body` and `max_depth` attributes.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct InputLimits {
+    max_body: usize,
+    max_depth: usize,
+}
+
+impl InputLimits {
+    pub const DEFAULT: Self = Self::new(DEFAULT_MAX_BODY, DEFAULT_MAX_DEPTH);
+
+    pub const fn new(max_body: usize, max_depth: usize) -> Self {
+        Self {
+            max_body,
+            max_depth,
+        }
+    }
+
+    /// Checks raw JSON input (a decoded query parameter or a request body).
+    pub fn check(&self, json: &[u8]) -> Result<(), InputError> {
+        if json.len() > self.max_body {
+            return Err(InputError::TooLarge {
+                limit: self.max_body,
+            });
+        }
+        self.check_depth(json)
+    }
+
+    /// Checks only the nesting of JSON input, for JSON transcoded from a
+    /// binary body whose size was already checked.
+    pub(crate) fn check_depth(&self, json: &[u8]) -> Result<(), InputError> {
+        if exceeds_depth(json, self.max_depth) {
+            return Err(InputError::TooDeep {
+                limit: self.max_depth,
+            });
+        }
+        Ok(())
+    }
+
+    /// Reads a request body, giving up as soon as it grows past `max_body`,
+    /// then [`check`](Self::check)s it.
+    pub async fn read_body<B>(&self, body: B) -> Result<Bytes, InputError>
+    where
+        B: Body,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let bytes = self.read_raw(body).await?;
+        self.check(&bytes)?;
+        Ok(bytes)
+    }
+
+    /// Reads a request body, giving up as soon as it grows past `max_body`.
+    pub(crate) async fn read_raw<B>(&self, body: B) -> Result<Bytes, InputError>
+    where
+        B: Body,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let bytes = Limited::new(body, self.max_body)
+            .collect()
+            .await
+            .map_err(|e| {
+                if e.is::<LengthLimitError>() {
+                    InputError::TooLarge {
+                        limit: self.max_body,
+                    }
+                } else {
+                    InputError::Read(e.to_string())
+                }
+            })?
+            .to_bytes();
+        Ok(bytes)
+    }
+}
+
+/// Returns `true` when arrays and objects nest deeper than `max_depth`.
+///
+/// Brackets inside strings are skipped; the input is not otherwise validated,
+/// which is left to the deserializer.
+fn exceeds_depth(json: &[u8], max_depth: usize) -> bool {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for &byte in json {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > max_depth {
+                    return true;
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Why an input was rejected before deserialization.
+#[doc(hidden)]
+#[derive(Debug)]
+pub enum InputError {
+    /// The input is larger than `max_body` bytes.
+    TooLarge { limit: usize },
+    /// The input nests deeper than `max_depth`.
+    TooDeep { limit: usize },
+    /// The body could not be read.
+    Read(String),
+    /// The `input64` query parameter is not valid base64url-encoded UTF-8.
+    Encoding(String),
+    /// A MessagePack or CBOR body could not be decoded.
+    Decode(String),
+    /// The body's `Content-Type` names a format this build does not support.
+    UnsupportedFormat(String),
+}
+
+impl InputError {
+    /// `413` for oversized input, `415` for an unsupported format, `400` otherwise.
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::TooLarge { .. } => 413,
+            Self::UnsupportedFormat(_) => 415,
+            Self::TooDeep { .. } | Self::Read(_) | Self::Encoding(_) | Self::Decode(_) => 400,
+        }
+    }
+
+    /// Stable `error.code` sent to the client.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::TooLarge { .. } => "PAYLOAD_TOO_LARGE",
+            Self::TooDeep { .. } => "INPUT_TOO_COMPLEX",
+            Self::UnsupportedFormat(_) => "UNSUPPORTED_MEDIA_TYPE",
+            Self::Read(_) | Self::Encoding(_) | Self::Decode(_) => "BAD_INPUT",
+        }
+    }
+
+    /// The `{ "error": { .. } }` response body.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "error": {
+                "type": "error",
+                "code": self.code(),
+                "message": self.to_string(),
+            }
+        })
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLarge { limit } => write!(f, "Input exceeds the limit of {limit} bytes"),
+            Self::TooDeep { limit } => {
+                write!(f, "Input exceeds the maximum nesting depth of {limit}")
+            }
+            Self::Read(e) => write!(f, "Failed to read request body: {e}"),
+            Self::Encoding(e) => write!(f, "Invalid base64url input: {e}"),
+            Self::Decode(e) => write!(f, "Failed to decode request body: {e}"),
+            Self::UnsupportedFormat(content_type) => {
+                write!(f, "Unsupported Content-Type \"{content_type}\"")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+#[cfg(test)]
+mod tests {
+    use http_body_util::Full;
+    use hyper::body::Bytes;
+
+    use super::{InputError, InputLimits};
+
+    #[test]
+    fn accepts_input_within_limits() {
+        let limits = InputLimits::new(64, 2);
+        assert!(limits.check(br#"{"a":[1,2],"b":"[[[["}"#).is_ok());
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let limits = InputLimits::new(64, 2);
+        let err = limits.check(br#"{"a":[[1]]}"#).unwrap_err();
+        assert!(matches!(err, InputError::TooDeep { limit: 2 }));
+        assert_eq!(err.status(), 400);
+        assert_eq!(err.code(), "INPUT_TOO_COMPLEX");
+    }
+
+    #[test]
+    fn escaped_quotes_stay_inside_strings() {
+        let limits = InputLimits::new(64, 1);
+        assert!(limits.check(br#"["\"[[[", 1]"#).is_ok());
+    }
+
+    #[test]
+    fn rejects_oversized_input() {
+        let err = InputLimits::new(4, 64).check(b"\"hello\"").unwrap_err();
+        assert!(matches!(err, InputError::TooLarge { limit: 4 }));
+        assert_eq!(err.status(), 413);
+        assert_eq!(
+            err.to_json(),
+            serde_json::json!({
+                "error": {
+                    "type": "error",
+                    "code": "PAYLOAD_TOO_LARGE",
+                    "message": "Input exceeds the limit of 4 bytes",
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn read_body_stops_at_limit() {
+        let limits = InputLimits::new(4, 64);
+        let err = limits
+            .read_body(Full::new(Bytes::from_static(b"[1,2,3]")))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, InputError::TooLarge { limit: 4 }));
+
+        let bytes = limits
+            .read_body(Full::new(Bytes::from_static(b"[1]")))
+            .await
+            .unwrap();
+        assert_eq!(&bytes[..], b"[1]");
+    }
+}
@@ -0,0 +1,98 @@
+use std::any::Any;
+use std::future::Future;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::task::Poll;
+
+/// Message sent to clients in place of the panic message in release builds.
+const REDACTED: &str = "Internal server error";
+
+/// Runs a handler body, turning a panic into `Err` with the message for the client.
+///
+/// Without this a panic would abort the invocation and the client would get an
+/// opaque platform error without CORS headers. The panic message is printed by
+/// the panic hook as usual and, with the `tracing` feature, logged as an error.
+/// Debug builds send it to the client; release builds send
+/// `"Internal server error"` instead.
+#[doc(hidden)]
+pub async fn catch_panic<F: Future>(procedure: &str, fut: F) -> Result<F::Output, String> {
+    let mut fut = std::pin::pin!(fut);
+    std::future::poll_fn(
+        |cx| match catch_unwind(AssertUnwindSafe(|| fut.as_mut().poll(cx))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => {
+                let message = panic_message(payload.as_ref());
+                #[cfg(feature = "tracing")]
+                tracing::error!(procedure, panic = message, "handler panicked");
+                #[cfg(not(feature = "tracing"))]
+                let _ = procedure;
+                Poll::Ready(Err(client_message(message, cfg!(debug_assertions))))
+            }
+        },
+    )
+    .await
+}
+
+/// Extracts the message of a `panic!` with a string literal or formatted string.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "Handler panicked"
+    }
+}
+
+fn client_message(message: &str, debug: bool) -> String {
+    if debug {
+        format!("Handler panicked: {message}")
+    } else {
+        REDACTED.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn passes_through_output() {
+        assert_eq!(catch_panic("ok", async { 7 }).await, Ok(7));
+    }
+
+    #[tokio::test]
+    async fn catches_formatted_panic() {
+        let id = 3;
+        let result = catch_panic("boom", async move {
+            if id > 0 {
+                panic!("user {id} missing");
+            }
+        })
+        .await;
+        assert_eq!(result, Err("Handler panicked: user 3 missing".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn catches_panic_after_await() {
+        let result = catch_panic("later", async {
+            tokio::task::yield_now().await;
+            panic!("late");
+        })
+        .await;
+        assert_eq!(result, Err::<(), _>("Handler panicked: late".to_owned()));
+    }
+
+    #[test]
+    fn release_message_is_redacted() {
+        assert_eq!(client_message("secret", false), "Internal server error");
+    }
+
+    #[test]
+    fn non_string_payload_has_generic_message() {
+        assert_eq!(
+            panic_message(&*std::panic::catch_unwind(|| std::panic::panic_any(42)).unwrap_err()),
+            "Handler panicked"
+        );
+    }
+}
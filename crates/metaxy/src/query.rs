@@ -0,0 +1,129 @@
+use base64::Engine as _;
+use base64::alphabet::URL_SAFE;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use http::{HeaderMap, Method};
+use hyper::body::Body;
+
+use crate::format::read_body_input;
+use crate::limits::{InputError, InputLimits};
+
+/// Header marking a query sent as `POST`, with its input as the request body
+/// instead of the `input` query parameter. The generated client does this
+/// when the encoded input would make the URL too long.
+const INPUT_HEADER: &str = "x-metaxy-input";
+
+/// Base64url, with or without padding.
+const BASE64URL: GeneralPurpose = GeneralPurpose::new(
+    &URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Whether a request to a query is a `POST` carrying its input in the body.
+#[doc(hidden)]
+pub fn is_body_query(method: &Method, headers: &HeaderMap) -> bool {
+    method == Method::POST
+        && headers
+            .get(INPUT_HEADER)
+            .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"body"))
+}
+
+/// Reads the raw JSON input of a query, checked against `limits`.
+///
+/// The input comes from, in order: the body of a [`is_body_query`] request,
+/// the `input` query parameter (URL-encoded JSON) or the `input64` query
+/// parameter (base64url-encoded JSON). `None` means no input was sent.
+#[doc(hidden)]
+pub async fn read_query_input<B>(
+    req: http::Request<B>,
+    limits: &InputLimits,
+) -> Result<Option<Vec<u8>>, InputError>
+where
+    B: Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    if is_body_query(req.method(), req.headers()) {
+        let bytes = read_body_input(req, limits).await?;
+        return Ok((!bytes.is_empty()).then(|| bytes.to_vec()));
+    }
+
+    let query = req.uri().query().unwrap_or_default();
+    let mut pairs = url::form_urlencoded::parse(query.as_bytes());
+    let raw = match pairs.find(|(k, _)| k == "input" || k == "input64") {
+        Some((k, v)) if k == "input64" => {
+            let bytes = BASE64URL
+                .decode(v.as_bytes())
+                .map_err(|e| InputError::Encoding(e.to_string()))?;
+            String::from_utf8(bytes).map_err(|e| InputError::Encoding(e.to_string()))?
+        }
+        Some((_, v)) => v.into_owned(),
+        None => return Ok(None),
+    };
+    limits.check(raw.as_bytes())?;
+    Ok(Some(raw.into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{Method, Request};
+
+    use super::{is_body_query, read_query_input};
+    use crate::limits::{InputError, InputLimits};
+
+    async fn read(req: Request<String>) -> Result<Option<String>, InputError> {
+        let raw = read_query_input(req, &InputLimits::new(64, 4)).await?;
+        Ok(raw.map(|raw| String::from_utf8(raw).unwrap()))
+    }
+
+    fn get(uri: &str) -> Request<String> {
+        Request::get(uri).body(String::new()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn reads_input_parameter() {
+        let input = read(get("/q?input=%7B%22a%22%3A1%7D")).await.unwrap();
+        assert_eq!(input.as_deref(), Some(r#"{"a":1}"#));
+        assert_eq!(read(get("/q")).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn reads_base64url_input() {
+        // `-` and `_` replace standard base64's `+` and `/`.
+        let input = read(get("/q?input64=eyJhIjoiPj4-In0")).await.unwrap();
+        assert_eq!(input.as_deref(), Some(r#"{"a":">>>"}"#));
+        let input = read(get("/q?input64=eyJhIjoiw7w_In0=")).await.unwrap();
+        assert_eq!(input.as_deref(), Some(r#"{"a":"ü?"}"#));
+
+        let err = read(get("/q?input64=%%%")).await.unwrap_err();
+        assert!(matches!(err, InputError::Encoding(_)));
+        assert_eq!(err.code(), "BAD_INPUT");
+    }
+
+    #[tokio::test]
+    async fn reads_body_of_marked_post() {
+        let req = Request::post("/q?input=1")
+            .header("X-Metaxy-Input", "body")
+            .body(r#"{"a":2}"#.to_owned())
+            .unwrap();
+        assert!(is_body_query(req.method(), req.headers()));
+        assert_eq!(read(req).await.unwrap().as_deref(), Some(r#"{"a":2}"#));
+
+        let req = Request::post("/q").body(String::new()).unwrap();
+        assert!(!is_body_query(&Method::POST, req.headers()));
+    }
+
+    #[tokio::test]
+    async fn applies_limits_to_every_source() {
+        let long = "1".repeat(65);
+        let err = read(get(&format!("/q?input={long}"))).await.unwrap_err();
+        assert!(matches!(err, InputError::TooLarge { .. }));
+
+        let req = Request::post("/q")
+            .header("x-metaxy-input", "body")
+            .body("[[[[[1]]]]]".to_owned())
+            .unwrap();
+        assert!(matches!(
+            read(req).await.unwrap_err(),
+            InputError::TooDeep { .. }
+        ));
+    }
+}
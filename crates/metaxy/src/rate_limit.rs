@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use http::header::{
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS, CONTENT_TYPE, RETRY_AFTER,
+};
+use http::{HeaderMap, StatusCode};
+use serde_json::Value;
+
+/// Allowed request rate set by the `rate_limit` attribute: `requests` per `window`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Requests allowed per window, which is also the burst size.
+    pub requests: u32,
+    /// Length of the window.
+    pub window: Duration,
+}
+
+impl RateLimit {
+    /// Creates a limit of `requests` per `window`.
+    pub const fn new(requests: u32, window: Duration) -> Self {
+        Self { requests, window }
+    }
+}
+
+/// Outcome of [`RateLimitStore::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    /// The request may proceed.
+    Allow,
+    /// The request is rejected with `429`; the client may retry after `retry_after`.
+    Deny {
+        /// Time until a request would be allowed again, sent as `Retry-After`.
+        retry_after: Duration,
+    },
+}
+
+/// Backing store for the `rate_limit` attribute.
+///
+/// Handlers use a per-procedure [`MemoryStore`] by default, which only
+/// limits requests reaching the same function instance. Implement this trait
+/// over a shared store (Redis, Upstash, a database) and pass it with
+/// `store = "path::to::STATIC"` to enforce the limit across instances:
+///
+/// ```rust,ignore
+/// use metaxy::{rpc_mutation, RateLimit, RateLimitDecision, RateLimitStore};
+///
+/// struct RedisStore { /* ... */ }
+///
+/// impl RateLimitStore for RedisStore {
+///     async fn check(&self, key: &str, limit: RateLimit) -> RateLimitDecision {
+///         // e.g. INCR + EXPIRE on `key` ...
+///     }
+/// }
+///
+/// static LIMITS: RedisStore = RedisStore { /* ... */ };
+///
+/// #[rpc_mutation(rate_limit = "10/m", key = "ip", store = "crate::LIMITS")]
+/// async fn login(input: Credentials) -> Session { /* ... */ }
+/// ```
+pub trait RateLimitStore: Send + Sync {
+    /// Records one request for `key` and decides whether it is allowed.
+    ///
+    /// Keys are prefixed with the procedure name (`login:203.0.113.7`), so one
+    /// store can back several procedures.
+    fn check(&self, key: &str, limit: RateLimit) -> impl Future<Output = RateLimitDecision> + Send;
+}
+
+/// Identity used by `key = "guard"` to rate limit per guard value (e.g. per user).
+///
+/// ```rust,ignore
+/// impl metaxy::RateLimitKey for User {
+///     fn rate_limit_key(&self) -> String {
+///         self.id.to_string()
+///     }
+/// }
+///
+/// #[rpc_query(guard = "require_user", rate_limit = "100/h", key = "guard")]
+/// async fn report(user: Guarded<User>) -> Report { /* ... */ }
+/// ```
+pub trait RateLimitKey {
+    /// Returns the bucket key for this identity.
+    fn rate_limit_key(&self) -> String;
+}
+
+/// Buckets beyond which refilled buckets are dropped before adding a new one.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// In-memory token bucket store, the default for the `rate_limit` attribute.
+///
+/// Each key gets a bucket of `requests` tokens refilled continuously over
+/// `window`, so bursts up to the limit are allowed and the average rate is
+/// capped.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    buckets: Mutex<BTreeMap<String, Bucket>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Tokens available at `now`, capped at the bucket size.
+    fn available(&self, now: Instant, limit: RateLimit) -> f64 {
+        let refilled = now.duration_since(self.updated).as_secs_f64() * refill_rate(limit);
+        (self.tokens + refilled).min(f64::from(limit.requests))
+    }
+}
+
+/// Tokens added per second.
+fn refill_rate(limit: RateLimit) -> f64 {
+    f64::from(limit.requests) / limit.window.as_secs_f64()
+}
+
+impl MemoryStore {
+    /// Creates an empty store, usable in a `static`.
+    pub const fn new() -> Self {
+        Self {
+            buckets: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn take(&self, key: &str, limit: RateLimit, now: Instant) -> RateLimitDecision {
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        if buckets.len() >= PRUNE_THRESHOLD && !buckets.contains_key(key) {
+            let full = f64::from(limit.requests);
+            buckets.retain(|_, bucket| bucket.available(now, limit) < full);
+        }
+
+        let bucket = buckets.entry(key.to_owned()).or_insert(Bucket {
+            tokens: f64::from(limit.requests),
+            updated: now,
+        });
+        bucket.tokens = bucket.available(now, limit);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            RateLimitDecision::Allow
+        } else {
+            RateLimitDecision::Deny {
+                retry_after: Duration::from_secs_f64((1.0 - bucket.tokens) / refill_rate(limit)),
+            }
+        }
+    }
+}
+
+impl RateLimitStore for MemoryStore {
+    fn check(&self, key: &str, limit: RateLimit) -> impl Future<Output = RateLimitDecision> + Send {
+        std::future::ready(self.take(key, limit, Instant::now()))
+    }
+}
+
+/// Bucket key for `key = "ip"`, or `None` when the request carries no client
+/// IP header. Such requests are not limited rather than sharing one bucket.
+#[doc(hidden)]
+pub fn ip_key(headers: &HeaderMap) -> Option<String> {
+    crate::extract::client_ip(headers).map(|ip| ip.to_string())
+}
+
+/// Bucket key for `key = "header:<name>"`, or `None` when the header is
+/// missing. Such requests are rejected with [`missing_key_header`].
+#[doc(hidden)]
+pub fn header_key(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty())
+        .map(str::to_owned)
+}
+
+/// Builds the `429` response for a denied request.
+#[doc(hidden)]
+pub fn rate_limited(retry_after: Duration) -> http::Response<Value> {
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    let message = format!("Too many requests, retry after {secs}s");
+    let mut res = error_response(StatusCode::TOO_MANY_REQUESTS, "RATE_LIMITED", &message);
+    res.headers_mut().insert(RETRY_AFTER, secs.into());
+    res
+}
+
+/// Builds the `400` response for a request without the header named by
+/// `key = "header:<name>"`.
+#[doc(hidden)]
+pub fn missing_key_header(name: &str) -> http::Response<Value> {
+    let message = format!("Missing `{name}` header");
+    error_response(StatusCode::BAD_REQUEST, "BAD_INPUT", &message)
+}
+
+fn error_response(status: StatusCode, code: &str, message: &str) -> http::Response<Value> {
+    let body = serde_json::json!({ "error": crate::error::error_object(code, message) });
+    let mut res = http::Response::new(body);
+    *res.status_mut() = status;
+    res.headers_mut().insert(
+        CONTENT_TYPE,
+        http::HeaderValue::from_static("application/json"),
+    );
+    res
+}
+
+/// Lets cross-origin clients read `Retry-After`, on responses whose origin the
+/// CORS policy allowed.
+#[doc(hidden)]
+pub fn expose_retry_after(headers: &mut HeaderMap) {
+    if headers.contains_key(ACCESS_CONTROL_ALLOW_ORIGIN) && headers.contains_key(RETRY_AFTER) {
+        crate::etag::append_list(headers, ACCESS_CONTROL_EXPOSE_HEADERS, "Retry-After");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{
+        MemoryStore, RateLimit, RateLimitDecision, header_key, ip_key, missing_key_header,
+        rate_limited,
+    };
+
+    const LIMIT: RateLimit = RateLimit::new(2, Duration::from_secs(10));
+
+    #[test]
+    fn allows_burst_then_denies() {
+        let store = MemoryStore::new();
+        let now = Instant::now();
+        assert_eq!(store.take("a", LIMIT, now), RateLimitDecision::Allow);
+        assert_eq!(store.take("a", LIMIT, now), RateLimitDecision::Allow);
+        assert_eq!(
+            store.take("a", LIMIT, now),
+            RateLimitDecision::Deny {
+                retry_after: Duration::from_secs(5)
+            }
+        );
+        assert_eq!(store.take("b", LIMIT, now), RateLimitDecision::Allow);
+    }
+
+    #[test]
+    fn tokens_refill_over_window() {
+        let store = MemoryStore::new();
+        let now = Instant::now();
+        store.take("a", LIMIT, now);
+        store.take("a", LIMIT, now);
+        let later = now + Duration::from_secs(5);
+        assert_eq!(store.take("a", LIMIT, later), RateLimitDecision::Allow);
+        assert!(matches!(
+            store.take("a", LIMIT, later),
+            RateLimitDecision::Deny { .. }
+        ));
+    }
+
+    #[test]
+    fn keys_from_headers() {
+        let mut headers = http::HeaderMap::new();
+        assert_eq!(ip_key(&headers), None);
+        headers.insert("x-forwarded-for", "10.0.0.1, 10.0.0.2".parse().unwrap());
+        headers.insert("x-api-key", "k1".parse().unwrap());
+        headers.insert("x-empty", "".parse().unwrap());
+        assert_eq!(ip_key(&headers).as_deref(), Some("10.0.0.1"));
+        assert_eq!(header_key(&headers, "x-api-key").as_deref(), Some("k1"));
+        assert_eq!(header_key(&headers, "x-missing"), None);
+        assert_eq!(header_key(&headers, "x-empty"), None);
+    }
+
+    #[test]
+    fn missing_key_header_is_bad_input() {
+        let res = missing_key_header("x-api-key");
+        assert_eq!(res.status(), 400);
+        assert_eq!(res.body()["error"]["code"], "BAD_INPUT");
+        assert_eq!(res.body()["error"]["message"], "Missing `x-api-key` header");
+    }
+
+    #[test]
+    fn denied_response_has_retry_after() {
+        let res = rate_limited(Duration::from_millis(1500));
+        assert_eq!(res.status(), 429);
+        assert_eq!(res.headers()["retry-after"], "2");
+        assert_eq!(res.body()["error"]["code"], "RATE_LIMITED");
+    }
+}
@@ -0,0 +1,204 @@
+use http::header::{CACHE_CONTROL, SET_COOKIE};
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use serde::{Serialize, Serializer};
+
+/// Successful handler result with response metadata attached.
+///
+/// Return `RpcResponse<T>` (or `Result<RpcResponse<T>, E>`) from a query or
+/// mutation to change the success status, add headers and cookies, or
+/// override `Cache-Control` for this response. The body is still
+/// `{ "result": { "type": "response", "data": <T> } }` and the generated
+/// TypeScript sees plain `T`.
+///
+/// ```rust,ignore
+/// use metaxy::{rpc_mutation, RpcResponse};
+///
+/// #[rpc_mutation]
+/// async fn login(input: Credentials) -> Result<RpcResponse<User>, AuthError> {
+///     let (user, token) = authenticate(input).await?;
+///     Ok(RpcResponse::new(user)
+///         .cookie(format!("session={token}; HttpOnly; Secure; SameSite=Lax; Path=/"))
+///         .header("X-Request-Id", "abc"))
+/// }
+///
+/// #[rpc_mutation]
+/// async fn create_post(input: NewPost) -> RpcResponse<Post> {
+///     RpcResponse::new(save(input).await).status(201)
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RpcResponse<T> {
+    data: T,
+    meta: ResponseMeta,
+}
+
+impl<T> RpcResponse<T> {
+    /// Wraps the response value with no metadata (200, default headers).
+    pub fn new(data: T) -> Self {
+        Self {
+            data,
+            meta: ResponseMeta::default(),
+        }
+    }
+
+    /// Sets the success status code (e.g. `201`). Invalid codes are ignored.
+    pub fn status(mut self, status: u16) -> Self {
+        self.meta.status = StatusCode::from_u16(status).ok();
+        self
+    }
+
+    /// Appends a response header. Invalid names or values are ignored.
+    pub fn header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        HeaderValue: TryFrom<V>,
+    {
+        if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+            self.meta.headers.append(name, value);
+        }
+        self
+    }
+
+    /// Appends a `Set-Cookie` header with the given cookie string
+    /// (e.g. `"session=abc; HttpOnly; Path=/"`).
+    pub fn cookie(self, cookie: impl AsRef<str>) -> Self {
+        self.header(SET_COOKIE, cookie.as_ref())
+    }
+
+    /// Overrides the `Cache-Control` header, including the one set by the
+    /// `cache` attribute.
+    pub fn cache_control(mut self, value: impl AsRef<str>) -> Self {
+        self.meta.cache_control = HeaderValue::from_str(value.as_ref()).ok();
+        self
+    }
+
+    /// Returns the wrapped value.
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    /// Unwraps the response value, discarding the metadata.
+    pub fn into_data(self) -> T {
+        self.data
+    }
+}
+
+impl<T> From<T> for RpcResponse<T> {
+    fn from(data: T) -> Self {
+        Self::new(data)
+    }
+}
+
+/// Serializes as the wrapped value; metadata never reaches the body.
+impl<T: Serialize> Serialize for RpcResponse<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.data.serialize(serializer)
+    }
+}
+
+/// Status, headers and `Cache-Control` override carried by an [`RpcResponse`].
+#[doc(hidden)]
+#[derive(Debug, Clone, Default)]
+pub struct ResponseMeta {
+    status: Option<StatusCode>,
+    headers: HeaderMap,
+    cache_control: Option<HeaderValue>,
+}
+
+impl ResponseMeta {
+    /// Applies the metadata to a response built by the generated handler.
+    pub fn apply<B>(&self, res: &mut http::Response<B>) {
+        if let Some(status) = self.status {
+            *res.status_mut() = status;
+        }
+        for (name, value) in &self.headers {
+            res.headers_mut().append(name, value.clone());
+        }
+        if let Some(cache_control) = &self.cache_control {
+            res.headers_mut()
+                .insert(CACHE_CONTROL, cache_control.clone());
+        }
+    }
+}
+
+/// Wraps a handler's success value so generated code can look for response metadata.
+///
+/// Same autoref pattern as [`ErrorProbe`](crate::__private::ErrorProbe):
+/// [`RpcResponse`] values yield their [`ResponseMeta`], everything else `None`.
+#[doc(hidden)]
+pub struct ResponseProbe<'a, T>(pub &'a T);
+
+/// Returns the metadata of an [`RpcResponse`].
+#[doc(hidden)]
+pub trait WithResponseMeta<'a> {
+    fn __rpc_response_meta(&self) -> Option<&'a ResponseMeta>;
+}
+
+impl<'a, T> WithResponseMeta<'a> for ResponseProbe<'a, RpcResponse<T>> {
+    fn __rpc_response_meta(&self) -> Option<&'a ResponseMeta> {
+        Some(&self.0.meta)
+    }
+}
+
+/// Fallback for plain return values.
+#[doc(hidden)]
+pub trait NoResponseMeta<'a> {
+    fn __rpc_response_meta(&self) -> Option<&'a ResponseMeta> {
+        None
+    }
+}
+
+impl<'a, T> NoResponseMeta<'a> for &ResponseProbe<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{NoResponseMeta, ResponseProbe, RpcResponse, WithResponseMeta};
+
+    #[test]
+    fn serializes_as_inner_value() {
+        let res = RpcResponse::new(vec![1, 2]).status(201).header("x-a", "b");
+        assert_eq!(
+            serde_json::to_value(&res).unwrap(),
+            serde_json::json!([1, 2])
+        );
+    }
+
+    #[test]
+    fn meta_applied_to_response() {
+        let res = RpcResponse::new(())
+            .status(201)
+            .header("x-request-id", "abc")
+            .cookie("a=1; Path=/")
+            .cookie("b=2; HttpOnly")
+            .cache_control("no-store");
+        let meta = ResponseProbe(&res).__rpc_response_meta().unwrap();
+
+        let mut http_res = http::Response::builder()
+            .header("cache-control", "public, max-age=60")
+            .body(())
+            .unwrap();
+        meta.apply(&mut http_res);
+
+        assert_eq!(http_res.status(), 201);
+        assert_eq!(http_res.headers()["x-request-id"], "abc");
+        let cookies: Vec<_> = http_res.headers().get_all("set-cookie").iter().collect();
+        assert_eq!(cookies, ["a=1; Path=/", "b=2; HttpOnly"]);
+        assert_eq!(http_res.headers()["cache-control"], "no-store");
+    }
+
+    #[test]
+    fn invalid_header_ignored() {
+        let res = RpcResponse::new(()).header("bad header", "x").status(1000);
+        let meta = ResponseProbe(&res).__rpc_response_meta().unwrap();
+        let mut http_res = http::Response::new(());
+        meta.apply(&mut http_res);
+        assert_eq!(http_res.status(), 200);
+        assert!(http_res.headers().is_empty());
+    }
+
+    #[test]
+    fn plain_value_has_no_meta() {
+        let value = String::from("x");
+        assert!((&ResponseProbe(&value)).__rpc_response_meta().is_none());
+    }
+}
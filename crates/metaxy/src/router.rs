@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::Router;
+use axum::body::Body;
+use axum::extract::Request;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{MethodRouter, any};
+use http::StatusCode;
+use vercel_runtime::Error;
+
+use crate::cors::Cors;
+use crate::format::Format;
+
+/// Builds an [`axum::Router`] from handlers compiled in router mode.
+///
+/// Annotate each handler with the `router` flag (`#[rpc_query(router)]`,
+/// `#[rpc_mutation(router)]`, `#[rpc_stream(router)]`) so the macro emits a
+/// registrable procedure instead of a `main` function, then list the handlers
+/// by path:
+///
+/// ```rust,ignore
+/// use metaxy::{rpc_mutation, rpc_query};
+///
+/// #[rpc_query(router)]
+/// async fn hello(name: String) -> String {
+///     format!("Hello, {name}!")
+/// }
+///
+/// #[rpc_mutation(router)]
+/// async fn echo(input: EchoInput) -> EchoOutput { /* ... */ }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+///     metaxy::serve(metaxy::router![hello, echo, users::create]).await
+/// }
+/// ```
+///
+/// Every procedure is routed at both `/<name>` and `/api/<name>`, so the same
+/// router works as one consolidated Vercel function (rewrite `/api/(.*)` to
+/// it), a standalone server (`axum::serve`) or a test target
+/// (`tower::ServiceExt::oneshot`). Registering two procedures with the same
+/// name panics.
+///
+/// The router also answers `POST /api/_batch`, which runs several queries in
+/// one round trip. The body is a JSON array of
+/// `{ "procedure": "<name>", "input": <json> }`. The response holds one
+/// `{ "status", "result" | "error" }` entry per call, in order; a call
+/// naming a mutation or stream gets a `405` entry without being run. The
+/// generated client uses it when created with the `batch` option.
+///
+/// Every call sees the batch request's headers (`Cookie`, `Authorization`,
+/// `Origin`, `traceparent`, `X-Forwarded-For`, custom headers), except those
+/// describing the batch body and its encoding: `Accept`, `Accept-Encoding`,
+/// `Content-Type`, `Content-Length`, `Transfer-Encoding` and `If-None-Match`.
+/// Each procedure's own CORS policy still decides whether its item is
+/// returned. The batch response itself uses
+/// [`Cors::DEFAULT`](crate::Cors::DEFAULT), which allows any origin without
+/// credentials. When procedures use credentialed CORS, pass the same policy
+/// to the router so browsers can read batched results:
+///
+/// ```rust,ignore
+/// metaxy::router![cors = crate::CORS; me, users::list]
+/// ```
+#[macro_export]
+macro_rules! router {
+    (cors = $cors:expr; $($($segment:ident)::+),* $(,)?) => {
+        $crate::__private::build_router($cors, [
+            $($($segment)::+::__rpc_procedure()),*
+        ])
+    };
+    ($($($segment:ident)::+),* $(,)?) => {
+        $crate::router![cors = $crate::Cors::DEFAULT; $($($segment)::+),*]
+    };
+}
+
+/// Serves a [`router!`] as a Vercel function.
+///
+/// Like the `main` generated for a single handler, this binds the port given
+/// by `metaxy dev` when running locally.
+pub async fn serve(router: Router) -> Result<(), Error> {
+    let app = tower::ServiceBuilder::new()
+        .layer(vercel_runtime::axum::VercelLayer::new())
+        .service(router);
+    crate::runtime::run(app).await
+}
+
+/// Kind of a [`Procedure`]. Only queries can be called through the batch endpoint.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcedureKind {
+    Query,
+    Mutation,
+    Stream,
+}
+
+/// A handler compiled in router mode, ready to be registered by [`router!`].
+#[doc(hidden)]
+pub struct Procedure {
+    name: &'static str,
+    kind: ProcedureKind,
+    route: MethodRouter,
+}
+
+impl Procedure {
+    pub fn new(name: &'static str, kind: ProcedureKind, route: MethodRouter) -> Self {
+        Self { name, kind, route }
+    }
+
+    /// Replaces the route, e.g. to wrap it in a layer.
+    pub fn map_route(self, f: impl FnOnce(MethodRouter) -> MethodRouter) -> Self {
+        Self {
+            route: f(self.route),
+            ..self
+        }
+    }
+}
+
+/// Serves a single `testable` handler as a Vercel function, answering on any path.
+#[doc(hidden)]
+pub async fn serve_procedure(procedure: Procedure) -> Result<(), Error> {
+    serve(Router::new().fallback_service(procedure.route)).await
+}
+
+/// Routes every procedure at `/<name>` and `/api/<name>`, plus the batch
+/// endpoint at `/_batch` and `/api/_batch` answering with the `cors` policy.
+#[doc(hidden)]
+pub fn build_router(cors: Cors, procedures: impl IntoIterator<Item = Procedure>) -> Router {
+    let mut kinds = HashMap::new();
+    let router =
+        procedures
+            .into_iter()
+            .fold(Router::new(), |router, Procedure { name, kind, route }| {
+                kinds.insert(name, kind);
+                router
+                    .route(&format!("/{name}"), route.clone())
+                    .route(&format!("/api/{name}"), route)
+            });
+    let kinds = Arc::new(kinds);
+    let procedures = router.clone();
+    let batch = any(move |req: Request| {
+        crate::batch::serve_batch(procedures.clone(), kinds.clone(), cors, req)
+    });
+    router
+        .route("/_batch", batch.clone())
+        .route("/api/_batch", batch)
+}
+
+/// Converts a query/mutation handler result into an axum response in the
+/// negotiated `format`, mirroring how standalone functions serialize it.
+#[doc(hidden)]
+pub fn into_axum_response(
+    res: Result<http::Response<serde_json::Value>, Error>,
+    format: Format,
+) -> Response {
+    match res {
+        Ok(res) => {
+            let (parts, body) = format.serialize(res).into_parts();
+            Response::from_parts(parts, Body::from(body))
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            axum::Json(serde_json::json!({
+                "error": crate::error::error_object("INTERNAL", &e.to_string()),
+            })),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::routing::get;
+    use http::{Request, StatusCode};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    use super::{Procedure, ProcedureKind, build_router, into_axum_response};
+    use crate::cors::Cors;
+    use crate::format::Format;
+
+    async fn call(router: axum::Router, uri: &str) -> (StatusCode, String) {
+        let res = router
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = res.status();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    fn router() -> axum::Router {
+        build_router(
+            Cors::DEFAULT,
+            [
+                Procedure::new("hello", ProcedureKind::Query, get(|| async { "hello" })),
+                Procedure::new("time", ProcedureKind::Query, get(|| async { "time" })),
+            ],
+        )
+    }
+
+    #[tokio::test]
+    async fn routes_by_procedure_name() {
+        assert_eq!(call(router(), "/api/hello").await.1, "hello");
+        assert_eq!(call(router(), "/time?input=1").await.1, "time");
+        assert_eq!(call(router(), "/api/nope").await.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn converts_json_response() {
+        let res = http::Response::builder()
+            .status(201)
+            .header("x-a", "b")
+            .body(serde_json::json!({ "ok": true }))
+            .unwrap();
+        let res = into_axum_response(Ok(res), Format::Json);
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.headers()["x-a"], "b");
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], br#"{"ok":true}"#);
+    }
+
+    #[tokio::test]
+    async fn handler_error_becomes_500() {
+        let res = into_axum_response(Err("boom".into()), Format::Json);
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "INTERNAL");
+        assert_eq!(body["error"]["message"], "boom");
+    }
+}
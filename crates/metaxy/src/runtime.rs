@@ -0,0 +1,67 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use http::StatusCode;
+use hyper::server::conn::http1;
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+use tower::{Service, ServiceExt};
+use vercel_runtime::{AppState, Error, LogContext, Request, Response, ResponseBody};
+
+/// Environment variable set by `metaxy dev` to the port a function must listen on.
+pub const DEV_PORT_ENV: &str = "METAXY_DEV_PORT";
+
+/// Serves a generated handler.
+///
+/// Deployed functions (and `vercel dev`) go through [`vercel_runtime::run`],
+/// which always binds `127.0.0.1:3000`. Under `metaxy dev` several functions
+/// run side by side, so each one is started with [`DEV_PORT_ENV`] and serves
+/// plain HTTP on that port instead.
+pub async fn run<S>(service: S) -> Result<(), Error>
+where
+    S: Service<(AppState, Request), Response = Response<ResponseBody>, Error = Error>
+        + Send
+        + Clone
+        + 'static,
+    S::Future: Send + 'static,
+{
+    let Some(port) = std::env::var(DEV_PORT_ENV)
+        .ok()
+        .and_then(|port| port.parse::<u16>().ok())
+    else {
+        return vercel_runtime::run(service).await;
+    };
+
+    let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], port))).await?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let service = service.clone();
+        tokio::spawn(async move {
+            let serve = hyper::service::service_fn(move |req| {
+                let service = service.clone();
+                async move {
+                    let res = service.oneshot((AppState::new(log_context()), req)).await;
+                    Ok::<_, Infallible>(res.unwrap_or_else(|e| {
+                        let mut res = Response::new(ResponseBody::from(e.to_string()));
+                        *res.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                        res
+                    }))
+                }
+            });
+            // Client disconnects mid-response are expected (e.g. aborted streams).
+            let _ = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), serve)
+                .await;
+        });
+    }
+}
+
+#[cfg(unix)]
+fn log_context() -> LogContext {
+    LogContext::new(None, None, None)
+}
+
+#[cfg(not(unix))]
+fn log_context() -> LogContext {
+    LogContext::new()
+}
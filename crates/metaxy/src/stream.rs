@@ -1,8 +1,22 @@
 use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
+use futures_core::Stream;
 use hyper::body::Bytes;
 use serde::Serialize;
+use serde_json::Value;
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::extract::LastEventId;
+
+/// Content type of `#[rpc_stream(format = "ndjson")]` responses.
+pub(crate) const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Content type of `#[rpc_stream(format = "bytes")]` responses.
+pub(crate) const BYTES_CONTENT_TYPE: &str = "application/octet-stream";
 
 /// Error returned when the streaming channel is closed.
 #[derive(Debug)]
@@ -16,11 +30,99 @@ impl std::fmt::Display for SendError {
 
 impl std::error::Error for SendError {}
 
+/// An SSE event with an id and an optional name, sent with
+/// [`StreamSender::send_event`].
+///
+/// Without an explicit [`id`](Self::id) the event gets the next number of the
+/// sender's automatic sequence (`1`, `2`, ...). A client that loses the
+/// connection reconnects with the last id it saw as `Last-Event-ID`, and the
+/// sequence continues after it, so a handler resumes by skipping the events
+/// the client already has:
+///
+/// ```rust,ignore
+/// use metaxy::{rpc_stream, Event, LastEventId, StreamSender};
+///
+/// #[rpc_stream]
+/// async fn tokens(prompt: String, last: LastEventId, tx: StreamSender<String>) {
+///     let seen = last.as_u64().unwrap_or(0) as usize;
+///     for token in generate(&prompt).skip(seen) {
+///         tx.send_event(Event::new(token)).await.ok();
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Event<T> {
+    data: T,
+    id: Option<String>,
+    name: Option<String>,
+}
+
+impl<T> Event<T> {
+    /// Creates an unnamed event carrying `data`.
+    pub fn new(data: T) -> Self {
+        Self {
+            data,
+            id: None,
+            name: None,
+        }
+    }
+
+    /// Uses `id` instead of the next number of the automatic sequence.
+    /// Line breaks are removed.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(single_line(id.into()));
+        self
+    }
+
+    /// Names the event (its `event:` field), e.g. for `EventSource` listeners.
+    /// Line breaks are removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is `done` or `error`, which are reserved for the end
+    /// of the stream: clients would take the event for it.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        let name = single_line(name.into());
+        assert!(
+            !RESERVED_EVENT_NAMES.contains(&name.as_str()),
+            "event name `{name}` is reserved for the end of the stream"
+        );
+        self.name = Some(name);
+        self
+    }
+}
+
+/// Event names the generated clients treat as the end of the stream.
+const RESERVED_EVENT_NAMES: &[&str] = &["done", "error"];
+
+/// Strips the line breaks that would end an SSE field early.
+fn single_line(mut field: String) -> String {
+    field.retain(|c| c != '\n' && c != '\r');
+    field
+}
+
 /// A typed sender for streaming RPC responses.
 ///
 /// Wraps an internal channel and serializes each value as an SSE `data:` event
 /// before sending it to the client. The type parameter `T` carries the chunk
-/// type so that the CLI can extract it for TypeScript codegen.
+/// type so that the CLI can extract it for TypeScript codegen. Use
+/// [`send_event`](Self::send_event) for events with ids, which let a dropped
+/// client resume the stream.
+///
+/// A handler that waits between sends can stop early when the client goes
+/// away by racing its work against [`closed`](Self::closed):
+///
+/// ```rust,ignore
+/// #[rpc_stream(heartbeat = "15s")]
+/// async fn jobs(tx: StreamSender<Job>) {
+///     loop {
+///         tokio::select! {
+///             job = next_job() => { tx.send(job).await.ok(); }
+///             () = tx.closed() => break,
+///         }
+///     }
+/// }
+/// ```
 ///
 /// # Example
 ///
@@ -36,6 +138,7 @@ impl std::error::Error for SendError {}
 /// ```
 pub struct StreamSender<T: Serialize = ()> {
     tx: mpsc::Sender<Result<Bytes, std::io::Error>>,
+    next_id: AtomicU64,
     _marker: PhantomData<fn(T)>,
 }
 
@@ -45,10 +148,22 @@ impl<T: Serialize> StreamSender<T> {
     pub fn new(tx: mpsc::Sender<Result<Bytes, std::io::Error>>) -> Self {
         Self {
             tx,
+            next_id: AtomicU64::new(1),
             _marker: PhantomData,
         }
     }
 
+    /// Continues the automatic id sequence after a numeric `Last-Event-ID`.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn resume_after(self, last: &LastEventId) -> Self {
+        if let Some(last) = last.as_u64() {
+            self.next_id
+                .store(last.saturating_add(1), Ordering::Relaxed);
+        }
+        self
+    }
+
     /// Sends a serializable value as an SSE `data:` event.
     ///
     /// The value is serialized to JSON and formatted as:
@@ -72,6 +187,213 @@ impl<T: Serialize> StreamSender<T> {
             .await
             .map_err(|_| SendError)
     }
+
+    /// Resolves once the client has disconnected; every send fails from then on.
+    pub async fn closed(&self) {
+        self.tx.closed().await;
+    }
+
+    /// Whether the client has disconnected.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Sends an [`Event`] with an `id:` (and `event:`) field:
+    /// ```text
+    /// id: 3
+    /// event: progress
+    /// data: {"done":3}
+    /// ```
+    pub async fn send_event(&self, event: Event<T>) -> Result<(), SendError> {
+        let json = serde_json::to_string(&event.data).map_err(|_| SendError)?;
+        let id = event
+            .id
+            .unwrap_or_else(|| self.next_id.fetch_add(1, Ordering::Relaxed).to_string());
+        let mut frame = format!("id: {id}\n");
+        if let Some(name) = event.name {
+            frame.push_str(&format!("event: {name}\n"));
+        }
+        frame.push_str(&format!("data: {json}\n\n"));
+        self.tx
+            .send(Ok(Bytes::from(frame)))
+            .await
+            .map_err(|_| SendError)
+    }
+}
+
+/// A typed sender for `#[rpc_stream(format = "ndjson")]` handlers.
+///
+/// Writes each value as one line of JSON (`application/x-ndjson`), for
+/// consumers that read a stream line by line rather than as server-sent
+/// events. The generated client yields the parsed values.
+///
+/// ```rust,ignore
+/// #[rpc_stream(format = "ndjson")]
+/// async fn export(filter: Filter, tx: NdjsonSender<Order>) {
+///     for order in orders(&filter).await {
+///         tx.send(order).await.ok();
+///     }
+/// }
+/// ```
+pub struct NdjsonSender<T: Serialize = ()> {
+    tx: mpsc::Sender<Result<Bytes, std::io::Error>>,
+    _marker: PhantomData<fn(T)>,
+}
+
+impl<T: Serialize> NdjsonSender<T> {
+    /// Creates a new `NdjsonSender` wrapping the given channel.
+    #[doc(hidden)]
+    pub fn new(tx: mpsc::Sender<Result<Bytes, std::io::Error>>) -> Self {
+        Self {
+            tx,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sends a serializable value as one line of JSON:
+    /// ```text
+    /// {"token":"Hello"}\n
+    /// ```
+    pub async fn send(&self, data: T) -> Result<(), SendError> {
+        let mut line = serde_json::to_vec(&data).map_err(|_| SendError)?;
+        line.push(b'\n');
+        self.tx
+            .send(Ok(Bytes::from(line)))
+            .await
+            .map_err(|_| SendError)
+    }
+
+    /// Resolves once the client has disconnected; every send fails from then on.
+    pub async fn closed(&self) {
+        self.tx.closed().await;
+    }
+
+    /// Whether the client has disconnected.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+/// A sender of raw chunks for `#[rpc_stream(format = "bytes")]` handlers.
+///
+/// The response is `application/octet-stream` and every chunk is written as
+/// is, e.g. the rows of a CSV download. The generated client yields each
+/// chunk as a `Uint8Array`.
+///
+/// ```rust,ignore
+/// #[rpc_stream(format = "bytes")]
+/// async fn report(month: String, tx: ByteSender) {
+///     tx.send("date,total\n").await.ok();
+///     for row in rows(&month).await {
+///         tx.send(format!("{},{}\n", row.date, row.total)).await.ok();
+///     }
+/// }
+/// ```
+pub struct ByteSender {
+    tx: mpsc::Sender<Result<Bytes, std::io::Error>>,
+}
+
+impl ByteSender {
+    /// Creates a new `ByteSender` wrapping the given channel.
+    #[doc(hidden)]
+    pub fn new(tx: mpsc::Sender<Result<Bytes, std::io::Error>>) -> Self {
+        Self { tx }
+    }
+
+    /// Sends a chunk of bytes as is.
+    pub async fn send(&self, chunk: impl Into<Bytes>) -> Result<(), SendError> {
+        self.tx.send(Ok(chunk.into())).await.map_err(|_| SendError)
+    }
+
+    /// Resolves once the client has disconnected; every send fails from then on.
+    pub async fn closed(&self) {
+        self.tx.closed().await;
+    }
+
+    /// Whether the client has disconnected.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+/// Writes what a generated stream handler sends around the handler itself:
+/// heartbeats while it runs, then its `done` value or `error`.
+#[doc(hidden)]
+pub struct StreamEnd {
+    tx: mpsc::Sender<Result<Bytes, std::io::Error>>,
+}
+
+impl StreamEnd {
+    pub fn new(tx: &mpsc::Sender<Result<Bytes, std::io::Error>>) -> Self {
+        Self { tx: tx.clone() }
+    }
+
+    /// Sends an SSE comment every `every` until the returned guard is dropped,
+    /// so proxies do not cut a quiet stream.
+    pub fn heartbeat(&self, every: Duration) -> Heartbeat {
+        let tx = self.tx.clone();
+        Heartbeat(tokio::spawn(async move {
+            let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + every, every);
+            loop {
+                interval.tick().await;
+                if tx
+                    .send(Ok(Bytes::from_static(b": heartbeat\n\n")))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        }))
+    }
+
+    /// Sends the handler's return value as a `done` event.
+    pub async fn done<R: Serialize>(&self, result: &R) {
+        let data = serde_json::to_string(result).unwrap_or_else(|_| "null".into());
+        let _ = self
+            .tx
+            .send(Ok(Bytes::from(format!("event: done\ndata: {data}\n\n"))))
+            .await;
+    }
+
+    /// Sends an `error` event shaped like the body of an error response, plus
+    /// the status that response would have had.
+    pub async fn error(&self, status: u16, code: &str, message: &str, data: Option<Value>) {
+        let mut error = crate::error::error_object(code, message);
+        if let Some(data) = data {
+            error["data"] = data;
+        }
+        let event = serde_json::json!({ "status": status, "error": error });
+        let _ = self
+            .tx
+            .send(Ok(Bytes::from(format!("event: error\ndata: {event}\n\n"))))
+            .await;
+    }
+
+    /// Fails the response body, so the client sees the connection break.
+    /// NDJSON and byte streams have no event to carry an error in.
+    pub async fn abort(&self, message: &str) {
+        let _ = self
+            .tx
+            .send(Err(std::io::Error::other(message.to_owned())))
+            .await;
+    }
+}
+
+/// Polls the next item of a stream returned by an `impl Stream` handler.
+#[doc(hidden)]
+pub async fn next_item<S: Stream + ?Sized>(mut stream: Pin<&mut S>) -> Option<S::Item> {
+    std::future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await
+}
+
+/// Stops the heartbeat task when dropped.
+#[doc(hidden)]
+pub struct Heartbeat(JoinHandle<()>);
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
 }
 
 #[cfg(test)]
@@ -145,6 +467,156 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    #[should_panic(expected = "event name `done` is reserved")]
+    fn done_event_name_is_reserved() {
+        let _ = Event::new(1).name("done");
+    }
+
+    #[test]
+    #[should_panic(expected = "event name `error` is reserved")]
+    fn error_event_name_is_reserved() {
+        let _ = Event::new(1).name("err\nor");
+    }
+
+    #[tokio::test]
+    async fn send_event_numbers_events_in_sequence() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let sender: StreamSender<u32> = StreamSender::new(tx);
+
+        sender.send_event(Event::new(7)).await.unwrap();
+        sender
+            .send_event(Event::new(8).name("progress"))
+            .await
+            .unwrap();
+        sender
+            .send_event(Event::new(9).id("custom\nid"))
+            .await
+            .unwrap();
+        sender.send_event(Event::new(10)).await.unwrap();
+
+        let frames: Vec<Bytes> = [
+            rx.recv().await,
+            rx.recv().await,
+            rx.recv().await,
+            rx.recv().await,
+        ]
+        .into_iter()
+        .map(|frame| frame.unwrap().unwrap())
+        .collect();
+        assert_eq!(frames[0], Bytes::from("id: 1\ndata: 7\n\n"));
+        assert_eq!(
+            frames[1],
+            Bytes::from("id: 2\nevent: progress\ndata: 8\n\n")
+        );
+        assert_eq!(frames[2], Bytes::from("id: customid\ndata: 9\n\n"));
+        assert_eq!(frames[3], Bytes::from("id: 3\ndata: 10\n\n"));
+    }
+
+    #[tokio::test]
+    async fn sequence_resumes_after_last_event_id() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let sender: StreamSender<u32> =
+            StreamSender::new(tx).resume_after(&LastEventId(Some("41".into())));
+        sender.send_event(Event::new(1)).await.unwrap();
+        let frame = rx.recv().await.unwrap().unwrap();
+        assert_eq!(frame, Bytes::from("id: 42\ndata: 1\n\n"));
+    }
+
+    #[tokio::test]
+    async fn closed_resolves_when_the_client_disconnects() {
+        let (tx, rx) = mpsc::channel(16);
+        let sender: StreamSender<u32> = StreamSender::new(tx);
+        assert!(!sender.is_closed());
+
+        drop(rx);
+        sender.closed().await;
+        assert!(sender.is_closed());
+    }
+
+    #[tokio::test]
+    async fn ndjson_sender_writes_one_line_per_value() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let sender: NdjsonSender<serde_json::Value> = NdjsonSender::new(tx);
+
+        sender.send(serde_json::json!({ "n": 1 })).await.unwrap();
+        sender.send(serde_json::json!("two")).await.unwrap();
+
+        assert_eq!(
+            rx.recv().await.unwrap().unwrap(),
+            Bytes::from("{\"n\":1}\n")
+        );
+        assert_eq!(rx.recv().await.unwrap().unwrap(), Bytes::from("\"two\"\n"));
+    }
+
+    #[tokio::test]
+    async fn byte_sender_writes_chunks_as_is() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let sender = ByteSender::new(tx);
+
+        sender.send("a,b\n").await.unwrap();
+        sender.send(vec![0u8, 255]).await.unwrap();
+
+        assert_eq!(rx.recv().await.unwrap().unwrap(), Bytes::from("a,b\n"));
+        assert_eq!(
+            rx.recv().await.unwrap().unwrap(),
+            Bytes::from(vec![0u8, 255])
+        );
+
+        drop(rx);
+        assert!(sender.is_closed());
+        assert!(sender.send("late").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn stream_end_abort_fails_the_body() {
+        let (tx, mut rx) = mpsc::channel(16);
+        StreamEnd::new(&tx).abort("Handler timed out").await;
+
+        let err = rx.recv().await.unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "Handler timed out");
+    }
+
+    #[tokio::test]
+    async fn stream_end_sends_done_and_structured_error() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let end = StreamEnd::new(&tx);
+
+        end.done(&vec![1, 2]).await;
+        end.error(
+            409,
+            "HANDLER_ERROR",
+            "taken",
+            Some(serde_json::json!({ "kind": "Taken" })),
+        )
+        .await;
+
+        let done = rx.recv().await.unwrap().unwrap();
+        assert_eq!(done, Bytes::from("event: done\ndata: [1,2]\n\n"));
+        let error = rx.recv().await.unwrap().unwrap();
+        assert_eq!(
+            error,
+            Bytes::from(
+                "event: error\ndata: {\"error\":{\"code\":\"HANDLER_ERROR\",\"data\":{\"kind\":\"Taken\"},\"message\":\"taken\",\"type\":\"error\"},\"status\":409}\n\n"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn heartbeat_stops_when_dropped() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let heartbeat = StreamEnd::new(&tx).heartbeat(Duration::from_millis(5));
+        drop(tx);
+
+        for _ in 0..2 {
+            let beat = rx.recv().await.unwrap().unwrap();
+            assert_eq!(beat, Bytes::from(": heartbeat\n\n"));
+        }
+
+        drop(heartbeat);
+        while rx.recv().await.is_some() {}
+    }
+
     #[test]
     fn send_error_display() {
         let error = SendError;
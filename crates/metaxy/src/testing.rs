@@ -0,0 +1,444 @@
+//! In-process test harness for generated handlers.
+//!
+//! Mark a handler `testable` (or `router`) so the macro exposes its procedure
+//! in test builds, then call it through a [`TestClient`]:
+//!
+//! ```rust,ignore
+//! use metaxy::rpc_query;
+//!
+//! #[rpc_query(testable, init = "setup")]
+//! async fn greet(name: String, state: &AppState) -> String {
+//!     format!("{}, {name}!", state.greeting)
+//! }
+//!
+//! #[cfg(test)]
+//! mod tests {
+//!     use super::*;
+//!
+//!     #[tokio::test]
+//!     async fn greets() {
+//!         let state = AppState { greeting: "Hi".into() };
+//!         let client = metaxy::test_client![greet = state];
+//!         let res = client.query("greet", &"Ada").await;
+//!         assert_eq!(res.status(), 200);
+//!         assert_eq!(res.data::<String>(), "Hi, Ada!");
+//!     }
+//! }
+//! ```
+//!
+//! A `testable` handler still compiles to a regular Vercel function outside
+//! of tests. Helpers on [`TestResponse`] panic with the response body when it
+//! does not have the expected shape, which is what a failing test wants.
+
+use axum::Router;
+use axum::body::Body;
+use http::{HeaderMap, Method, StatusCode};
+use http_body_util::BodyExt;
+use hyper::body::Bytes;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tower::ServiceExt;
+
+use crate::FieldError;
+
+pub use http::Request;
+
+/// Builds a [`TestClient`] serving `testable` or `router` handlers in-process.
+///
+/// Handlers taking init state can be given a pre-built value with
+/// `name = expr`, which is used instead of running `init`. Each client keeps
+/// its own state, so tests may run in parallel with different states.
+/// Handlers listed without a state run `init` once per test binary.
+///
+/// ```rust,ignore
+/// let client = metaxy::test_client![hello, users::create = AppState::fake()];
+/// ```
+#[macro_export]
+macro_rules! test_client {
+    ($($($segment:ident)::+ $(= $state:expr)?),* $(,)?) => {
+        $crate::testing::TestClient::new($crate::__private::build_router($crate::Cors::DEFAULT, [
+            $($crate::__test_procedure!([$($segment)::+] $($state)?)),*
+        ]))
+    };
+}
+
+/// Selects the procedure constructor for [`test_client!`].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __test_procedure {
+    ([$($segment:ident)::+]) => {
+        $($segment)::+::__rpc_procedure()
+    };
+    ([$($segment:ident)::+] $state:expr) => {
+        $($segment)::+::__rpc_procedure_with_state($state)
+    };
+}
+
+/// Calls handlers in-process, without a network or the Vercel runtime.
+///
+/// Wraps the [`axum::Router`] built by [`test_client!`] or
+/// [`router!`](crate::router). Procedures are addressed by name, exactly as
+/// the generated TypeScript client does.
+#[derive(Debug, Clone)]
+pub struct TestClient {
+    router: Router,
+}
+
+impl TestClient {
+    /// Wraps a router of procedures.
+    pub fn new(router: Router) -> Self {
+        Self { router }
+    }
+
+    /// Calls a query with `GET /api/<procedure>?input=<json>`.
+    pub async fn query(&self, procedure: &str, input: &impl Serialize) -> TestResponse {
+        let input = to_json(input);
+        let uri = format!(
+            "/api/{procedure}?input={}",
+            utf8_percent_encode(&input, NON_ALPHANUMERIC)
+        );
+        self.send(
+            Request::get(uri)
+                .body(Body::empty())
+                .expect("valid request"),
+        )
+        .await
+    }
+
+    /// Calls a mutation with `POST /api/<procedure>` and a JSON body.
+    pub async fn mutation(&self, procedure: &str, input: &impl Serialize) -> TestResponse {
+        self.send(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/api/{procedure}"))
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(to_json(input))
+                .expect("valid request"),
+        )
+        .await
+    }
+
+    /// Calls a stream and waits for it to finish. Read the events with
+    /// [`TestResponse::chunks`] or [`TestResponse::events`], NDJSON with
+    /// [`TestResponse::lines`] and bytes with [`TestResponse::body`].
+    /// Streams declared with `method = "GET"` are called with [`query`](Self::query).
+    pub async fn stream(&self, procedure: &str, input: &impl Serialize) -> TestResponse {
+        self.mutation(procedure, input).await
+    }
+
+    /// Sends a hand-built request, e.g. to set headers or cookies.
+    pub async fn send<B: Into<Body>>(&self, request: Request<B>) -> TestResponse {
+        let res = self
+            .router
+            .clone()
+            .oneshot(request.map(Into::into))
+            .await
+            .unwrap_or_else(|e| match e {});
+        let (parts, mut body) = res.into_parts();
+        // Keep what arrived before a stream was aborted.
+        let mut bytes = Vec::new();
+        let mut aborted = false;
+        while let Some(frame) = body.frame().await {
+            match frame {
+                Ok(frame) => {
+                    if let Ok(data) = frame.into_data() {
+                        bytes.extend_from_slice(&data);
+                    }
+                }
+                Err(_) => {
+                    aborted = true;
+                    break;
+                }
+            }
+        }
+        TestResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body: Bytes::from(bytes),
+            aborted,
+        }
+    }
+}
+
+fn to_json(input: &impl Serialize) -> String {
+    serde_json::to_string(input).expect("failed to serialize input")
+}
+
+/// Fully buffered response returned by [`TestClient`].
+#[derive(Debug, Clone)]
+pub struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    aborted: bool,
+}
+
+impl TestResponse {
+    /// HTTP status code.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Response headers.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Value of the header `name`, if present and valid UTF-8.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)?.to_str().ok()
+    }
+
+    /// Raw response body.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Whether the body ended with an error, as an NDJSON or byte stream
+    /// does when its handler fails or times out.
+    pub fn aborted(&self) -> bool {
+        self.aborted
+    }
+
+    /// Response body as text.
+    pub fn text(&self) -> &str {
+        std::str::from_utf8(&self.body).expect("response body is not UTF-8")
+    }
+
+    /// Deserializes the whole body as JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.body)
+            .unwrap_or_else(|e| panic!("unexpected response body ({e}): {}", self.text()))
+    }
+
+    /// Deserializes `result.data` of a successful response.
+    ///
+    /// Panics with the error message when the handler failed.
+    pub fn data<T: DeserializeOwned>(&self) -> T {
+        let mut body: Value = self.json();
+        match body.pointer_mut("/result/data") {
+            Some(data) => serde_json::from_value(data.take())
+                .unwrap_or_else(|e| panic!("unexpected result data ({e}): {}", self.text())),
+            None => panic!("expected a successful response: {}", self.text()),
+        }
+    }
+
+    /// Returns the `error` object of a failed response.
+    ///
+    /// Panics when the handler succeeded.
+    pub fn error(&self) -> TestError {
+        let mut body: Value = self.json();
+        match body.get_mut("error") {
+            Some(error) => serde_json::from_value(error.take())
+                .unwrap_or_else(|e| panic!("unexpected error body ({e}): {}", self.text())),
+            None => panic!("expected an error response: {}", self.text()),
+        }
+    }
+
+    /// Parses the body as a stream of server-sent events.
+    pub fn events(&self) -> Vec<SseEvent> {
+        parse_sse(self.text())
+    }
+
+    /// Deserializes the `data:` payload of every unnamed event sent with
+    /// [`StreamSender::send`](crate::StreamSender::send), skipping named
+    /// events such as `error`.
+    pub fn chunks<T: DeserializeOwned>(&self) -> Vec<T> {
+        self.events()
+            .into_iter()
+            .filter(|event| event.event.is_none())
+            .map(|event| {
+                serde_json::from_str(&event.data)
+                    .unwrap_or_else(|e| panic!("unexpected stream chunk ({e}): {}", event.data))
+            })
+            .collect()
+    }
+
+    /// Deserializes every line of an NDJSON stream.
+    pub fn lines<T: DeserializeOwned>(&self) -> Vec<T> {
+        self.text()
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                serde_json::from_str(line)
+                    .unwrap_or_else(|e| panic!("unexpected stream line ({e}): {line}"))
+            })
+            .collect()
+    }
+
+    /// Deserializes the `done` event a stream handler's return value is sent as.
+    ///
+    /// Panics when the stream ended without one.
+    pub fn done<T: DeserializeOwned>(&self) -> T {
+        let event = self.named_event("done");
+        serde_json::from_str(&event.data)
+            .unwrap_or_else(|e| panic!("unexpected done event ({e}): {}", event.data))
+    }
+
+    /// Returns the `error` object of the `error` event a stream ended with.
+    ///
+    /// Panics when the stream ended without one.
+    pub fn stream_error(&self) -> TestError {
+        let event = self.named_event("error");
+        let mut data: Value = serde_json::from_str(&event.data)
+            .unwrap_or_else(|e| panic!("unexpected error event ({e}): {}", event.data));
+        serde_json::from_value(data["error"].take())
+            .unwrap_or_else(|e| panic!("unexpected error event ({e}): {}", event.data))
+    }
+
+    fn named_event(&self, name: &str) -> SseEvent {
+        self.events()
+            .into_iter()
+            .find(|event| event.event.as_deref() == Some(name))
+            .unwrap_or_else(|| panic!("expected an `{name}` event: {}", self.text()))
+    }
+}
+
+/// The `error` object of a failed response.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct TestError {
+    /// Stable error code, e.g. `BAD_INPUT` or `HANDLER_ERROR`.
+    #[serde(default)]
+    pub code: String,
+    /// Human-readable error message.
+    pub message: String,
+    /// Serialized handler error, when the error type implements `Serialize`.
+    #[serde(default)]
+    pub data: Option<Value>,
+    /// Failed fields of a `validate` rejection.
+    #[serde(default)]
+    pub fields: Vec<FieldError>,
+}
+
+/// A single server-sent event.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The `id:` of an [`Event`](crate::Event), `None` for plain data events.
+    pub id: Option<String>,
+    /// The `event:` name, `None` for plain data events.
+    pub event: Option<String>,
+    /// The `data:` lines, joined with `\n`.
+    pub data: String,
+}
+
+fn parse_sse(text: &str) -> Vec<SseEvent> {
+    let mut events = Vec::new();
+    for block in text.split("\n\n") {
+        let mut event = SseEvent::default();
+        let mut data = Vec::new();
+        for line in block.lines() {
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "id" => event.id = Some(value.to_owned()),
+                "event" => event.event = Some(value.to_owned()),
+                "data" => data.push(value),
+                _ => {}
+            }
+        }
+        if !data.is_empty() {
+            event.data = data.join("\n");
+            events.push(event);
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::routing::{get, post};
+    use http::StatusCode;
+
+    use super::{SseEvent, TestClient, parse_sse};
+    use crate::__private::{Procedure, ProcedureKind, build_router};
+    use crate::cors::Cors;
+
+    fn client() -> TestClient {
+        TestClient::new(build_router(
+            Cors::DEFAULT,
+            [
+                Procedure::new(
+                    "echo",
+                    ProcedureKind::Query,
+                    get(|uri: http::Uri| async move {
+                        let query = uri.query().unwrap_or_default().to_owned();
+                        axum::Json(serde_json::json!({
+                            "result": { "type": "response", "data": query }
+                        }))
+                    }),
+                ),
+                Procedure::new(
+                    "fail",
+                    ProcedureKind::Mutation,
+                    post(|| async {
+                        (
+                            StatusCode::UNPROCESSABLE_ENTITY,
+                            axum::Json(serde_json::json!({
+                                "error": {
+                                    "type": "error",
+                                    "message": "Validation failed",
+                                    "fields": [{ "path": "name", "message": "required" }]
+                                }
+                            })),
+                        )
+                    }),
+                ),
+                Procedure::new(
+                    "ticks",
+                    ProcedureKind::Stream,
+                    post(|| async { "data: 1\n\ndata: 2\n\nevent: error\ndata: \"boom\"\n\n" }),
+                ),
+            ],
+        ))
+    }
+
+    #[tokio::test]
+    async fn query_encodes_input() {
+        let res = client().query("echo", &"a b&c").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.data::<String>(), "input=%22a%20b%26c%22");
+    }
+
+    #[tokio::test]
+    async fn error_exposes_fields() {
+        let res = client().mutation("fail", &()).await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let error = res.error();
+        assert_eq!(error.message, "Validation failed");
+        assert_eq!(error.fields[0].path, "name");
+        assert_eq!(error.data, None);
+    }
+
+    #[tokio::test]
+    async fn stream_chunks_skip_named_events() {
+        let res = client().stream("ticks", &()).await;
+        assert_eq!(res.chunks::<u32>(), [1, 2]);
+        assert_eq!(res.events().len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected a successful response")]
+    fn data_panics_on_error() {
+        let res = super::TestResponse {
+            status: StatusCode::BAD_REQUEST,
+            headers: http::HeaderMap::new(),
+            body: r#"{"error":{"type":"error","message":"nope"}}"#.into(),
+            aborted: false,
+        };
+        let _: String = res.data();
+    }
+
+    #[test]
+    fn sse_joins_multiline_data() {
+        assert_eq!(
+            parse_sse(": comment\nid: 4\nevent: note\ndata: a\ndata:b\n\n"),
+            [SseEvent {
+                id: Some("4".into()),
+                event: Some("note".into()),
+                data: "a\nb".into(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::fmt;
+use std::future::Future;
+use std::time::Instant;
+
+use http::{HeaderMap, HeaderName, HeaderValue};
+
+/// Request id header read from the request and echoed on the response.
+const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Span around one request, created by every generated handler.
+///
+/// With the `tracing` feature each request is logged in a `rpc` span carrying
+/// the procedure name and kind, the request id, the W3C trace context from an
+/// incoming `traceparent` header, and, once the response is ready, its
+/// status and duration. Without the feature this only echoes `x-request-id`.
+#[doc(hidden)]
+#[derive(Debug, Clone)]
+pub struct RequestSpan {
+    request_id: Option<HeaderValue>,
+    start: Instant,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl RequestSpan {
+    /// Opens the span for a request to `procedure` of the given `kind`.
+    pub fn new(procedure: &'static str, kind: &'static str, headers: &HeaderMap) -> Self {
+        let request_id = headers
+            .get(&REQUEST_ID)
+            .or_else(|| headers.get("x-vercel-id"))
+            .cloned();
+        #[cfg(feature = "tracing")]
+        let span = {
+            let parent = headers
+                .get("traceparent")
+                .and_then(|v| v.to_str().ok())
+                .and_then(TraceParent::parse);
+            tracing::info_span!(
+                "rpc",
+                procedure,
+                kind,
+                request_id = request_id.as_ref().and_then(|v| v.to_str().ok()),
+                trace_id = parent.as_ref().map(|p| p.trace_id),
+                parent_id = parent.as_ref().map(|p| p.parent_id),
+                status = tracing::field::Empty,
+                duration_ms = tracing::field::Empty,
+            )
+        };
+        #[cfg(not(feature = "tracing"))]
+        let _ = (procedure, kind);
+        Self {
+            request_id,
+            start: Instant::now(),
+            #[cfg(feature = "tracing")]
+            span,
+        }
+    }
+
+    /// Runs `fut` inside the span.
+    pub async fn instrument<F: Future>(&self, fut: F) -> F::Output {
+        #[cfg(feature = "tracing")]
+        {
+            tracing::Instrument::instrument(fut, self.span.clone()).await
+        }
+        #[cfg(not(feature = "tracing"))]
+        {
+            fut.await
+        }
+    }
+
+    /// Records the response status and logs the request, with the error
+    /// message when `error` is set. Also echoes the request id.
+    pub fn finish(&self, status: u16, error: Option<&str>, headers: &mut HeaderMap) {
+        if let Some(id) = &self.request_id {
+            headers.insert(REQUEST_ID, id.clone());
+        }
+        #[cfg(feature = "tracing")]
+        {
+            let duration_ms = u64::try_from(self.start.elapsed().as_millis()).unwrap_or(u64::MAX);
+            self.span.record("status", status);
+            self.span.record("duration_ms", duration_ms);
+            let _entered = self.span.enter();
+            match (status, error) {
+                (500.., Some(error)) => tracing::error!(status, error, "request failed"),
+                (500.., None) => tracing::error!(status, "request failed"),
+                (400.., Some(error)) => tracing::warn!(status, error, "request rejected"),
+                (400.., None) => tracing::warn!(status, "request rejected"),
+                _ => tracing::info!(status, duration_ms, "request completed"),
+            }
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = (status, error, self.start);
+    }
+
+    /// Finishes a handler result, logging `error.message` from a JSON error
+    /// body or the error itself.
+    pub fn finish_result<E: fmt::Display>(
+        &self,
+        res: Result<http::Response<serde_json::Value>, E>,
+    ) -> Result<http::Response<serde_json::Value>, E> {
+        match res {
+            Ok(mut res) => {
+                let error = res
+                    .body()
+                    .pointer("/error/message")
+                    .and_then(serde_json::Value::as_str)
+                    .map(str::to_owned);
+                self.finish(res.status().as_u16(), error.as_deref(), res.headers_mut());
+                Ok(res)
+            }
+            Err(err) => {
+                self.finish(500, Some(&err.to_string()), &mut HeaderMap::new());
+                Err(err)
+            }
+        }
+    }
+}
+
+/// The span active when a stream opens, carried into its body task so that
+/// events emitted while streaming belong to the request.
+#[doc(hidden)]
+#[derive(Debug, Clone)]
+pub struct CurrentSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl CurrentSpan {
+    /// Captures the current span.
+    pub fn get() -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::current(),
+        }
+    }
+
+    /// Runs `fut` inside the captured span.
+    pub async fn instrument<F: Future>(self, fut: F) -> F::Output {
+        #[cfg(feature = "tracing")]
+        {
+            tracing::Instrument::instrument(fut, self.span).await
+        }
+        #[cfg(not(feature = "tracing"))]
+        {
+            fut.await
+        }
+    }
+}
+
+/// Serves a streaming procedure inside a [`RequestSpan`]; the logged duration
+/// is the time until the stream opened.
+#[doc(hidden)]
+pub async fn trace_stream(
+    procedure: &'static str,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let span = RequestSpan::new(procedure, "stream", req.headers());
+    let mut res = span.instrument(next.run(req)).await;
+    let status = res.status().as_u16();
+    span.finish(status, None, res.headers_mut());
+    res
+}
+
+/// Trace context from a W3C `traceparent` header:
+/// `00-<32 hex trace id>-<16 hex parent id>-<2 hex flags>`.
+#[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TraceParent<'a> {
+    trace_id: &'a str,
+    parent_id: &'a str,
+}
+
+#[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+impl<'a> TraceParent<'a> {
+    fn parse(header: &'a str) -> Option<Self> {
+        let mut parts = header.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let parent_id = parts.next()?;
+        let flags = parts.next()?;
+        let is_hex = |s: &str, len: usize| {
+            s.len() == len && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        };
+        let valid = is_hex(version, 2)
+            && version != "ff"
+            && is_hex(trace_id, 32)
+            && trace_id.bytes().any(|b| b != b'0')
+            && is_hex(parent_id, 16)
+            && parent_id.bytes().any(|b| b != b'0')
+            && is_hex(flags, 2)
+            && (version != "00" || parts.next().is_none());
+        valid.then_some(Self {
+            trace_id,
+            parent_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderMap;
+
+    use super::{RequestSpan, TraceParent};
+
+    #[test]
+    fn parses_traceparent() {
+        let parent =
+            TraceParent::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").unwrap();
+        assert_eq!(parent.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(parent.parent_id, "00f067aa0ba902b7");
+    }
+
+    #[test]
+    fn rejects_invalid_traceparent() {
+        for header in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+        ] {
+            assert_eq!(TraceParent::parse(header), None, "{header}");
+        }
+    }
+
+    #[test]
+    fn echoes_request_id() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", "req-1".parse().unwrap());
+        let span = RequestSpan::new("hello", "query", &headers);
+        let res = http::Response::new(serde_json::json!({
+            "error": { "type": "error", "message": "nope" }
+        }));
+        let res = span.finish_result::<String>(Ok(res)).unwrap();
+        assert_eq!(res.headers()["x-request-id"], "req-1");
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Business-rule validation for handler inputs.
+///
+/// Handlers annotated with `validate` (e.g. `#[rpc_mutation(validate)]`) call
+/// [`Validate::validate`] on the deserialized input before the function body
+/// runs. An `Err` short-circuits the request with a `422` response whose
+/// `error.fields` lists every failing field.
+///
+/// ```rust,ignore
+/// use metaxy::{rpc_mutation, Validate, ValidationErrors};
+///
+/// #[derive(serde::Deserialize)]
+/// struct SignupInput {
+///     email: String,
+///     age: u32,
+/// }
+///
+/// impl Validate for SignupInput {
+///     fn validate(&self) -> Result<(), ValidationErrors> {
+///         let mut errors = ValidationErrors::new();
+///         if !self.email.contains('@') {
+///             errors.add("email", "must be a valid email address");
+///         }
+///         if self.age < 18 {
+///             errors.add("age", "must be at least 18");
+///         }
+///         errors.into_result()
+///     }
+/// }
+///
+/// #[rpc_mutation(validate)]
+/// async fn signup(input: SignupInput) -> String { /* ... */ }
+/// ```
+pub trait Validate {
+    /// Checks the value, returning every constraint violation on failure.
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+/// A single failed constraint, addressed by a dotted field path (e.g. `address.zip`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// Path of the offending field.
+    pub path: String,
+    /// Human-readable description of the violation.
+    pub message: String,
+}
+
+/// Collection of [`FieldError`]s returned by [`Validate::validate`].
+///
+/// Serializes as a plain array of `{ "path", "message" }` objects.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    /// Creates an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a violation for `path`.
+    pub fn add(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.0.push(FieldError {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Returns `true` when no violation has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the recorded violations.
+    pub fn errors(&self) -> &[FieldError] {
+        &self.0
+    }
+
+    /// `Ok(())` when empty, `Err(self)` otherwise.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Validation failed")?;
+        for (i, err) in self.0.iter().enumerate() {
+            let sep = if i == 0 { ": " } else { "; " };
+            write!(f, "{sep}{}: {}", err.path, err.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+#[cfg(test)]
+mod tests {
+    use super::ValidationErrors;
+
+    #[test]
+    fn empty_errors_are_ok() {
+        assert_eq!(ValidationErrors::new().into_result(), Ok(()));
+    }
+
+    #[test]
+    fn errors_serialize_as_array() {
+        let mut errors = ValidationErrors::new();
+        errors.add("email", "invalid");
+        errors.add("address.zip", "too short");
+        assert_eq!(
+            serde_json::to_value(&errors).unwrap(),
+            serde_json::json!([
+                { "path": "email", "message": "invalid" },
+                { "path": "address.zip", "message": "too short" },
+            ])
+        );
+    }
+
+    #[test]
+    fn display_lists_every_field() {
+        let mut errors = ValidationErrors::new();
+        errors.add("email", "invalid");
+        errors.add("age", "must be at least 18");
+        assert_eq!(
+            errors.to_string(),
+            "Validation failed: email: invalid; age: must be at least 18"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use metaxy::testing::Request;
+use metaxy::{Headers, StreamSender, rpc_mutation, rpc_query, rpc_stream};
+use serde_json::{Value, json};
+
+#[rpc_query(testable)]
+async fn double(n: u32) -> u32 {
+    n * 2
+}
+
+#[rpc_query(testable)]
+async fn whoami(headers: Headers) -> Option<String> {
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned)
+}
+
+#[rpc_query(testable, cors(origins = "https://admin.example.com"))]
+async fn admin_stats() -> u32 {
+    7
+}
+
+#[rpc_mutation(testable)]
+async fn reset() -> bool {
+    true
+}
+
+#[rpc_stream(testable, method = "GET", heartbeat = "1s")]
+async fn ticks(tx: StreamSender<u32>) {
+    while tx.send(1).await.is_ok() {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+}
+
+fn batch(calls: &Value, origin: Option<&str>) -> Request<String> {
+    let mut req = Request::post("/api/_batch")
+        .header("content-type", "application/json")
+        .header("authorization", "Bearer t");
+    if let Some(origin) = origin {
+        req = req.header("origin", origin);
+    }
+    req.body(calls.to_string()).unwrap()
+}
+
+#[tokio::test]
+async fn batch_runs_queries_with_request_headers() {
+    let client = metaxy::test_client![double, whoami, reset];
+    let calls = json!([
+        { "procedure": "double", "input": 21 },
+        { "procedure": "whoami" },
+        { "procedure": "double", "input": "x" },
+        { "procedure": "reset" },
+    ]);
+    let res = client.send(batch(&calls, None)).await;
+    assert_eq!(res.status(), 200);
+    let items = res.json::<Value>();
+    assert_eq!(items[0]["result"]["data"], 42);
+    assert_eq!(items[1]["result"]["data"], "Bearer t");
+    assert_eq!(items[2]["status"], 400);
+    assert_eq!(items[3]["status"], 405);
+}
+
+#[tokio::test]
+async fn batch_keeps_procedure_cors_policies() {
+    let client = metaxy::test_client![double, admin_stats];
+    let calls = json!([{ "procedure": "double", "input": 1 }, { "procedure": "admin_stats" }]);
+
+    let res = client
+        .send(batch(&calls, Some("https://evil.example.com")))
+        .await;
+    assert_eq!(res.header("access-control-allow-origin"), Some("*"));
+    let items = res.json::<Value>();
+    assert_eq!(items[0]["result"]["data"], 2);
+    assert_eq!(items[1]["status"], 403);
+
+    let res = client
+        .send(batch(&calls, Some("https://admin.example.com")))
+        .await;
+    assert_eq!(res.json::<Value>()[1]["result"]["data"], 7);
+}
+
+#[tokio::test]
+async fn batch_rejects_get_streams_without_calling_them() {
+    let client = metaxy::test_client![double, ticks];
+    let calls = json!([{ "procedure": "ticks" }, { "procedure": "double", "input": 2 }]);
+    let res = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        client.send(batch(&calls, None)),
+    )
+    .await
+    .expect("batch should not wait for the stream");
+    let items = res.json::<Value>();
+    assert_eq!(items[0]["status"], 405);
+    assert_eq!(items[0]["error"]["code"], "METHOD_NOT_ALLOWED");
+    assert_eq!(items[1]["result"]["data"], 4);
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "compression")]
+
+use std::io::Read;
+
+use metaxy::testing::{Request, TestResponse};
+use metaxy::{StreamSender, rpc_query, rpc_stream};
+
+#[rpc_query(testable)]
+async fn report() -> Vec<String> {
+    vec!["metaxy".into(); 500]
+}
+
+#[rpc_query(testable, compress = false)]
+async fn raw_report() -> Vec<String> {
+    vec!["metaxy".into(); 500]
+}
+
+#[rpc_stream(testable)]
+async fn ticks(tx: StreamSender<u32>) {
+    for n in 0..3 {
+        tx.send(n).await.ok();
+    }
+}
+
+fn gunzip(res: &TestResponse) -> String {
+    let mut text = String::new();
+    flate2::read::GzDecoder::new(res.body())
+        .read_to_string(&mut text)
+        .unwrap();
+    text
+}
+
+#[tokio::test]
+async fn large_json_is_compressed_when_accepted() {
+    let client = metaxy::test_client![report, raw_report];
+    let get = |uri: &str| {
+        Request::get(uri)
+            .header("accept-encoding", "gzip")
+            .body(())
+            .unwrap()
+    };
+
+    let res = client.send(get("/api/report?input=null")).await;
+    assert_eq!(res.header("content-encoding"), Some("gzip"));
+    // With `msgpack`/`cbor`, the response also varies by `Accept`.
+    let vary = res.headers().get_all("vary");
+    assert!(vary.iter().any(|v| v == "accept-encoding"));
+    let body: serde_json::Value = serde_json::from_str(&gunzip(&res)).unwrap();
+    assert_eq!(body["result"]["data"][499], "metaxy");
+
+    let res = client.send(get("/api/raw_report?input=null")).await;
+    assert_eq!(res.header("content-encoding"), None);
+    assert_eq!(res.data::<Vec<String>>().len(), 500);
+
+    let res = client.query("report", &()).await;
+    assert_eq!(res.header("content-encoding"), None);
+}
+
+#[tokio::test]
+async fn streams_are_compressed_when_accepted() {
+    let client = metaxy::test_client![ticks];
+    let res = client
+        .send(
+            Request::post("/api/ticks")
+                .header("accept-encoding", "gzip")
+                .body("")
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(res.header("content-encoding"), Some("gzip"));
+    assert_eq!(gunzip(&res), "data: 0\n\ndata: 1\n\ndata: 2\n\n");
+}
@@ -0,0 +1,105 @@
+use metaxy::testing::Request;
+use metaxy::{RpcErrorStatus, StreamSender, rpc_mutation, rpc_query, rpc_stream};
+
+#[rpc_query(testable)]
+async fn find(id: u32) -> Result<String, String> {
+    Err(format!("user {id} not found"))
+}
+
+#[derive(Debug)]
+struct Status(u16);
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "status {}", self.0)
+    }
+}
+
+impl RpcErrorStatus for Status {
+    fn status(&self) -> u16 {
+        self.0
+    }
+}
+
+#[rpc_query(testable)]
+async fn fail_with(status: u16) -> Result<(), Status> {
+    Err(Status(status))
+}
+
+#[rpc_mutation(testable, timeout = "1s")]
+async fn hang() {
+    std::future::pending::<()>().await;
+}
+
+#[rpc_stream(testable)]
+async fn feed(from: u32, tx: StreamSender<u32>) -> Result<(), String> {
+    tx.send(from).await.ok();
+    Err("feed closed".to_owned())
+}
+
+#[tokio::test]
+async fn handler_error_is_handler_error() {
+    let client = metaxy::test_client![find];
+    let error = client.query("find", &1).await.error();
+    assert_eq!(error.code, "HANDLER_ERROR");
+    assert_eq!(error.message, "user 1 not found");
+}
+
+#[tokio::test]
+async fn out_of_range_status_is_500() {
+    let client = metaxy::test_client![fail_with];
+    for status in [1000, 200, 302] {
+        let req = Request::get(format!("/api/fail_with?input={status}"))
+            .header("origin", "https://app.example.com")
+            .body(())
+            .unwrap();
+        let res = client.send(req).await;
+        assert_eq!(res.status(), 500);
+        assert_eq!(res.header("access-control-allow-origin"), Some("*"));
+        let error = res.error();
+        assert_eq!(error.code, "HANDLER_ERROR");
+        assert_eq!(error.message, format!("status {status}"));
+    }
+}
+
+#[tokio::test]
+async fn bad_input_is_bad_input() {
+    let client = metaxy::test_client![find];
+    let res = client.query("find", &"one").await;
+    assert_eq!(res.status(), 400);
+    assert_eq!(res.error().code, "BAD_INPUT");
+}
+
+#[tokio::test]
+async fn wrong_method_is_method_not_allowed() {
+    let client = metaxy::test_client![find, feed];
+    let res = client.mutation("find", &1).await;
+    assert_eq!(res.status(), 405);
+    assert_eq!(res.error().code, "METHOD_NOT_ALLOWED");
+
+    let req = Request::get("/api/feed").body(String::new()).unwrap();
+    let res = client.send(req).await;
+    assert_eq!(res.status(), 405);
+    let error = res.error();
+    assert_eq!(error.code, "METHOD_NOT_ALLOWED");
+    assert_eq!(error.message, "Method GET not allowed, expected POST");
+}
+
+#[tokio::test]
+async fn timeout_is_timeout() {
+    let client = metaxy::test_client![hang];
+    let res = client.mutation("hang", &()).await;
+    assert_eq!(res.status(), 504);
+    assert_eq!(res.error().code, "TIMEOUT");
+}
+
+#[tokio::test]
+async fn stream_errors_carry_codes() {
+    let client = metaxy::test_client![feed];
+    let res = client.stream("feed", &1).await;
+    assert_eq!(res.stream_error().code, "HANDLER_ERROR");
+
+    let res = client.stream("feed", &"one").await;
+    assert_eq!(res.status(), 400);
+    assert_eq!(res.error().code, "BAD_INPUT");
+}
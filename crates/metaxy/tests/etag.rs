@@ -0,0 +1,51 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use metaxy::rpc_query;
+use metaxy::testing::Request;
+
+static VERSION: AtomicU32 = AtomicU32::new(1);
+
+#[rpc_query(testable, etag, cache = "private, 1m")]
+async fn stats() -> Vec<u32> {
+    vec![VERSION.load(Ordering::SeqCst); 3]
+}
+
+#[rpc_query(testable)]
+async fn plain() -> u32 {
+    1
+}
+
+fn conditional(uri: &str, etag: &str) -> Request<()> {
+    Request::get(uri)
+        .header("if-none-match", etag)
+        .header("origin", "https://app.example.com")
+        .body(())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn unchanged_response_is_304() {
+    let client = metaxy::test_client![stats, plain];
+    let uri = "/api/stats?input=null";
+
+    let res = client.query("stats", &()).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.data::<Vec<u32>>(), [1, 1, 1]);
+    let etag = res.header("etag").unwrap().to_owned();
+
+    let res = client.send(conditional(uri, &etag)).await;
+    assert_eq!(res.status(), 304);
+    assert_eq!(res.header("etag"), Some(etag.as_str()));
+    assert_eq!(res.header("cache-control"), Some("private, max-age=60"));
+    assert_eq!(res.header("access-control-expose-headers"), Some("ETag"));
+    assert_eq!(res.header("content-type"), None);
+    assert!(res.body().is_empty());
+
+    VERSION.store(2, Ordering::SeqCst);
+    let res = client.send(conditional(uri, &etag)).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.data::<Vec<u32>>(), [2, 2, 2]);
+    assert_ne!(res.header("etag"), Some(etag.as_str()));
+
+    assert_eq!(client.query("plain", &()).await.header("etag"), None);
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "msgpack")]
+
+use metaxy::testing::{Request, TestResponse};
+use metaxy::{rpc_mutation, rpc_query};
+use serde_json::{Value, json};
+
+#[rpc_query(testable)]
+async fn series(len: usize) -> Vec<f64> {
+    (0..len).map(|n| n as f64 / 2.0).collect()
+}
+
+#[rpc_mutation(testable, max_depth = "2")]
+async fn scale(input: Vec<f64>) -> Result<Vec<f64>, String> {
+    if input.is_empty() {
+        return Err("empty".into());
+    }
+    Ok(input.iter().map(|n| n * 2.0).collect())
+}
+
+fn decode(res: &TestResponse) -> Value {
+    assert_eq!(res.header("content-type"), Some("application/msgpack"));
+    rmp_serde::from_slice(res.body()).unwrap()
+}
+
+fn msgpack_mutation(input: &Value) -> Request<Vec<u8>> {
+    Request::post("/api/scale")
+        .header("content-type", "application/msgpack")
+        .header("accept", "application/msgpack")
+        .body(rmp_serde::to_vec_named(input).unwrap())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn query_response_follows_accept() {
+    let client = metaxy::test_client![series, scale];
+
+    let res = client.query("series", &3).await;
+    assert_eq!(res.header("content-type"), Some("application/json"));
+    assert_eq!(res.header("vary"), Some("accept"));
+    assert_eq!(res.data::<Vec<f64>>(), [0.0, 0.5, 1.0]);
+
+    let res = client
+        .send(
+            Request::get("/api/series?input=3")
+                .header("accept", "application/msgpack")
+                .body(())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(decode(&res)["result"]["data"], json!([0.0, 0.5, 1.0]));
+}
+
+#[tokio::test]
+async fn mutation_reads_and_writes_msgpack() {
+    let client = metaxy::test_client![series, scale];
+
+    let res = client.send(msgpack_mutation(&json!([1.5, -2]))).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(decode(&res)["result"]["data"], json!([3.0, -4.0]));
+
+    let res = client.send(msgpack_mutation(&json!([]))).await;
+    assert_eq!(res.status(), 400);
+    assert_eq!(decode(&res)["error"]["message"], "empty");
+
+    let res = client.send(msgpack_mutation(&json!([[[1]]]))).await;
+    assert_eq!(res.status(), 400);
+    assert_eq!(decode(&res)["error"]["code"], "INPUT_TOO_COMPLEX");
+}
+
+#[tokio::test]
+async fn malformed_msgpack_is_400() {
+    let client = metaxy::test_client![series, scale];
+    let res = client
+        .send(
+            Request::post("/api/scale")
+                .header("content-type", "application/msgpack")
+                .body(vec![0xc1])
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(res.status(), 400);
+    assert_eq!(res.json::<Value>()["error"]["code"], "BAD_INPUT");
+}
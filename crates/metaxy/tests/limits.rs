@@ -0,0 +1,71 @@
+use metaxy::{StreamSender, rpc_mutation, rpc_query, rpc_stream};
+use serde_json::{Value, json};
+
+#[rpc_query(testable, max_body = "64b", max_depth = "2")]
+async fn search(filter: Value) -> Value {
+    filter
+}
+
+#[rpc_mutation(testable, max_body = "64b", max_depth = "2")]
+async fn save(input: Value) -> Value {
+    input
+}
+
+#[rpc_stream(testable, max_body = "64b", max_depth = "2")]
+async fn replay(input: Vec<u32>, tx: StreamSender<u32>) {
+    for n in input {
+        tx.send(n).await.ok();
+    }
+}
+
+#[tokio::test]
+async fn input_within_limits_is_accepted() {
+    let client = metaxy::test_client![search, save, replay];
+    let input = json!({ "tags": ["a", "b"] });
+    assert_eq!(client.query("search", &input).await.data::<Value>(), input);
+    assert_eq!(client.mutation("save", &input).await.data::<Value>(), input);
+    assert_eq!(
+        client.stream("replay", &[1, 2]).await.chunks::<u32>(),
+        [1, 2]
+    );
+}
+
+#[tokio::test]
+async fn oversized_input_is_413() {
+    let client = metaxy::test_client![search, save, replay];
+    let input = "x".repeat(100);
+    for res in [
+        client.query("search", &input).await,
+        client.mutation("save", &input).await,
+        client.stream("replay", &input).await,
+    ] {
+        assert_eq!(res.status(), 413);
+        assert_eq!(res.json::<Value>()["error"]["code"], "PAYLOAD_TOO_LARGE");
+    }
+}
+
+#[tokio::test]
+async fn deeply_nested_input_is_400() {
+    let client = metaxy::test_client![search, save, replay];
+    let input = json!([[[1]]]);
+    for res in [
+        client.query("search", &input).await,
+        client.mutation("save", &input).await,
+        client.stream("replay", &input).await,
+    ] {
+        assert_eq!(res.status(), 400);
+        assert_eq!(res.json::<Value>()["error"]["code"], "INPUT_TOO_COMPLEX");
+    }
+}
+
+#[tokio::test]
+async fn malformed_stream_input_is_json_error() {
+    let client = metaxy::test_client![replay];
+    let res = client.stream("replay", &"nope").await;
+    assert_eq!(res.status(), 400);
+    assert!(
+        res.error()
+            .message
+            .starts_with("Failed to deserialize request body")
+    );
+}
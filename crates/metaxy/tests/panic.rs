@@ -0,0 +1,73 @@
+use metaxy::testing::Request;
+use metaxy::{NdjsonSender, StreamSender, rpc_mutation, rpc_query, rpc_stream};
+
+#[rpc_query(testable, cors(origins = "https://app.example.com"))]
+async fn lookup(id: u32) -> String {
+    if id == 0 {
+        panic!("no user {id}");
+    }
+    format!("user {id}")
+}
+
+#[rpc_mutation(testable, timeout = "5s")]
+async fn save(value: Option<u32>) -> u32 {
+    value.expect("value is required")
+}
+
+#[rpc_stream(testable)]
+async fn feed(tx: StreamSender<u32>) {
+    tx.send(1).await.ok();
+    panic!("feed broke");
+}
+
+#[rpc_stream(testable, format = "ndjson")]
+async fn rows(tx: NdjsonSender<u32>) {
+    tx.send(1).await.ok();
+    panic!("cursor broke");
+}
+
+#[tokio::test]
+async fn query_panic_is_a_500_with_cors_headers() {
+    let client = metaxy::test_client![lookup];
+    let req = Request::get("/api/lookup?input=0")
+        .header("origin", "https://app.example.com")
+        .body(String::new())
+        .unwrap();
+    let res = client.send(req).await;
+    assert_eq!(res.status(), 500);
+    assert_eq!(
+        res.header("access-control-allow-origin"),
+        Some("https://app.example.com")
+    );
+    let error = res.error();
+    assert_eq!(error.code, "INTERNAL");
+    assert_eq!(error.message, "Handler panicked: no user 0");
+
+    assert_eq!(client.query("lookup", &1).await.data::<String>(), "user 1");
+}
+
+#[tokio::test]
+async fn mutation_panic_inside_timeout_is_a_500() {
+    let client = metaxy::test_client![save];
+    let res = client.mutation("save", &None::<u32>).await;
+    assert_eq!(res.status(), 500);
+    assert_eq!(res.error().message, "Handler panicked: value is required");
+}
+
+#[tokio::test]
+async fn stream_panic_ends_with_error_event() {
+    let client = metaxy::test_client![feed];
+    let res = client.stream("feed", &()).await;
+    assert_eq!(res.chunks::<u32>(), [1]);
+    let error = res.stream_error();
+    assert_eq!(error.code, "INTERNAL");
+    assert_eq!(error.message, "Handler panicked: feed broke");
+}
+
+#[tokio::test]
+async fn ndjson_panic_aborts_the_body() {
+    let client = metaxy::test_client![rows];
+    let res = client.stream("rows", &()).await;
+    assert_eq!(res.lines::<u32>(), [1]);
+    assert!(res.aborted());
+}
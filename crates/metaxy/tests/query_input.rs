@@ -0,0 +1,64 @@
+use metaxy::rpc_query;
+use metaxy::testing::Request;
+use serde_json::{Value, json};
+
+#[rpc_query(testable, max_body = "1kb")]
+async fn search(filter: Value) -> Value {
+    filter
+}
+
+fn body_query(body: &str, marked: bool) -> Request<String> {
+    let mut req = Request::post("/api/search").header("content-type", "application/json");
+    if marked {
+        req = req.header("x-metaxy-input", "body");
+    }
+    req.body(body.to_owned()).unwrap()
+}
+
+#[tokio::test]
+async fn marked_post_body_is_query_input() {
+    let client = metaxy::test_client![search];
+    let filter = json!({ "tags": ["a", "b"], "q": "x y" });
+
+    let res = client.send(body_query(&filter.to_string(), true)).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.data::<Value>(), filter);
+
+    let res = client.send(body_query(&filter.to_string(), false)).await;
+    assert_eq!(res.status(), 405);
+}
+
+#[tokio::test]
+async fn body_input_keeps_query_limits() {
+    let client = metaxy::test_client![search];
+    let res = client
+        .send(body_query(&json!("x".repeat(2000)).to_string(), true))
+        .await;
+    assert_eq!(res.status(), 413);
+    assert_eq!(res.json::<Value>()["error"]["code"], "PAYLOAD_TOO_LARGE");
+}
+
+#[tokio::test]
+async fn base64url_input_parameter() {
+    let client = metaxy::test_client![search];
+    // {"a":">>>"}
+    let res = client
+        .send(
+            Request::get("/api/search?input64=eyJhIjoiPj4-In0")
+                .body(())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.data::<Value>(), json!({ "a": ">>>" }));
+
+    let res = client
+        .send(
+            Request::get("/api/search?input64=not*base64")
+                .body(())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(res.status(), 400);
+    assert_eq!(res.json::<Value>()["error"]["code"], "BAD_INPUT");
+}
@@ -0,0 +1,202 @@
+use metaxy::testing::Request;
+use metaxy::{
+    Guarded, RateLimitKey, RequestParts, StreamSender, rpc_mutation, rpc_query, rpc_stream,
+};
+use serde_json::Value;
+
+#[rpc_mutation(testable, rate_limit = "2/m")]
+async fn login(name: String) -> String {
+    name
+}
+
+#[rpc_mutation(testable, rate_limit = "1/h")]
+async fn signup(name: String) -> String {
+    name
+}
+
+#[rpc_query(testable, rate_limit = "1/h", key = "header:x-api-key")]
+async fn quota() -> u32 {
+    1
+}
+
+struct User(String);
+
+impl RateLimitKey for User {
+    fn rate_limit_key(&self) -> String {
+        self.0.clone()
+    }
+}
+
+#[derive(Debug)]
+struct Unauthorized;
+
+impl std::fmt::Display for Unauthorized {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("unauthorized")
+    }
+}
+
+async fn require_user(parts: &RequestParts) -> Result<User, Unauthorized> {
+    parts
+        .headers
+        .get("x-user")
+        .and_then(|v| v.to_str().ok())
+        .map(|user| User(user.to_owned()))
+        .ok_or(Unauthorized)
+}
+
+#[rpc_query(testable, guard = "require_user", rate_limit = "1/h", key = "guard")]
+async fn report(user: Guarded<User>) -> String {
+    user.into_inner().0
+}
+
+#[rpc_stream(testable, rate_limit = "1/h")]
+async fn feed(tx: StreamSender<u32>) {
+    tx.send(1).await.ok();
+}
+
+#[rpc_stream(testable, rate_limit = "1/h")]
+async fn alerts(tx: StreamSender<u32>) {
+    tx.send(1).await.ok();
+}
+
+fn with_header(uri: &str, name: &str, value: &str) -> Request<()> {
+    Request::get(uri).header(name, value).body(()).unwrap()
+}
+
+/// A `POST` with a JSON string body from the client at `ip`.
+fn post_from(uri: &str, ip: &str) -> Request<&'static str> {
+    Request::post(uri)
+        .header("x-forwarded-for", ip)
+        .header("origin", "https://app.example.com")
+        .header("content-type", "application/json")
+        .body(r#""ada""#)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn requests_over_the_limit_are_429() {
+    let client = metaxy::test_client![login];
+    for _ in 0..2 {
+        let res = client.send(post_from("/api/login", "10.0.0.1")).await;
+        assert_eq!(res.status(), 200);
+    }
+
+    let res = client.send(post_from("/api/login", "10.0.0.1")).await;
+    assert_eq!(res.status(), 429);
+    assert_eq!(res.header("retry-after"), Some("30"));
+    assert_eq!(res.json::<Value>()["error"]["code"], "RATE_LIMITED");
+
+    let res = client.send(post_from("/api/login", "10.0.0.2")).await;
+    assert_eq!(res.status(), 200);
+}
+
+#[tokio::test]
+async fn requests_without_a_client_ip_are_not_limited() {
+    let client = metaxy::test_client![login];
+    for _ in 0..3 {
+        assert_eq!(client.mutation("login", &"ada").await.status(), 200);
+    }
+}
+
+#[tokio::test]
+async fn retry_after_is_exposed_to_allowed_origins() {
+    let client = metaxy::test_client![signup, alerts];
+    let post = |uri: &str| post_from(uri, "10.0.0.3");
+    let res = client.send(post("/api/signup")).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.header("access-control-expose-headers"), None);
+
+    let res = client.send(post("/api/signup")).await;
+    assert_eq!(res.status(), 429);
+    assert_eq!(res.header("access-control-allow-origin"), Some("*"));
+    assert_eq!(
+        res.header("access-control-expose-headers"),
+        Some("Retry-After")
+    );
+
+    assert_eq!(client.send(post("/api/alerts")).await.status(), 200);
+    let res = client.send(post("/api/alerts")).await;
+    assert_eq!(res.status(), 429);
+    assert_eq!(
+        res.header("access-control-expose-headers"),
+        Some("Retry-After")
+    );
+}
+
+#[tokio::test]
+async fn header_key_limits_each_value_separately() {
+    let client = metaxy::test_client![quota];
+    let uri = "/api/quota?input=null";
+    assert_eq!(
+        client
+            .send(with_header(uri, "x-api-key", "a"))
+            .await
+            .status(),
+        200
+    );
+    assert_eq!(
+        client
+            .send(with_header(uri, "x-api-key", "b"))
+            .await
+            .status(),
+        200
+    );
+    assert_eq!(
+        client
+            .send(with_header(uri, "x-api-key", "a"))
+            .await
+            .status(),
+        429
+    );
+}
+
+#[tokio::test]
+async fn missing_key_header_is_bad_input() {
+    let client = metaxy::test_client![quota];
+    for _ in 0..2 {
+        let res = client.query("quota", &()).await;
+        assert_eq!(res.status(), 400);
+        assert_eq!(res.error().code, "BAD_INPUT");
+        assert_eq!(res.error().message, "Missing `x-api-key` header");
+    }
+}
+
+#[tokio::test]
+async fn guard_key_limits_each_identity_and_runs_after_the_guard() {
+    let client = metaxy::test_client![report];
+    let uri = "/api/report?input=null";
+    assert_eq!(client.query("report", &()).await.status(), 400);
+    assert_eq!(
+        client
+            .send(with_header(uri, "x-user", "ada"))
+            .await
+            .status(),
+        200
+    );
+    assert_eq!(
+        client
+            .send(with_header(uri, "x-user", "bob"))
+            .await
+            .status(),
+        200
+    );
+    assert_eq!(
+        client
+            .send(with_header(uri, "x-user", "ada"))
+            .await
+            .status(),
+        429
+    );
+}
+
+#[tokio::test]
+async fn streams_are_rate_limited_before_starting() {
+    let client = metaxy::test_client![feed];
+    let res = client.send(post_from("/api/feed", "10.0.0.4")).await;
+    assert_eq!(res.chunks::<u32>(), [1]);
+
+    let res = client.send(post_from("/api/feed", "10.0.0.4")).await;
+    assert_eq!(res.status(), 429);
+    assert!(res.header("retry-after").is_some());
+}
@@ -0,0 +1,99 @@
+use metaxy::{RpcErrorStatus, StreamSender, rpc_stream};
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind")]
+enum ImportError {
+    BadRow { row: usize },
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BadRow { row } => write!(f, "row {row} is invalid"),
+        }
+    }
+}
+
+impl RpcErrorStatus for ImportError {
+    fn status(&self) -> u16 {
+        422
+    }
+}
+
+#[derive(Serialize)]
+struct Summary {
+    imported: usize,
+}
+
+#[rpc_stream(testable)]
+async fn import(rows: Vec<i32>, tx: StreamSender<usize>) -> Result<Summary, ImportError> {
+    for (i, row) in rows.iter().enumerate() {
+        if *row < 0 {
+            return Err(ImportError::BadRow { row: i });
+        }
+        tx.send(i + 1).await.ok();
+    }
+    Ok(Summary {
+        imported: rows.len(),
+    })
+}
+
+#[rpc_stream(testable)]
+async fn total(rows: Vec<i32>, tx: StreamSender<i32>) -> i32 {
+    for row in &rows {
+        tx.send(*row).await.ok();
+    }
+    rows.iter().sum()
+}
+
+#[rpc_stream(testable, timeout = "1s", heartbeat = "1s")]
+async fn stalled(tx: StreamSender<u32>) {
+    tx.send(1).await.ok();
+    std::future::pending::<()>().await;
+}
+
+#[tokio::test]
+async fn ok_result_ends_with_done_event() {
+    let client = metaxy::test_client![import];
+    let res = client.stream("import", &[3, 4]).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.chunks::<usize>(), [1, 2]);
+    assert_eq!(res.done::<serde_json::Value>(), json!({ "imported": 2 }));
+    assert_eq!(res.events().last().unwrap().event.as_deref(), Some("done"));
+}
+
+#[tokio::test]
+async fn err_result_ends_with_typed_error_event() {
+    let client = metaxy::test_client![import];
+    let res = client.stream("import", &[3, -1]).await;
+    assert_eq!(res.chunks::<usize>(), [1]);
+
+    let error = res.stream_error();
+    assert_eq!(error.message, "row 1 is invalid");
+    assert_eq!(error.data, Some(json!({ "kind": "BadRow", "row": 1 })));
+
+    let event = res.events().pop().unwrap();
+    let payload: serde_json::Value = serde_json::from_str(&event.data).unwrap();
+    assert_eq!(payload["status"], 422);
+}
+
+#[tokio::test]
+async fn plain_return_value_is_done() {
+    let client = metaxy::test_client![total];
+    let res = client.stream("total", &[1, 2, 3]).await;
+    assert_eq!(res.chunks::<i32>(), [1, 2, 3]);
+    assert_eq!(res.done::<i32>(), 6);
+}
+
+#[tokio::test]
+async fn timeout_is_a_504_error_event() {
+    let client = metaxy::test_client![stalled];
+    let res = client.stream("stalled", &()).await;
+    assert_eq!(res.chunks::<u32>(), [1]);
+    assert_eq!(res.stream_error().message, "Handler timed out");
+    let event = res.events().pop().unwrap();
+    let payload: serde_json::Value = serde_json::from_str(&event.data).unwrap();
+    assert_eq!(payload["status"], 504);
+}
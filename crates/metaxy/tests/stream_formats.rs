@@ -0,0 +1,65 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use metaxy::{ByteSender, NdjsonSender, Stream, rpc_stream};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Order {
+    id: u32,
+}
+
+#[rpc_stream(testable, format = "ndjson")]
+async fn orders(count: u32, tx: NdjsonSender<Order>) {
+    for id in 1..=count {
+        tx.send(Order { id }).await.ok();
+    }
+}
+
+#[rpc_stream(testable, format = "bytes")]
+async fn report(tx: ByteSender) {
+    tx.send("date,total\n").await.ok();
+    tx.send(b"2026-01-01,3\n".to_vec()).await.ok();
+}
+
+struct Rows(std::vec::IntoIter<Result<Order, String>>);
+
+impl Stream for Rows {
+    type Item = Result<Order, String>;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.0.next())
+    }
+}
+
+#[rpc_stream(testable, format = "ndjson")]
+async fn broken() -> impl Stream<Item = Result<Order, String>> {
+    Rows(vec![Ok(Order { id: 1 }), Err("cursor lost".to_owned())].into_iter())
+}
+
+#[tokio::test]
+async fn ndjson_stream_writes_one_value_per_line() {
+    let client = metaxy::test_client![orders];
+    let res = client.stream("orders", &2).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.header("content-type"), Some("application/x-ndjson"));
+    assert_eq!(res.text(), "{\"id\":1}\n{\"id\":2}\n");
+    assert_eq!(res.lines::<Order>(), [Order { id: 1 }, Order { id: 2 }]);
+    assert!(!res.aborted());
+}
+
+#[tokio::test]
+async fn bytes_stream_writes_raw_chunks() {
+    let client = metaxy::test_client![report];
+    let res = client.stream("report", &()).await;
+    assert_eq!(res.header("content-type"), Some("application/octet-stream"));
+    assert_eq!(res.body(), b"date,total\n2026-01-01,3\n");
+}
+
+#[tokio::test]
+async fn err_item_aborts_ndjson_body() {
+    let client = metaxy::test_client![broken];
+    let res = client.stream("broken", &()).await;
+    assert_eq!(res.lines::<Order>(), [Order { id: 1 }]);
+    assert!(res.aborted());
+}
@@ -0,0 +1,58 @@
+use metaxy::{StreamSender, rpc_stream};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Range {
+    from: u32,
+    to: u32,
+}
+
+#[rpc_stream(testable, method = "GET")]
+async fn ticks(range: Range, tx: StreamSender<u32>) {
+    for n in range.from..=range.to {
+        tx.send(n).await.ok();
+    }
+}
+
+#[rpc_stream(testable, method = "get")]
+async fn clock(tx: StreamSender<&'static str>) {
+    tx.send("tick").await.ok();
+}
+
+#[tokio::test]
+async fn get_stream_reads_query_input() {
+    let client = metaxy::test_client![ticks];
+    let res = client
+        .query("ticks", &serde_json::json!({ "from": 2, "to": 4 }))
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.header("content-type"), Some("text/event-stream"));
+    assert_eq!(res.chunks::<u32>(), [2, 3, 4]);
+}
+
+#[tokio::test]
+async fn get_stream_ends_with_done_event() {
+    let client = metaxy::test_client![clock];
+    let res = client.query("clock", &()).await;
+    assert_eq!(res.chunks::<String>(), ["tick"]);
+    res.done::<()>();
+}
+
+#[tokio::test]
+async fn get_stream_rejects_post() {
+    let client = metaxy::test_client![clock];
+    let res = client.stream("clock", &()).await;
+    assert_eq!(res.status(), 405);
+}
+
+#[tokio::test]
+async fn get_stream_rejects_bad_input() {
+    let client = metaxy::test_client![ticks];
+    let res = client.query("ticks", &"oops").await;
+    assert_eq!(res.status(), 400);
+    assert!(
+        res.error()
+            .message
+            .starts_with("Failed to deserialize input")
+    );
+}
@@ -0,0 +1,52 @@
+use metaxy::testing::Request;
+use metaxy::{Event, LastEventId, StreamSender, rpc_stream};
+
+#[rpc_stream(testable)]
+async fn feed(total: u64, last: LastEventId, tx: StreamSender<u64>) {
+    for n in last.as_u64().unwrap_or(0) + 1..=total {
+        tx.send_event(Event::new(n)).await.ok();
+    }
+    tx.send_event(Event::new(total).name("summary").id("end"))
+        .await
+        .ok();
+}
+
+fn resume(last_event_id: &str) -> Request<String> {
+    Request::post("/api/feed")
+        .header("content-type", "application/json")
+        .header("last-event-id", last_event_id)
+        .body("4".to_owned())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn events_are_numbered_from_one() {
+    let client = metaxy::test_client![feed];
+    let res = client.stream("feed", &2).await;
+    assert_eq!(res.status(), 200);
+
+    let events = res.events();
+    let ids: Vec<_> = events.iter().map(|e| e.id.as_deref()).collect();
+    assert_eq!(ids, [Some("1"), Some("2"), Some("end")]);
+    assert_eq!(events[2].event.as_deref(), Some("summary"));
+    assert_eq!(res.chunks::<u64>(), [1, 2]);
+}
+
+#[tokio::test]
+async fn resumed_stream_continues_after_last_event_id() {
+    let client = metaxy::test_client![feed];
+    let res = client.send(resume("2")).await;
+    assert_eq!(res.status(), 200);
+
+    let ids: Vec<_> = res.events().into_iter().filter_map(|e| e.id).collect();
+    assert_eq!(ids, ["3", "4", "end"]);
+    assert_eq!(res.chunks::<u64>(), [3, 4]);
+}
+
+#[tokio::test]
+async fn non_numeric_last_event_id_restarts_the_sequence() {
+    let client = metaxy::test_client![feed];
+    let res = client.send(resume("end")).await;
+    assert_eq!(res.chunks::<u64>(), [1, 2, 3, 4]);
+    assert_eq!(res.events()[0].id.as_deref(), Some("1"));
+}
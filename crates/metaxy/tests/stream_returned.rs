@@ -0,0 +1,77 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use metaxy::{RpcErrorStatus, Stream, rpc_stream};
+use serde::Serialize;
+use serde_json::json;
+
+/// A `Stream` over an iterator, standing in for a DB cursor or upstream feed.
+struct Iter<I>(I);
+
+impl<I: Iterator + Unpin> Stream for Iter<I> {
+    type Item = I::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<I::Item>> {
+        Poll::Ready(self.0.next())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RowError {
+    row: u32,
+}
+
+impl std::fmt::Display for RowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "row {} is unreadable", self.row)
+    }
+}
+
+impl RpcErrorStatus for RowError {
+    fn status(&self) -> u16 {
+        500
+    }
+}
+
+#[rpc_stream(testable)]
+async fn count(to: u32) -> impl Stream<Item = u32> {
+    Iter(1..=to)
+}
+
+#[rpc_stream(testable)]
+async fn rows(broken: u32) -> impl Stream<Item = Result<u32, RowError>> {
+    Iter((1..=5).map(move |row| {
+        if row == broken {
+            Err(RowError { row })
+        } else {
+            Ok(row)
+        }
+    }))
+}
+
+#[tokio::test]
+async fn every_item_is_a_chunk() {
+    let client = metaxy::test_client![count];
+    let res = client.stream("count", &3).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.chunks::<u32>(), [1, 2, 3]);
+    assert!(res.events().iter().all(|e| e.event.is_none()));
+}
+
+#[tokio::test]
+async fn ok_items_are_unwrapped() {
+    let client = metaxy::test_client![rows];
+    let res = client.stream("rows", &0).await;
+    assert_eq!(res.chunks::<u32>(), [1, 2, 3, 4, 5]);
+}
+
+#[tokio::test]
+async fn first_err_item_ends_the_stream() {
+    let client = metaxy::test_client![rows];
+    let res = client.stream("rows", &3).await;
+    assert_eq!(res.chunks::<u32>(), [1, 2]);
+
+    let error = res.stream_error();
+    assert_eq!(error.message, "row 3 is unreadable");
+    assert_eq!(error.data, Some(json!({ "row": 3 })));
+}
@@ -0,0 +1,162 @@
+use metaxy::testing::Request;
+use metaxy::{
+    Headers, RpcErrorStatus, StreamSender, Validate, ValidationErrors, rpc_mutation, rpc_query,
+    rpc_stream,
+};
+use serde::{Deserialize, Serialize};
+
+struct AppState {
+    greeting: String,
+}
+
+async fn setup() -> AppState {
+    AppState {
+        greeting: "Hello".into(),
+    }
+}
+
+#[rpc_query(testable, init = "setup")]
+async fn greet(name: String, state: &AppState) -> String {
+    format!("{}, {name}!", state.greeting)
+}
+
+#[rpc_query(testable)]
+async fn whoami(headers: Headers) -> Option<String> {
+    headers
+        .get("x-user")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned)
+}
+
+#[derive(Deserialize)]
+struct Signup {
+    email: String,
+}
+
+impl Validate for Signup {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if !self.email.contains('@') {
+            errors.add("email", "must be a valid email address");
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Debug, Serialize)]
+enum SignupError {
+    Taken,
+}
+
+impl std::fmt::Display for SignupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("email already taken")
+    }
+}
+
+impl RpcErrorStatus for SignupError {
+    fn status(&self) -> u16 {
+        409
+    }
+}
+
+#[rpc_mutation(testable, validate)]
+async fn signup(input: Signup) -> Result<u32, SignupError> {
+    if input.email == "taken@example.com" {
+        Err(SignupError::Taken)
+    } else {
+        Ok(1)
+    }
+}
+
+#[rpc_stream(testable)]
+async fn countdown(from: u32, tx: StreamSender<u32>) {
+    for n in (0..=from).rev() {
+        tx.send(n).await.ok();
+    }
+}
+
+#[rpc_query(router)]
+async fn ping() -> &'static str {
+    "pong"
+}
+
+#[tokio::test]
+async fn query_runs_init_when_no_state_is_injected() {
+    let client = metaxy::test_client![greet];
+    let res = client.query("greet", &"Ada").await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.header("content-type"), Some("application/json"));
+    assert_eq!(res.data::<String>(), "Hello, Ada!");
+}
+
+#[tokio::test]
+async fn injected_state_replaces_init() {
+    let hi = metaxy::test_client![
+        greet = AppState {
+            greeting: "Hi".into()
+        }
+    ];
+    let yo = metaxy::test_client![
+        greet = AppState {
+            greeting: "Yo".into()
+        }
+    ];
+    assert_eq!(hi.query("greet", &"Ada").await.data::<String>(), "Hi, Ada!");
+    assert_eq!(yo.query("greet", &"Ada").await.data::<String>(), "Yo, Ada!");
+}
+
+#[tokio::test]
+async fn send_passes_request_headers() {
+    let client = metaxy::test_client![whoami];
+    let res = client
+        .send(
+            Request::get("/api/whoami")
+                .header("x-user", "ada")
+                .body(())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(res.data::<Option<String>>().as_deref(), Some("ada"));
+}
+
+#[tokio::test]
+async fn mutation_errors_are_readable() {
+    let client = metaxy::test_client![signup];
+
+    let res = client
+        .mutation("signup", &serde_json::json!({ "email": "ada" }))
+        .await;
+    assert_eq!(res.status(), 422);
+    assert_eq!(res.error().code, "VALIDATION_FAILED");
+    assert_eq!(res.error().fields[0].path, "email");
+
+    let res = client
+        .mutation(
+            "signup",
+            &serde_json::json!({ "email": "taken@example.com" }),
+        )
+        .await;
+    assert_eq!(res.status(), 409);
+    let error = res.error();
+    assert_eq!(error.message, "email already taken");
+    assert_eq!(error.data, Some(serde_json::json!("Taken")));
+
+    let res = client.query("signup", &()).await;
+    assert_eq!(res.status(), 405);
+}
+
+#[tokio::test]
+async fn stream_chunks_are_collected() {
+    let client = metaxy::test_client![countdown];
+    let res = client.stream("countdown", &3).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.chunks::<u32>(), [3, 2, 1, 0]);
+}
+
+#[tokio::test]
+async fn router_handlers_are_testable() {
+    let client = metaxy::test_client![ping, greet];
+    assert_eq!(client.query("ping", &()).await.data::<String>(), "pong");
+    assert_eq!(client.query("nope", &()).await.status(), 404);
+}
@@ -169,12 +169,12 @@ export type Procedures = {
     hello: { input: string; output: string };
     init_demo: { input: void; output: InitDemoResponse };
     /** Perform a math operation. Returns an error on division by zero. */
-    math: { input: MathInput; output: MathResult };
+    math: { input: MathInput; output: MathResult; error: string };
     /**
      * Access a protected secret.
      * Requires a valid Bearer token in the Authorization header.
      */
-    secret: { input: void; output: string };
+    secret: { input: void; output: string; error: string };
     /** Returns the current server time as a Unix timestamp. */
     time: { input: void; output: TimeResponse };
     timeout_demo: { input: TimeoutDemoInput; output: TimeoutDemoResponse };
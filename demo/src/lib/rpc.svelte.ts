@@ -11,12 +11,16 @@ export type { RpcClient, CallOptions, Procedures, BigIntDemoResponse, BigIntDemo
 type QueryKey = keyof Procedures["queries"];
 type QueryInput<K extends QueryKey> = Procedures["queries"][K]["input"];
 type QueryOutput<K extends QueryKey> = Procedures["queries"][K]["output"];
+type QueryError<K extends QueryKey> = Procedures["queries"][K] extends { error: infer E } ? E : never;
 type MutationKey = keyof Procedures["mutations"];
 type MutationInput<K extends MutationKey> = Procedures["mutations"][K]["input"];
 type MutationOutput<K extends MutationKey> = Procedures["mutations"][K]["output"];
+type MutationError<K extends MutationKey> = Procedures["mutations"][K] extends { error: infer E } ? E : never;
 type StreamKey = keyof Procedures["streams"];
 type StreamInput<K extends StreamKey> = Procedures["streams"][K]["input"];
 type StreamOutput<K extends StreamKey> = Procedures["streams"][K]["output"];
+type StreamReturn<K extends StreamKey> = Procedures["streams"][K] extends { result: infer R } ? R : void;
+type StreamError<K extends StreamKey> = Procedures["streams"][K] extends { error: infer E } ? E : never;
 
 type VoidQueryKey = "bigint_demo" | "cached_time" | "cached_time_private" | "cached_time_stale" | "cookie_demo" | "dedup_demo" | "init_demo" | "secret" | "time" | "types";
 type NonVoidQueryKey = "hello" | "math" | "timeout_demo";
@@ -46,7 +50,7 @@ export interface QueryOptions<K extends QueryKey> {
   onSuccess?: (data: QueryOutput<K>) => void;
 
   /** Called when the query fails. */
-  onError?: (error: RpcError) => void;
+  onError?: (error: RpcError<QueryError<K>>) => void;
 
   /** Called when the query settles (success or failure). */
   onSettled?: () => void;
@@ -59,7 +63,7 @@ export interface QueryResult<K extends QueryKey> {
   readonly data: QueryOutput<K> | undefined;
 
   /** The error from the most recent failed fetch, cleared on next attempt. */
-  readonly error: RpcError | undefined;
+  readonly error: RpcError<QueryError<K>> | undefined;
 
   /** Current status of the query. Derived: loading > error > success > idle. */
   readonly status: QueryStatus;
@@ -88,7 +92,7 @@ export interface MutationOptions<K extends MutationKey> {
   onSuccess?: (data: MutationOutput<K>) => void;
 
   /** Called when the mutation fails. */
-  onError?: (error: RpcError) => void;
+  onError?: (error: RpcError<MutationError<K>>) => void;
 
   /** Called when the mutation settles (success or failure). */
   onSettled?: () => void;
@@ -105,7 +109,7 @@ export interface MutationResult<K extends MutationKey> {
   readonly data: MutationOutput<K> | undefined;
 
   /** The error from the most recent failed mutation, cleared on next attempt. */
-  readonly error: RpcError | undefined;
+  readonly error: RpcError<MutationError<K>> | undefined;
 
   /** True while a mutation is in-flight. */
   readonly isLoading: boolean;
@@ -127,11 +131,11 @@ export interface StreamOptions<K extends StreamKey> {
   /** Called for each chunk received from the stream. */
   onChunk?: (chunk: StreamOutput<K>) => void;
 
-  /** Called when the stream completes successfully. */
-  onDone?: () => void;
+  /** Called with the handler's final value when the stream completes successfully. */
+  onDone?: (result: StreamReturn<K>) => void;
 
   /** Called when the stream encounters an error. */
-  onError?: (error: RpcError) => void;
+  onError?: (error: RpcError<StreamError<K>>) => void;
 }
 
 export interface StreamResult<K extends StreamKey> {
@@ -139,7 +143,10 @@ export interface StreamResult<K extends StreamKey> {
   readonly chunks: StreamOutput<K>[];
 
   /** The error from the stream, if any. */
-  readonly error: RpcError | undefined;
+  readonly error: RpcError<StreamError<K>> | undefined;
+
+  /** The final value returned by the handler, once the stream is done. */
+  readonly result: StreamReturn<K> | undefined;
 
   /** True while the stream is active. */
   readonly isStreaming: boolean;
@@ -203,7 +210,7 @@ export function createQuery<K extends QueryKey>(
   }
 
   let data = $state<QueryOutput<K> | undefined>(resolveOptions()?.placeholderData);
-  let error = $state<RpcError | undefined>();
+  let error = $state<RpcError<QueryError<K>> | undefined>();
   let hasFetched = $state(false);
   let loading = $state(false);
 
@@ -228,7 +235,7 @@ export function createQuery<K extends QueryKey>(
       opts?.onSuccess?.(result);
     } catch (e) {
       if (gen !== generation) return;
-      error = e as RpcError;
+      error = e as RpcError<QueryError<K>>;
       opts?.onError?.(error);
     } finally {
       if (gen === generation) {
@@ -311,7 +318,7 @@ export function createMutation<K extends MutationKey>(
   options?: MutationOptions<K>,
 ): MutationResult<K> {
   let data = $state<MutationOutput<K> | undefined>();
-  let error = $state<RpcError | undefined>();
+  let error = $state<RpcError<MutationError<K>> | undefined>();
   let loading = $state(false);
   let hasSucceeded = $state(false);
 
@@ -329,7 +336,7 @@ export function createMutation<K extends MutationKey>(
       options?.onSuccess?.(result);
       return result;
     } catch (e) {
-      error = e as RpcError;
+      error = e as RpcError<MutationError<K>>;
       options?.onError?.(error);
       throw e;
     } finally {
@@ -374,7 +381,8 @@ export function createStream<K extends StreamKey>(
   }
 
   let chunks = $state<StreamOutput<K>[]>([]);
-  let error = $state<RpcError | undefined>();
+  let error = $state<RpcError<StreamError<K>> | undefined>();
+  let result = $state<StreamReturn<K> | undefined>();
   let streaming = $state(false);
   let done = $state(false);
   let controller: AbortController | undefined;
@@ -384,6 +392,7 @@ export function createStream<K extends StreamKey>(
     controller = new AbortController();
     chunks = [];
     error = undefined;
+    result = undefined;
     streaming = true;
     done = false;
 
@@ -396,18 +405,26 @@ export function createStream<K extends StreamKey>(
       const mergedCallOptions = { ...options?.callOptions, signal: mergedSignal };
       callArgs.push(mergedCallOptions);
       const gen = (client.stream as (...a: unknown[]) => AsyncGenerator<unknown>)(...callArgs);
-      for await (const chunk of gen) {
-        if (controller.signal.aborted) break;
+      // Iterated by hand to keep the value the handler returns in its `done` event.
+      let next = await gen.next();
+      while (!next.done) {
+        if (controller.signal.aborted) {
+          await gen.return(undefined);
+          break;
+        }
+        const chunk = next.value;
         chunks = [...chunks, chunk as StreamOutput<K>];
         options?.onChunk?.(chunk as StreamOutput<K>);
+        next = await gen.next();
       }
       if (!controller.signal.aborted) {
+        result = next.value as StreamReturn<K>;
         done = true;
-        options?.onDone?.();
+        options?.onDone?.(result);
       }
     } catch (e) {
       if (!controller.signal.aborted) {
-        error = e as RpcError;
+        error = e as RpcError<StreamError<K>>;
         options?.onError?.(error);
       }
     } finally {
@@ -418,6 +435,7 @@ export function createStream<K extends StreamKey>(
   return {
     get chunks() { return chunks; },
     get error() { return error; },
+    get result() { return result; },
     get isStreaming() { return streaming; },
     get isDone() { return done; },
     start: () => { void run(); },